@@ -0,0 +1,72 @@
+use std::{path::Path, process::Command};
+
+use cargo_smart_release::{command::changelog::commit_history, Context};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// The amount of commits in the history, of which every third one changes the crate.
+const NUM_COMMITS: usize = 500;
+
+fn git(root: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=a",
+            "-c",
+            "user.email=a@example.com",
+            "-c",
+            "commit.gpgsign=false",
+        ])
+        .args(args)
+        .current_dir(root)
+        .status()
+        .expect("git can be launched");
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    std::fs::create_dir_all(path.parent().expect("files are in directories")).expect("creatable");
+    std::fs::write(path, content).expect("writable");
+}
+
+/// Create a workspace whose only crate is in the nested directory `crates/nested/a`, with a history in which most
+/// commits change other top-level directories.
+fn workspace_with_nested_crate(root: &Path) {
+    std::fs::remove_dir_all(root).ok();
+    write(root, "Cargo.toml", "[workspace]\nmembers = [\"crates/nested/a\"]\n");
+    write(
+        root,
+        "crates/nested/a/Cargo.toml",
+        "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2018\"\n",
+    );
+    write(root, ".gitignore", "target\nCargo.lock\n");
+    git(root, &["init", "-q"]);
+    for index in 0..NUM_COMMITS {
+        match index % 3 {
+            0 => write(root, "crates/nested/a/src/lib.rs", &format!("// {}\n", index)),
+            _ => write(root, "docs/notes.md", &format!("{}\n", index)),
+        }
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "-q", "-m", &format!("fix: change {}", index)]);
+    }
+}
+
+fn path_lookup_threshold(c: &mut Criterion) {
+    let root = std::env::temp_dir().join(format!("smart-release-bench-{}", std::process::id()));
+    workspace_with_nested_crate(&root);
+    std::env::set_current_dir(&root).expect("the workspace exists");
+    let mut ctx = Context::new(vec!["a".into()]).expect("valid workspace");
+
+    let mut group = c.benchmark_group("commit_history of a crate in a nested directory");
+    for (name, threshold) in [("reused entries", 1), ("looked up entries", usize::MAX)] {
+        ctx.config.changelog.path_lookup_threshold = Some(threshold);
+        group.bench_function(BenchmarkId::new(name, NUM_COMMITS), |b| {
+            b.iter(|| black_box(commit_history(&ctx, Default::default(), &[]).expect("traversable")))
+        });
+    }
+    group.finish();
+    std::fs::remove_dir_all(&root).ok();
+}
+
+criterion_group!(benches, path_lookup_threshold);
+criterion_main!(benches);
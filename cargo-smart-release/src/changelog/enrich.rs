@@ -0,0 +1,74 @@
+//! Programmatic post-processing of changelogs after they were generated from the commit history and before they are rendered.
+use super::ChangeLog;
+
+/// A step to alter the changelog of a crate before it is rendered, like adding links to an issue tracker, reordering
+/// entries or dropping internal notes.
+pub trait ChangelogEnricher {
+    /// Alter `log`, the changelog of the crate named `crate_name`, in place.
+    fn enrich(&self, crate_name: &str, log: &mut ChangeLog) -> anyhow::Result<()>;
+}
+
+/// Drop all entries whose title matches `pattern`.
+#[derive(Debug, Clone)]
+pub struct DropEntries {
+    pub pattern: regex::Regex,
+}
+
+impl ChangelogEnricher for DropEntries {
+    fn enrich(&self, _crate_name: &str, log: &mut ChangeLog) -> anyhow::Result<()> {
+        for section in &mut log.sections {
+            section.entries.retain(|entry| !self.pattern.is_match(&entry.title));
+        }
+        Ok(())
+    }
+}
+
+/// Apply all `enrichers` to `log` in order.
+pub fn apply(enrichers: &[Box<dyn ChangelogEnricher>], crate_name: &str, log: &mut ChangeLog) -> anyhow::Result<()> {
+    for enricher in enrichers {
+        enricher.enrich(crate_name, log)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::{Entry, Section, Version};
+
+    #[test]
+    fn enrichers_are_applied_in_order() {
+        struct Uppercase;
+        impl ChangelogEnricher for Uppercase {
+            fn enrich(&self, _crate_name: &str, log: &mut ChangeLog) -> anyhow::Result<()> {
+                for entry in log.sections.iter_mut().flat_map(|s| s.entries.iter_mut()) {
+                    entry.title = entry.title.to_uppercase();
+                }
+                Ok(())
+            }
+        }
+        let entry = |title: &str| Entry {
+            kind: Some("fix"),
+            title: title.into(),
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let mut log = ChangeLog {
+            sections: vec![Section {
+                version: Version::Unreleased,
+                date: None,
+                entries: vec![entry("public"), entry("internal: note")],
+            }],
+        };
+        let enrichers: Vec<Box<dyn ChangelogEnricher>> = vec![
+            Box::new(Uppercase),
+            Box::new(DropEntries {
+                pattern: regex::Regex::new("^INTERNAL").unwrap(),
+            }),
+        ];
+        apply(&enrichers, "a", &mut log).unwrap();
+        let titles: Vec<_> = log.sections[0].entries.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["PUBLIC"]);
+    }
+}
@@ -0,0 +1,175 @@
+//! Generation of links to issues, pull requests, commits and comparisons on the forge hosting the repository.
+use git_repository::bstr::ByteSlice;
+use serde::Deserialize;
+
+/// The kind of software hosting a repository, which determines the shape of the paths to link to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Forge {
+    GitHub,
+    GitLab,
+    /// Gitea and its fork Forgejo, which share the same paths.
+    Gitea,
+}
+
+impl Forge {
+    /// Classify the forge by its well-known `host` name, or return `None` if it can't be determined.
+    pub fn from_host(host: &str) -> Option<Self> {
+        let host = host.to_ascii_lowercase();
+        if host.contains("github") {
+            Some(Forge::GitHub)
+        } else if host.contains("gitlab") {
+            Some(Forge::GitLab)
+        } else if host.contains("gitea") || host.contains("forgejo") || host == "codeberg.org" {
+            Some(Forge::Gitea)
+        } else {
+            None
+        }
+    }
+}
+
+/// Produces links into a repository hosted on a [`Forge`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Links {
+    /// The forge hosting the repository.
+    pub forge: Forge,
+    /// The URL of the repository's web page, like `https://github.com/Byron/gitoxide`, without trailing slash.
+    pub base_url: String,
+}
+
+impl Links {
+    /// Create links for the repository at the remote `url`, using `forge` if set or deriving it from the host otherwise.
+    ///
+    /// Return `None` if `url` has no host or if the forge can't be determined.
+    pub fn from_remote_url(url: &git_url::Url, forge: Option<Forge>) -> Option<Self> {
+        let host = url.host.as_deref()?;
+        let forge = forge.or_else(|| Forge::from_host(host))?;
+        let path = url.path.to_str().ok()?.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        let base_url = match (url.scheme, url.port) {
+            (git_url::Scheme::Http, Some(port)) => format!("http://{}:{}/{}", host, port, path),
+            (git_url::Scheme::Http, None) => format!("http://{}/{}", host, path),
+            (git_url::Scheme::Https, Some(port)) => format!("https://{}:{}/{}", host, port, path),
+            _ => format!("https://{}/{}", host, path),
+        };
+        Some(Links { forge, base_url })
+    }
+
+    /// The link to the issue with the given `id`, like `42`.
+    pub fn issue(&self, id: &str) -> String {
+        format!("{}/{}issues/{}", self.base_url, self.separator(), id)
+    }
+
+    /// The link to the pull request, or merge request on GitLab, with the given `id`.
+    pub fn pull_request(&self, id: &str) -> String {
+        let kind = match self.forge {
+            Forge::GitHub => "pull",
+            Forge::GitLab => "merge_requests",
+            Forge::Gitea => "pulls",
+        };
+        format!("{}/{}{}/{}", self.base_url, self.separator(), kind, id)
+    }
+
+    /// The link to the commit with the given full or abbreviated `id`.
+    pub fn commit(&self, id: impl std::fmt::Display) -> String {
+        format!("{}/{}commit/{}", self.base_url, self.separator(), id)
+    }
+
+    /// The link to the comparison of the revisions `from` and `to`, like two tag names.
+    pub fn compare(&self, from: &str, to: &str) -> String {
+        format!("{}/{}compare/{}...{}", self.base_url, self.separator(), from, to)
+    }
+
+    /// The URL of the repository in the REST API of GitHub, like `https://api.github.com/repos/Byron/gitoxide`, with the
+    /// `/api/v3` prefix of GitHub Enterprise servers on other hosts, or `None` if the forge isn't GitHub.
+    pub fn github_api_url(&self) -> Option<String> {
+        if self.forge != Forge::GitHub {
+            return None;
+        }
+        let (scheme, rest) = self.base_url.split_once("://")?;
+        let (host, path) = rest.split_once('/')?;
+        Some(if host.eq_ignore_ascii_case("github.com") {
+            format!("https://api.github.com/repos/{}", path)
+        } else {
+            format!("{}://{}/api/v3/repos/{}", scheme, host, path)
+        })
+    }
+
+    /// GitLab puts all repository pages below the `-` path component to separate them from nested groups.
+    fn separator(&self) -> &'static str {
+        match self.forge {
+            Forge::GitLab => "-/",
+            Forge::GitHub | Forge::Gitea => "",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links(url: &str, forge: Option<Forge>) -> Option<Links> {
+        Links::from_remote_url(&git_url::parse(url.as_bytes()).expect("valid url"), forge)
+    }
+
+    #[test]
+    fn forge_is_derived_from_well_known_hosts() {
+        assert_eq!(Forge::from_host("github.com"), Some(Forge::GitHub));
+        assert_eq!(Forge::from_host("gitlab.example.com"), Some(Forge::GitLab));
+        assert_eq!(Forge::from_host("codeberg.org"), Some(Forge::Gitea));
+        assert_eq!(Forge::from_host("git.example.com"), None);
+    }
+
+    #[test]
+    fn github_over_ssh() {
+        let links = links("git@github.com:Byron/gitoxide.git", None).expect("known forge");
+        assert_eq!(links.base_url, "https://github.com/Byron/gitoxide");
+        assert_eq!(links.issue("42"), "https://github.com/Byron/gitoxide/issues/42");
+        assert_eq!(links.pull_request("42"), "https://github.com/Byron/gitoxide/pull/42");
+        assert_eq!(
+            links.compare("v1.0.0", "v1.1.0"),
+            "https://github.com/Byron/gitoxide/compare/v1.0.0...v1.1.0"
+        );
+        assert_eq!(
+            links.github_api_url().as_deref(),
+            Some("https://api.github.com/repos/Byron/gitoxide")
+        );
+    }
+
+    #[test]
+    fn github_enterprise_api() {
+        let links = links("https://github.example.com/org/repo.git", None).expect("known forge");
+        assert_eq!(
+            links.github_api_url().as_deref(),
+            Some("https://github.example.com/api/v3/repos/org/repo")
+        );
+        let links = self::links("https://gitlab.com/group/project", None).expect("known forge");
+        assert_eq!(links.github_api_url(), None, "only GitHub has this API");
+    }
+
+    #[test]
+    fn gitlab_with_nested_groups() {
+        let links = links("https://gitlab.com/group/sub/project.git", None).expect("known forge");
+        assert_eq!(links.issue("1"), "https://gitlab.com/group/sub/project/-/issues/1");
+        assert_eq!(
+            links.pull_request("1"),
+            "https://gitlab.com/group/sub/project/-/merge_requests/1"
+        );
+        assert_eq!(
+            links.commit(git_repository::hash::ObjectId::null_sha1()),
+            "https://gitlab.com/group/sub/project/-/commit/0000000000000000000000000000000000000000"
+        );
+        assert_eq!(
+            links.compare("a", "b"),
+            "https://gitlab.com/group/sub/project/-/compare/a...b"
+        );
+    }
+
+    #[test]
+    fn self_hosted_gitea_with_override() {
+        assert_eq!(links("https://git.example.com/org/repo", None), None);
+        let links = links("https://git.example.com/org/repo", Some(Forge::Gitea)).expect("forge is set");
+        assert_eq!(links.issue("7"), "https://git.example.com/org/repo/issues/7");
+        assert_eq!(links.pull_request("7"), "https://git.example.com/org/repo/pulls/7");
+    }
+}
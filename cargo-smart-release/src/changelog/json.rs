@@ -0,0 +1,237 @@
+//! Changelogs as JSON for consumption by other tools, described by a [JSON schema](https://json-schema.org).
+//!
+//! [`ChangeLog`][crate::changelog::ChangeLog] and its parts serialize to and deserialize from this format with
+//! `serde_json`, and its shape only changes along with the schema returned by [`schema()`].
+
+/// Return the JSON schema of changelogs serialized as JSON, to validate them against.
+pub fn schema() -> &'static str {
+    include_str!("schema.json")
+}
+
+/// Commit ids as full hex strings.
+pub(crate) mod id {
+    use git_repository::hash::ObjectId;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &ObjectId, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(id)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ObjectId, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        id.parse()
+            .map_err(|_| de::Error::custom(format!("'{}' is not a full hex commit id", id)))
+    }
+}
+
+/// Optional days like `2021-09-15`.
+pub(crate) mod date {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(date: &Option<time::Date>, serializer: S) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => serializer.collect_str(date),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<time::Date>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|date| crate::changelog::parse_date(&date).map_err(de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Semantic versions like `1.0.0`.
+pub(crate) mod semantic_version {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(version: &semver::Version, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(version)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<semver::Version, D::Error> {
+        let version = String::deserialize(deserializer)?;
+        semver::Version::parse(&version)
+            .map_err(|err| de::Error::custom(format!("'{}' is not a semantic version: {}", version, err)))
+    }
+}
+
+/// Conventional commit types, which are interned as they are known in advance.
+pub(crate) mod kind {
+    use serde::{de, Deserialize, Deserializer};
+
+    use crate::changelog::write::GROUPS;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<&'static str>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|kind| to_static(&kind))
+            .transpose()
+    }
+
+    pub fn deserialize_all<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<&'static str>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|kind| to_static(kind))
+            .collect()
+    }
+
+    fn to_static<E: de::Error>(kind: &str) -> Result<&'static str, E> {
+        GROUPS
+            .iter()
+            .map(|(known, _)| *known)
+            .find(|known| *known == kind)
+            .ok_or_else(|| E::custom(format!("'{}' is not a known conventional commit type", kind)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::schema;
+    use crate::changelog::{ChangeLog, Entry, Section, Version};
+
+    /// Validate `value` at `path` against `schema` of the `root` schema, supporting the parts of the JSON schema
+    /// vocabulary ours uses.
+    fn validate(value: &Value, schema: &Value, root: &Value, path: &str) -> Result<(), String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.strip_prefix("#/definitions/").expect("local references only");
+            return validate(value, &root["definitions"][name], root, path);
+        }
+        if let Some(alternatives) = schema["oneOf"].as_array() {
+            let matching = alternatives
+                .iter()
+                .filter(|alternative| validate(value, alternative, root, path).is_ok())
+                .count();
+            return match matching {
+                1 => Ok(()),
+                _ => Err(format!(
+                    "{} matches {} alternatives instead of one: {}",
+                    path, matching, value
+                )),
+            };
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                return Err(format!("{} must be one of {:?}: {}", path, allowed, value));
+            }
+        }
+        if !schema["type"].is_null() {
+            let type_name = match value {
+                Value::Null => "null",
+                Value::Bool(_) => "boolean",
+                Value::Number(_) => "number",
+                Value::String(_) => "string",
+                Value::Array(_) => "array",
+                Value::Object(_) => "object",
+            };
+            let types = match &schema["type"] {
+                Value::Array(types) => types.clone(),
+                single => vec![single.clone()],
+            };
+            if !types.iter().any(|t| t == type_name) {
+                return Err(format!("{} must be of type {:?}: {}", path, types, value));
+            }
+        }
+        if let (Some(pattern), Some(string)) = (schema["pattern"].as_str(), value.as_str()) {
+            if !regex::Regex::new(pattern).expect("valid pattern").is_match(string) {
+                return Err(format!("{} must match '{}': {}", path, pattern, string));
+            }
+        }
+        if let Value::Array(items) = value {
+            for (index, item) in items.iter().enumerate() {
+                validate(item, &schema["items"], root, &format!("{}[{}]", path, index))?;
+            }
+        }
+        if let Value::Object(fields) = value {
+            for required in schema["required"].as_array().into_iter().flatten() {
+                let required = required.as_str().expect("field names");
+                if !fields.contains_key(required) {
+                    return Err(format!("{} lacks '{}'", path, required));
+                }
+            }
+            for (name, field) in fields {
+                let field_schema = &schema["properties"][name];
+                if field_schema.is_null() && schema["additionalProperties"] == Value::Bool(false) {
+                    return Err(format!("{} has the undocumented field '{}'", path, name));
+                }
+                validate(field, field_schema, root, &format!("{}.{}", path, name))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn log() -> ChangeLog {
+        let entry = Entry {
+            id: "f5e5dcbb3b5e1eef2f28f0e3fbd0c1c0d1a5c1d1".parse().unwrap(),
+            kind: Some("feat"),
+            other_kinds: vec!["fix"],
+            prefix: Some("feat(parser)".into()),
+            title: "parse all the things".into(),
+            body: Some("It's a lot.".into()),
+            breaking: true,
+            breaking_description: Some("the parser changed".into()),
+            issues: vec!["123".into()],
+            regressions: vec!["abc1234".into()],
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            changed_paths: vec!["src/".into()],
+            ..Default::default()
+        };
+        ChangeLog {
+            sections: vec![
+                Section {
+                    version: Version::Unreleased,
+                    date: None,
+                    entries: vec![Entry::default()],
+                },
+                Section {
+                    version: Version::Semantic("1.0.0-rc.1".parse().unwrap()),
+                    date: Some(time::Date::from_calendar_date(2021, time::Month::September, 15).unwrap()),
+                    entries: vec![entry],
+                },
+                Section {
+                    version: Version::Range {
+                        from: "v0.9.0".into(),
+                        to: "main".into(),
+                    },
+                    date: None,
+                    entries: Vec::new(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn changelogs_match_the_schema_and_survive_a_round_trip() {
+        let schema: Value = serde_json::from_str(schema()).expect("the schema is valid JSON");
+        let json = serde_json::to_value(log()).unwrap();
+        assert_eq!(validate(&json, &schema, &schema, "changelog"), Ok(()));
+        assert_eq!(json["sections"][0]["version"], "unreleased");
+        assert_eq!(json["sections"][1]["version"]["semantic"], "1.0.0-rc.1");
+        assert_eq!(json["sections"][1]["date"], "2021-09-15");
+        assert_eq!(
+            json["sections"][1]["entries"][0]["id"],
+            "f5e5dcbb3b5e1eef2f28f0e3fbd0c1c0d1a5c1d1"
+        );
+
+        let read: ChangeLog = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&read).unwrap(), json);
+    }
+
+    #[test]
+    fn unknown_conventional_commit_types_are_rejected() {
+        let schema: Value = serde_json::from_str(schema()).unwrap();
+        let mut json = serde_json::to_value(log()).unwrap();
+        json["sections"][1]["entries"][0]["kind"] = "feature".into();
+        assert!(
+            validate(&json, &schema, &schema, "changelog").is_err(),
+            "the schema knows all types"
+        );
+        assert_eq!(
+            serde_json::from_value::<ChangeLog>(json).unwrap_err().to_string(),
+            "'feature' is not a known conventional commit type"
+        );
+    }
+}
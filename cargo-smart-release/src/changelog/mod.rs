@@ -0,0 +1,276 @@
+//! A model of a changelog generated from the commit history, along with the means to render it.
+use git_repository as git;
+use serde::{Deserialize, Serialize};
+
+pub mod enrich;
+pub mod forge;
+pub mod json;
+pub mod template;
+pub mod write;
+
+/// A changelog of a single crate, made of one section per release with the most recent release first.
+///
+/// It serializes to the JSON described by [`json::schema()`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangeLog {
+    pub sections: Vec<Section>,
+}
+
+impl ChangeLog {
+    /// Fold the sections of pre-releases like `1.0.0-rc.2` and `1.0.0-rc.1` into the section of the release of the same
+    /// version, like `1.0.0`, which precedes them, so it contains all changes accumulated across its pre-releases.
+    ///
+    /// Pre-releases without such a release are kept as they are. The release takes the date of its most recent pre-release
+    /// if it has none.
+    pub fn merge_prereleases(&mut self) {
+        let mut sections: Vec<Section> = Vec::with_capacity(self.sections.len());
+        let mut release_index = None;
+        for section in self.sections.drain(..) {
+            let version = match &section.version {
+                Version::Semantic(version) => version,
+                _ => {
+                    release_index = None;
+                    sections.push(section);
+                    continue;
+                }
+            };
+            if version.pre.is_empty() {
+                release_index = Some(sections.len());
+                sections.push(section);
+                continue;
+            }
+            match release_index.map(|index| &mut sections[index]) {
+                Some(Section {
+                    version: Version::Semantic(release),
+                    date,
+                    entries,
+                }) if (release.major, release.minor, release.patch)
+                    == (version.major, version.minor, version.patch) =>
+                {
+                    // A release tagged right after its last pre-release has no changes of its own to take the date from.
+                    if date.is_none() {
+                        *date = section.date;
+                    }
+                    entries.extend(section.entries)
+                }
+                _ => {
+                    release_index = None;
+                    sections.push(section);
+                }
+            }
+        }
+        self.sections = sections;
+    }
+}
+
+/// The version documented by a section of the changelog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Version {
+    /// Changes that were not released yet.
+    Unreleased,
+    /// A released version as parsed from its tag.
+    Semantic(#[serde(with = "json::semantic_version")] semver::Version),
+    /// The changes in an explicitly given range of commits, like `v1.0.0..main`.
+    Range { from: String, to: String },
+}
+
+/// Return the versions of all releases in `markdown`, the content of a changelog, in order of appearance.
+///
+/// A release is a heading with a version, like `### v1.0.0 (2021-09-09)` or `## [1.0.0] - 2021-09-09`, and its version is
+/// the first word of the heading that is one.
+pub fn released_versions(markdown: &str) -> Vec<semver::Version> {
+    markdown
+        .lines()
+        .filter(|line| line.starts_with('#'))
+        .filter_map(|heading| {
+            heading.split_whitespace().find_map(|word| {
+                let word = word.trim_matches(|c| matches!(c, '[' | ']' | '(' | ')' | ':' | ','));
+                semver::Version::parse(word.strip_prefix('v').unwrap_or(word)).ok()
+            })
+        })
+        .collect()
+}
+
+/// Parse a day like `2021-09-15`.
+pub fn parse_date(value: &str) -> Result<time::Date, String> {
+    use std::convert::TryFrom;
+
+    let invalid = || format!("'{}' is not a date like '2021-09-15'", value);
+    let parts: Vec<_> = value.split('-').collect();
+    let (year, month, day) = match parts.as_slice() {
+        [year, month, day] => (
+            year.parse::<i32>().map_err(|_| invalid())?,
+            month.parse::<u8>().map_err(|_| invalid())?,
+            day.parse::<u8>().map_err(|_| invalid())?,
+        ),
+        _ => return Err(invalid()),
+    };
+    let month = time::Month::try_from(month).map_err(|_| invalid())?;
+    time::Date::from_calendar_date(year, month, day).map_err(|_| invalid())
+}
+
+/// All changes of a single release.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Section {
+    pub version: Version,
+    /// The day of the release, or `None` if it wasn't released yet.
+    #[serde(with = "json::date")]
+    pub date: Option<time::Date>,
+    /// All changes of the release, the most recent one first.
+    pub entries: Vec<Entry>,
+}
+
+/// A conventional commit type, spelled as an alias as serde would otherwise borrow `Option<&'static str>` when
+/// deserializing, which is only possible from `'static` data.
+type Kind = &'static str;
+
+/// A single change as parsed from a commit message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// The commit that introduced the change.
+    #[serde(with = "json::id")]
+    pub id: git::hash::ObjectId,
+    /// The conventional commit type like `feat`, or `None` if the commit message doesn't follow the convention.
+    #[serde(deserialize_with = "json::kind::deserialize")]
+    pub kind: Option<Kind>,
+    /// Further conventional commit types the change belongs to, like `fix` for a `feat` which fixes something as well.
+    #[serde(deserialize_with = "json::kind::deserialize_all")]
+    pub other_kinds: Vec<Kind>,
+    /// The conventional commit prefix of the title as written, like `feat(parser)`, if there is one.
+    pub prefix: Option<String>,
+    /// The title of the commit without the conventional commit prefix and issue references.
+    pub title: String,
+    /// The body of the commit message, if there is one.
+    pub body: Option<String>,
+    /// If set, this change is marked as breaking.
+    pub breaking: bool,
+    /// A specific description of the breaking change, if present.
+    pub breaking_description: Option<String>,
+    /// If set, this change is featured among the highlights of its release.
+    pub highlight: bool,
+    /// If set, the commit message has no subject, and the `title` is empty.
+    pub empty_subject: bool,
+    /// If set, the change only updates dependencies, like the ones made by bots.
+    pub dependency_update: bool,
+    /// If set, the change only updates the commits of git submodules within the crate.
+    pub submodule_update: bool,
+    /// All issues referenced in the commit title, like `123`.
+    pub issues: Vec<String>,
+    /// The full or abbreviated ids of the commits which introduced the regressions fixed by this change, as referenced
+    /// in `Fixes: <commit>` footers.
+    pub regressions: Vec<String>,
+    /// The name of the author of the change.
+    pub author: String,
+    /// The email address of the author of the change.
+    pub author_email: String,
+    /// The top-level files and directories of the repository the change touched, with directories ending in `/`, or
+    /// empty if they weren't computed.
+    pub changed_paths: Vec<String>,
+}
+
+#[cfg(test)]
+impl Default for Entry {
+    fn default() -> Self {
+        Entry {
+            id: git::hash::ObjectId::null_sha1(),
+            kind: None,
+            other_kinds: Vec::new(),
+            prefix: None,
+            title: String::new(),
+            body: None,
+            breaking: false,
+            breaking_description: None,
+            highlight: false,
+            empty_subject: false,
+            dependency_update: false,
+            submodule_update: false,
+            issues: Vec::new(),
+            regressions: Vec::new(),
+            author: String::new(),
+            author_email: String::new(),
+            changed_paths: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn released_versions_are_parsed_from_headings() {
+        let markdown = "# Changelog\n\n## Unreleased\n\n### v1.2.0 (2021-09-09)\n\n- fix 1.1.0 bug\n\n## [1.1.0] - 2021-08-01\n\n## my-crate 1.0.0-alpha.1\n";
+        assert_eq!(
+            released_versions(markdown),
+            vec![
+                semver::Version::new(1, 2, 0),
+                semver::Version::new(1, 1, 0),
+                semver::Version::parse("1.0.0-alpha.1").unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn dates_of_existing_days() {
+        assert_eq!(
+            parse_date("2021-09-15"),
+            Ok(time::Date::from_calendar_date(2021, time::Month::September, 15).unwrap())
+        );
+        for invalid in ["2021-09", "2021-09-15-1", "2021-13-01", "2021-02-30", "2021-Sep-15", ""] {
+            assert_eq!(
+                parse_date(invalid),
+                Err(format!("'{}' is not a date like '2021-09-15'", invalid))
+            );
+        }
+    }
+
+    #[test]
+    fn prereleases_are_merged_into_the_release_of_their_version() {
+        let section = |version: &str, titles: &[&str]| Section {
+            version: Version::Semantic(semver::Version::parse(version).unwrap()),
+            date: None,
+            entries: titles
+                .iter()
+                .map(|title| Entry {
+                    kind: Some("fix"),
+                    title: (*title).into(),
+                    author: "Sebastian Thiel".into(),
+                    author_email: "sebastian.thiel@icloud.com".into(),
+                    ..Default::default()
+                })
+                .collect(),
+        };
+        let mut log = ChangeLog {
+            sections: vec![
+                section("1.1.0-rc.1", &["e"]),
+                section("1.0.0", &["d"]),
+                section("1.0.0-rc.2", &["c"]),
+                section("1.0.0-rc.1", &["b"]),
+                section("0.9.0", &["a"]),
+            ],
+        };
+        log.merge_prereleases();
+        let sections: Vec<_> = log
+            .sections
+            .iter()
+            .map(|s| {
+                (
+                    match &s.version {
+                        Version::Semantic(v) => v.to_string(),
+                        _ => unreachable!("only semantic versions are used"),
+                    },
+                    s.entries.iter().map(|e| e.title.as_str()).collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                ("1.1.0-rc.1".to_string(), vec!["e"]),
+                ("1.0.0".into(), vec!["d", "c", "b"]),
+                ("0.9.0".into(), vec!["a"]),
+            ]
+        );
+    }
+}
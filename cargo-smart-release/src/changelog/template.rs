@@ -0,0 +1,321 @@
+//! Rendering of changelogs with user-provided templates as an alternative to the built-in markdown.
+//!
+//! Templates use the [tinytemplate syntax](https://docs.rs/tinytemplate/1.2.1/tinytemplate/syntax/index.html) and
+//! receive a [`Context`] describing the crate and all sections of its changelog. Values are inserted without escaping.
+use serde::Serialize;
+use tinytemplate::{format_unescaped, TinyTemplate};
+
+use crate::changelog::{
+    write::{self, END_MARKER, GROUPS, START_MARKER},
+    ChangeLog,
+};
+
+/// The name under which a user-provided template is registered.
+const NAME: &str = "changelog";
+
+/// The data available to a template.
+#[derive(Debug, Serialize)]
+pub struct Context<'a> {
+    /// The name of the crate the changelog is for.
+    pub crate_name: &'a str,
+    /// All sections of the changelog, the most recent one first.
+    pub sections: Vec<Section<'a>>,
+}
+
+/// A release, or the unreleased changes, as seen by templates.
+#[derive(Debug, Serialize)]
+pub struct Section<'a> {
+    /// The heading the built-in renderer uses, like `v1.0.0 (2021-09-15)` or `Unreleased`.
+    pub heading: String,
+    /// The anchor of the heading as used by the table of contents of the built-in renderer, like `v100-2021-09-15`.
+    pub anchor: String,
+    /// True if this section contains changes that were not released yet.
+    pub unreleased: bool,
+    /// The released version, like `1.0.0`, if the changes were released and aren't an explicit range of commits.
+    pub version: Option<String>,
+    /// The day of the release, like `2021-09-15`, if the changes were released.
+    pub date: Option<String>,
+    /// All entries marked as breaking.
+    pub breaking: Vec<Entry<'a>>,
+    /// All entries with a `Highlight: true` footer if highlights are enabled, or none.
+    pub highlights: Vec<Entry<'a>>,
+    /// All entries grouped by their conventional commit type, omitting empty groups.
+    pub groups: Vec<Group<'a>>,
+    /// The names of the authors of all entries, or their handles if configured, sorted and without duplicates.
+    pub contributors: Vec<String>,
+    /// The summary line with the amount of entries of each kind, if one is configured.
+    pub summary: Option<String>,
+}
+
+/// Entries of the same conventional commit type.
+#[derive(Debug, Serialize)]
+pub struct Group<'a> {
+    /// The conventional commit type, like `feat`, or `other` for changes not following the convention.
+    pub kind: &'static str,
+    /// The title the built-in renderer uses, like `New Features`, or its translation if configured.
+    pub title: String,
+    /// The icon configured for the group, like `🚀`.
+    pub icon: Option<String>,
+    pub entries: Vec<Entry<'a>>,
+}
+
+/// A single change as seen by templates.
+#[derive(Debug, Serialize)]
+pub struct Entry<'a> {
+    /// The full hex id of the commit that introduced the change.
+    pub id: String,
+    /// The link to the commit on the forge hosting the repository, if known.
+    pub commit_url: Option<String>,
+    pub kind: Option<&'static str>,
+    /// Further conventional commit types of the change, whose groups list it as well.
+    pub other_kinds: Vec<&'static str>,
+    pub prefix: Option<&'a str>,
+    pub title: &'a str,
+    /// The body of the commit message, without trailers if they are stripped.
+    pub body: Option<String>,
+    pub breaking: bool,
+    pub breaking_description: Option<&'a str>,
+    pub highlight: bool,
+    pub issues: Vec<Issue<'a>>,
+    /// The commits which introduced the regressions fixed by the change.
+    pub regressions: Vec<Regression<'a>>,
+    pub author: &'a str,
+    /// The top-level files and directories the change touched, directories ending in `/`, if enabled.
+    pub changed_paths: Vec<&'a str>,
+}
+
+/// A commit which introduced a regression fixed by an entry.
+#[derive(Debug, Serialize)]
+pub struct Regression<'a> {
+    /// The full or abbreviated commit id as referenced, like `abc1234`.
+    pub id: &'a str,
+    /// The link to the commit on the forge hosting the repository, if known.
+    pub url: Option<String>,
+}
+
+/// An issue referenced by an entry.
+#[derive(Debug, Serialize)]
+pub struct Issue<'a> {
+    /// The issue id, like `123`.
+    pub id: &'a str,
+    /// The link to the issue on the forge hosting the repository, if known.
+    pub url: Option<String>,
+}
+
+impl<'a> Context<'a> {
+    /// Create the data for templates from the changelog `log` of the crate named `crate_name`, using `options` to
+    /// produce links.
+    pub fn new(crate_name: &'a str, log: &'a ChangeLog, options: &write::Options) -> Self {
+        let entry = |e: &'a crate::changelog::Entry| Entry {
+            id: e.id.to_string(),
+            commit_url: options.links.as_ref().map(|links| links.commit(e.id)),
+            kind: e.kind,
+            other_kinds: e.other_kinds.clone(),
+            prefix: e.prefix.as_deref(),
+            title: e.rendered_title(options).unwrap_or_default(),
+            body: e.rendered_body(options),
+            breaking: e.breaking,
+            breaking_description: e.breaking_description.as_deref(),
+            highlight: e.highlight,
+            issues: e
+                .issues
+                .iter()
+                .map(|id| Issue {
+                    id,
+                    url: options.links.as_ref().map(|links| links.issue(id)),
+                })
+                .collect(),
+            regressions: e
+                .regressions
+                .iter()
+                .map(|id| Regression {
+                    id,
+                    url: options.links.as_ref().map(|links| links.commit(id)),
+                })
+                .collect(),
+            author: &e.author,
+            changed_paths: e
+                .changed_paths
+                .iter()
+                .take(options.changed_paths)
+                .map(String::as_str)
+                .collect(),
+        };
+        Context {
+            crate_name,
+            sections: log
+                .sections
+                .iter()
+                .zip(log.anchors(options))
+                .map(|(section, anchor)| {
+                    let entries: Vec<_> = section
+                        .entries
+                        .iter()
+                        .filter(|e| e.rendered_title(options).is_some())
+                        .collect();
+                    Section {
+                        heading: section.heading(options),
+                        anchor,
+                        unreleased: section.version == crate::changelog::Version::Unreleased,
+                        version: match &section.version {
+                            crate::changelog::Version::Unreleased | crate::changelog::Version::Range { .. } => None,
+                            crate::changelog::Version::Semantic(version) => Some(version.to_string()),
+                        },
+                        date: section.date.map(|date| date.to_string()),
+                        breaking: entries.iter().copied().filter(|e| e.breaking).map(entry).collect(),
+                        highlights: entries
+                            .iter()
+                            .copied()
+                            .filter(|e| options.highlights && e.highlight)
+                            .map(entry)
+                            .collect(),
+                        groups: GROUPS
+                            .iter()
+                            .map(|(kind, title)| Group {
+                                kind,
+                                title: write::text(kind, title, options).to_owned(),
+                                icon: options.icons.get(*kind).cloned(),
+                                entries: entries
+                                    .iter()
+                                    .copied()
+                                    .filter(|e| e.groups(options).contains(kind))
+                                    .map(entry)
+                                    .collect(),
+                            })
+                            .filter(|group| !group.entries.is_empty())
+                            .collect(),
+                        contributors: section.authors(&options.author_handles),
+                        summary: section.summary(options),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Render `context` with `template` into a string enclosed in our markers, like [`ChangeLog::write_to()`] does.
+pub fn render(template: &str, context: &Context<'_>) -> anyhow::Result<String> {
+    Ok(format!(
+        "{}\n\n{}\n\n{}",
+        START_MARKER,
+        render_unmarked(template, context)?,
+        END_MARKER
+    ))
+}
+
+/// Render `context` with `template` into a string without leading or trailing whitespace.
+pub fn render_unmarked(template: &str, context: &Context<'_>) -> anyhow::Result<String> {
+    let mut engine = TinyTemplate::new();
+    engine.set_default_formatter(&format_unescaped);
+    engine.add_template(NAME, template)?;
+    Ok(engine.render(NAME, context)?.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::{Entry, Section, Version};
+
+    #[test]
+    fn sections_entries_and_contributors_are_available() {
+        let entry = |title: &str, author: &str| Entry {
+            kind: Some("fix"),
+            title: title.into(),
+            issues: vec!["42".into()],
+            author: author.into(),
+            author_email: format!("{}@example.com", author.to_lowercase()),
+            ..Default::default()
+        };
+        let log = ChangeLog {
+            sections: vec![Section {
+                version: Version::Semantic(semver::Version::new(1, 0, 0)),
+                date: None,
+                entries: vec![entry("a <b>", "Sebastian Thiel"), entry("c", "Anne")],
+            }],
+        };
+        let template = "{crate_name}\n{{ for s in sections }}{s.version}:{{ for g in s.groups }} {g.title}:{{ for e in g.entries }} {e.title} #{{ for i in e.issues }}{i.id}{{ endfor }}{{ endfor }}{{ endfor }}\nby {{ for c in s.contributors }}{c}{{ if not @last }}, {{ endif }}{{ endfor }}{{ endfor }}";
+        assert_eq!(
+            render(template, &Context::new("a", &log, &Default::default())).unwrap(),
+            format!(
+                "{}\n\na\n1.0.0: Bug Fixes: a <b> #42 c #42\nby Anne, Sebastian Thiel\n\n{}",
+                START_MARKER, END_MARKER
+            )
+        );
+    }
+
+    #[test]
+    fn highlights_are_available_if_enabled() {
+        let log = ChangeLog {
+            sections: vec![Section {
+                version: Version::Unreleased,
+                date: None,
+                entries: vec![
+                    Entry {
+                        title: "a".into(),
+                        highlight: true,
+                        ..Default::default()
+                    },
+                    Entry {
+                        title: "b".into(),
+                        ..Default::default()
+                    },
+                ],
+            }],
+        };
+        let template = "{{ for s in sections }}{{ for e in s.highlights }}{e.title}{{ endfor }}{{ endfor }}";
+        let render = |highlights| {
+            render_unmarked(
+                template,
+                &Context::new(
+                    "a",
+                    &log,
+                    &write::Options {
+                        highlights,
+                        ..Default::default()
+                    },
+                ),
+            )
+            .unwrap()
+        };
+        assert_eq!(render(true), "a");
+        assert_eq!(
+            render(false),
+            "",
+            "highlights follow the configuration like in the built-in renderer"
+        );
+    }
+
+    #[test]
+    fn bodies_are_available_without_trailers_if_stripped() {
+        let log = ChangeLog {
+            sections: vec![Section {
+                version: Version::Unreleased,
+                date: None,
+                entries: vec![Entry {
+                    title: "a".into(),
+                    body: Some("the body\n\nSigned-off-by: A\nCo-authored-by: B".into()),
+                    ..Default::default()
+                }],
+            }],
+        };
+        let template = "{{ for s in sections }}{{ for g in s.groups }}{{ for e in g.entries }}{e.body}{{ endfor }}{{ endfor }}{{ endfor }}";
+        let render = |strip_trailers, keep_trailers: &[&str]| {
+            render_unmarked(
+                template,
+                &Context::new(
+                    "a",
+                    &log,
+                    &write::Options {
+                        strip_trailers,
+                        keep_trailers: keep_trailers.iter().map(|token| token.to_string()).collect(),
+                        ..Default::default()
+                    },
+                ),
+            )
+            .unwrap()
+        };
+        assert_eq!(render(false, &[]), "the body\n\nSigned-off-by: A\nCo-authored-by: B");
+        assert_eq!(render(true, &[]), "the body");
+        assert_eq!(render(true, &["Co-authored-by"]), "the body\n\nCo-authored-by: B");
+    }
+}
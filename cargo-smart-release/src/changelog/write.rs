@@ -0,0 +1,1431 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Write},
+    ops::Range,
+};
+
+use crate::{
+    changelog::{forge::Links, ChangeLog, Entry, Section, Version},
+    config::{Anchors, DependencyUpdates, EmptySubjects},
+};
+
+/// The marker preceding all generated content in a changelog file.
+pub const START_MARKER: &str =
+    "<!-- cargo-smart-release:start - everything up to the end marker is generated and will be overwritten -->";
+/// The marker following all generated content in a changelog file.
+pub const END_MARKER: &str = "<!-- cargo-smart-release:end -->";
+/// The name of the file with the unreleased changes in a directory with one file per release.
+pub const UNRELEASED_FILE_NAME: &str = "unreleased.md";
+
+/// Conventional commit types along with the title of their group of changes, in the order they are rendered.
+///
+/// Changes that don't follow the convention are rendered along with the `other` type.
+pub(crate) const GROUPS: &[(&str, &str)] = &[
+    ("feat", "New Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("revert", "Reverts"),
+    ("refactor", "Refactor"),
+    ("docs", "Documentation"),
+    ("style", "Style"),
+    ("test", "Test"),
+    ("chore", "Chore"),
+    ("deps", "Dependencies"),
+    ("submodules", "Submodule Updates"),
+    ("other", "Other"),
+];
+
+/// Options to control how a changelog is rendered.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// If set, render a table of contents linking to each section at the top.
+    pub table_of_contents: bool,
+    /// If set, render the conventional commit prefix of entries, like `feat: add X`, instead of only their title.
+    pub keep_type_prefix: bool,
+    /// If set, list highlighted entries in their own group ahead of all others, in addition to their usual group.
+    pub highlights: bool,
+    /// If set, issue references are rendered as links into the repository on its forge.
+    pub links: Option<Links>,
+    /// The maximum amount of entries to render per group of changes, keyed by conventional commit type like `fix`.
+    ///
+    /// Entries beyond the limit are summarized with their count.
+    pub max_entries: BTreeMap<String, usize>,
+    /// Icons to put in front of the headings of groups, keyed by conventional commit type like `feat`, or by `highlights`,
+    /// `breaking` and `authors`.
+    pub icons: BTreeMap<String, String>,
+    /// If set, render the authors of all entries of a section at its end.
+    pub authors: bool,
+    /// Handles to render instead of the names of authors, keyed by their email address.
+    pub author_handles: BTreeMap<String, String>,
+    /// What to do with entries whose commit message has an empty subject.
+    pub empty_subjects: EmptySubjects,
+    /// The maximum amount of changed paths to render after each entry, or 0 to render none.
+    pub changed_paths: usize,
+    /// Whether to render entries that update dependencies in their own group.
+    pub dependency_updates: DependencyUpdates,
+    /// If set, entries in the group of dependency updates that bump the same dependency, like `bump serde from 1.0.1 to
+    /// 1.0.2`, are rendered as a single line with the net version change.
+    pub coalesce_dependency_bumps: bool,
+    /// If set, render the group of dependency updates as a single line if it has more entries than this.
+    pub collapse_dependency_updates: Option<usize>,
+    /// How to derive the anchors of section headings that the table of contents links to.
+    pub anchors: Anchors,
+    /// If set, remove trailer lines like `Signed-off-by: X` or `Fixes #1` from the bodies of entries.
+    pub strip_trailers: bool,
+    /// The tokens of trailers to keep even if `strip_trailers` is set, like `Co-authored-by`.
+    pub keep_trailers: Vec<String>,
+    /// The text to render in sections without any changes to list, or nothing if unset.
+    pub no_changes_text: Option<String>,
+    /// Texts to render instead of the built-in english headings, keyed by a conventional commit type like `feat` for the
+    /// heading of its group, or by `unreleased`, `highlights`, `breaking` or `authors`.
+    pub texts: BTreeMap<String, String>,
+    /// The line to render at the top of sections with entries, with placeholders like `{commits}`, `{breaking}` or
+    /// `{feat}` replaced by the amount of the respective entries.
+    pub summary: Option<String>,
+}
+
+/// The keys of texts that can be translated, besides the conventional commit types of [`GROUPS`].
+pub(crate) const TEXT_KEYS: &[&str] = &["unreleased", "highlights", "breaking", "authors"];
+
+impl ChangeLog {
+    /// Render all sections as markdown into `out`, enclosed in our markers.
+    pub fn write_to(&self, mut out: impl Write, options: &Options) -> fmt::Result {
+        writeln!(out, "{}", START_MARKER)?;
+        writeln!(out)?;
+        let anchors = self.anchors(options);
+        if options.table_of_contents && !self.sections.is_empty() {
+            for (section, anchor) in self.sections.iter().zip(&anchors) {
+                writeln!(out, "- [{}](#{})", section.heading(options), anchor)?;
+            }
+            writeln!(out)?;
+        }
+        for (section, anchor) in self.sections.iter().zip(&anchors) {
+            if options.anchors == Anchors::Explicit {
+                writeln!(out, "<a id=\"{}\"></a>", anchor)?;
+            }
+            section.write_to(&mut out, options)?;
+        }
+        write!(out, "{}", END_MARKER)
+    }
+
+    /// Return the unique anchor of the heading of each section, in order, as derived with the `anchors` of `options`.
+    pub fn anchors(&self, options: &Options) -> Vec<String> {
+        let mut slugs = Slugs::new(options.anchors);
+        self.sections
+            .iter()
+            .map(|section| slugs.unique(&section.heading(options)))
+            .collect()
+    }
+}
+
+impl Section {
+    /// The text of the heading of this section, in the language of the `texts` of `options`.
+    pub fn heading(&self, options: &Options) -> String {
+        match (&self.version, self.date) {
+            (Version::Unreleased, _) => text("unreleased", "Unreleased", options).into(),
+            (Version::Semantic(version), Some(date)) => format!("v{} ({})", version, date),
+            (Version::Semantic(version), None) => format!("v{}", version),
+            (Version::Range { from, to }, Some(date)) => format!("{}..{} ({})", from, to, date),
+            (Version::Range { from, to }, None) => format!("{}..{}", from, to),
+        }
+    }
+
+    /// The name of the file of this section in a directory with one file per release, like `1.2.0.md`, or `None` for
+    /// ranges of commits.
+    pub fn file_name(&self) -> Option<String> {
+        match &self.version {
+            Version::Unreleased => Some(UNRELEASED_FILE_NAME.into()),
+            Version::Semantic(version) => Some(format!("{}.md", version)),
+            Version::Range { .. } => None,
+        }
+    }
+
+    /// The authors of all entries, sorted and without duplicates, with their handle from `handles` if their email address
+    /// has one, or their name otherwise.
+    pub fn authors(&self, handles: &BTreeMap<String, String>) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|e| handles.get(&e.author_email).unwrap_or(&e.author).to_owned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// The summary line of this section as configured in `options`, or `None` if there is none or no entry is rendered.
+    pub fn summary(&self, options: &Options) -> Option<String> {
+        let format = options.summary.as_deref()?;
+        let entries: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| e.rendered_title(options).is_some())
+            .collect();
+        if entries.is_empty() {
+            return None;
+        }
+        let mut summary = format
+            .replace("{commits}", &entries.len().to_string())
+            .replace("{breaking}", &entries.iter().filter(|e| e.breaking).count().to_string());
+        for (kind, _) in GROUPS {
+            let count = entries.iter().filter(|e| e.groups(options).contains(kind)).count();
+            summary = summary.replace(&format!("{{{}}}", kind), &count.to_string());
+        }
+        Some(summary)
+    }
+
+    /// Render this section as markdown into `out`, without any markers.
+    pub fn write_to(&self, out: &mut impl Write, options: &Options) -> fmt::Result {
+        writeln!(out, "### {}", self.heading(options))?;
+        writeln!(out)?;
+
+        let entries: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| e.rendered_title(options).is_some())
+            .collect();
+        if entries.is_empty() {
+            if let Some(text) = &options.no_changes_text {
+                writeln!(out, "{}", text)?;
+                writeln!(out)?;
+            }
+            return Ok(());
+        }
+        if let Some(summary) = self.summary(options) {
+            writeln!(out, "{}", summary)?;
+            writeln!(out)?;
+        }
+        let mut highlights = entries.iter().filter(|e| options.highlights && e.highlight).peekable();
+        if highlights.peek().is_some() {
+            writeln!(out, "#### {}", group_heading("highlights", "Highlights", options))?;
+            writeln!(out)?;
+            for entry in highlights {
+                entry.write_to(out, options)?;
+            }
+            writeln!(out)?;
+        }
+
+        let mut breaking = entries.iter().filter(|e| e.breaking).peekable();
+        if breaking.peek().is_some() {
+            writeln!(out, "#### {}", group_heading("breaking", "Breaking Changes", options))?;
+            writeln!(out)?;
+            for entry in breaking {
+                writeln!(
+                    out,
+                    "- {}",
+                    entry
+                        .breaking_description
+                        .as_deref()
+                        .or_else(|| entry.rendered_title(options))
+                        .unwrap_or_default()
+                )?;
+            }
+            writeln!(out)?;
+        }
+
+        for (kind, title) in GROUPS {
+            let entries: Vec<_> = entries
+                .iter()
+                .copied()
+                .filter(|e| e.groups(options).contains(kind))
+                .collect();
+            if entries.is_empty() {
+                continue;
+            }
+            writeln!(out, "#### {}", group_heading(kind, title, options))?;
+            writeln!(out)?;
+            let entries = if *kind == "deps" && options.coalesce_dependency_bumps {
+                coalesce_dependency_bumps(&entries)
+            } else {
+                entries.into_iter().map(Line::Entry).collect()
+            };
+            if *kind == "deps" && matches!(options.collapse_dependency_updates, Some(limit) if entries.len() > limit) {
+                writeln!(out, "- Updated {} dependencies", entries.len())?;
+                writeln!(out)?;
+                continue;
+            }
+            let limit = options.max_entries.get(*kind).copied().unwrap_or(entries.len());
+            for entry in entries.iter().take(limit) {
+                match entry {
+                    Line::Entry(entry)
+                    | Line::Bumps {
+                        newest: entry,
+                        count: 1,
+                        ..
+                    } => entry.write_to(out, options)?,
+                    Line::Bumps { bump, .. } => {
+                        writeln!(out, "- bumped {} from {} to {}", bump.name, bump.from, bump.to)?
+                    }
+                }
+            }
+            if entries.len() > limit {
+                writeln!(out, "- ...and {} more", entries.len() - limit)?;
+            }
+            writeln!(out)?;
+        }
+
+        if options.authors {
+            writeln!(out, "#### {}", group_heading("authors", "Authors", options))?;
+            writeln!(out)?;
+            for author in self.authors(&options.author_handles) {
+                writeln!(out, "- {}", author)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// A line in a group of changes.
+enum Line<'a> {
+    /// A single entry.
+    Entry(&'a Entry),
+    /// The `count` entries bumping the same dependency, the `newest` one first, combined into the bump from the oldest to
+    /// the newest version.
+    Bumps {
+        newest: &'a Entry,
+        bump: DependencyBump<'a>,
+        count: usize,
+    },
+}
+
+/// A dependency update as described by titles like `bump serde from 1.0.1 to 1.0.2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DependencyBump<'a> {
+    name: &'a str,
+    from: &'a str,
+    to: &'a str,
+}
+
+impl<'a> DependencyBump<'a> {
+    fn parse(title: &'a str) -> Option<Self> {
+        let mut words = title.split_whitespace();
+        words.next().filter(|w| w.eq_ignore_ascii_case("bump"))?;
+        let name = words.next()?;
+        words.next().filter(|w| *w == "from")?;
+        let from = words.next()?;
+        words.next().filter(|w| *w == "to")?;
+        let to = words.next()?;
+        if words.next().is_some() {
+            return None;
+        }
+        Some(DependencyBump { name, from, to })
+    }
+}
+
+/// Combine all `entries` bumping the same dependency into one line with the net version change, at the position of the
+/// newest one, assuming `entries` are ordered from newest to oldest. Other entries are kept as they are.
+fn coalesce_dependency_bumps<'a>(entries: &[&'a Entry]) -> Vec<Line<'a>> {
+    let mut lines: Vec<Line<'a>> = Vec::new();
+    for entry in entries {
+        let bump = match DependencyBump::parse(&entry.title) {
+            Some(bump) => bump,
+            None => {
+                lines.push(Line::Entry(entry));
+                continue;
+            }
+        };
+        let newer = lines.iter_mut().find_map(|line| match line {
+            Line::Bumps { bump: newer, count, .. } if newer.name == bump.name => Some((newer, count)),
+            _ => None,
+        });
+        match newer {
+            Some((newer, count)) => {
+                newer.from = bump.from;
+                *count += 1;
+            }
+            None => lines.push(Line::Bumps {
+                newest: entry,
+                bump,
+                count: 1,
+            }),
+        }
+    }
+    lines
+}
+
+/// Return the text configured for `key`, or `default` if there is none.
+pub(crate) fn text<'a>(key: &str, default: &'a str, options: &'a Options) -> &'a str {
+    options.texts.get(key).map(String::as_str).unwrap_or(default)
+}
+
+/// Return the heading of the group of `kind` with `title`, preceded by its icon if one is configured.
+fn group_heading(kind: &str, title: &str, options: &Options) -> String {
+    let title = text(kind, title, options);
+    match options.icons.get(kind) {
+        Some(icon) => format!("{} {}", icon, title),
+        None => title.to_owned(),
+    }
+}
+
+/// Tokens of trailers which don't follow the `Word-by` pattern of most others.
+const TRAILER_TOKENS: &[&str] = &[
+    "breaking-change",
+    "cc",
+    "change-id",
+    "close",
+    "closed",
+    "closes",
+    "fix",
+    "fixed",
+    "fixes",
+    "highlight",
+    "ref",
+    "refs",
+    "resolve",
+    "resolved",
+    "resolves",
+    "type",
+];
+
+/// Return true if `line` is a trailer to strip as per `options`, like `Signed-off-by: X`, `Change-Id: X` or `Fixes #1`.
+fn is_stripped_trailer(line: &str, options: &Options) -> bool {
+    if !options.strip_trailers {
+        return false;
+    }
+    let line = line.trim_start();
+    let token = match line.find(|c: char| c == ':' || c.is_whitespace()) {
+        Some(end) if line[end..].starts_with(':') || line[end..].trim_start().starts_with('#') => &line[..end],
+        _ => return false,
+    };
+    let token_lowercase = token.to_ascii_lowercase();
+    (token_lowercase.ends_with("-by") || TRAILER_TOKENS.contains(&token_lowercase.as_str()))
+        && !options
+            .keep_trailers
+            .iter()
+            .any(|kept| kept.eq_ignore_ascii_case(token))
+}
+
+impl Entry {
+    /// The types of the groups in [`GROUPS`] to render this entry in, usually just one.
+    pub fn groups(&self, options: &Options) -> Vec<&'static str> {
+        if self.submodule_update {
+            vec!["submodules"]
+        } else if self.dependency_update && options.dependency_updates == DependencyUpdates::Group {
+            vec!["deps"]
+        } else {
+            std::iter::once(self.kind.unwrap_or("other"))
+                .chain(self.other_kinds.iter().copied())
+                .collect()
+        }
+    }
+
+    /// The title to render for this entry, or `None` if it has an empty subject and should be skipped as per `options`.
+    pub fn rendered_title(&self, options: &Options) -> Option<&str> {
+        if !self.empty_subject {
+            return Some(&self.title);
+        }
+        match options.empty_subjects {
+            EmptySubjects::Skip => None,
+            EmptySubjects::FirstBodyLine => self
+                .body
+                .as_deref()?
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty() && !is_stripped_trailer(line, options)),
+        }
+    }
+
+    /// The body to render for this entry, without trailers if they should be stripped as per `options`, or `None` if
+    /// nothing remains.
+    pub fn rendered_body(&self, options: &Options) -> Option<String> {
+        let body = self
+            .body
+            .as_deref()?
+            .lines()
+            .filter(|line| !is_stripped_trailer(line, options))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let body = body.trim();
+        (!body.is_empty()).then(|| body.to_owned())
+    }
+
+    fn write_to(&self, out: &mut impl Write, options: &Options) -> fmt::Result {
+        let title = self.rendered_title(options).unwrap_or_default();
+        match self.prefix.as_ref().filter(|_| options.keep_type_prefix) {
+            Some(prefix) => write!(out, "- {}: {}", prefix, title)?,
+            None => write!(out, "- {}", title)?,
+        }
+        if !self.issues.is_empty() {
+            write!(
+                out,
+                " ({})",
+                self.issues
+                    .iter()
+                    .map(|id| match &options.links {
+                        Some(links) => format!("[#{}]({})", id, links.issue(id)),
+                        None => format!("#{}", id),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        if !self.regressions.is_empty() {
+            write!(
+                out,
+                " (fixes regression introduced in {})",
+                self.regressions
+                    .iter()
+                    .map(|id| match &options.links {
+                        Some(links) => format!("[{}]({})", id, links.commit(id)),
+                        None => id.clone(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        if options.changed_paths > 0 && !self.changed_paths.is_empty() {
+            write!(
+                out,
+                " (changed {}",
+                self.changed_paths
+                    .iter()
+                    .take(options.changed_paths)
+                    .map(|path| format!("`{}`", path))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+            match self.changed_paths.len().checked_sub(options.changed_paths) {
+                Some(remaining) if remaining > 0 => write!(out, " and {} more)", remaining)?,
+                _ => write!(out, ")")?,
+            }
+        }
+        writeln!(out)
+    }
+}
+
+/// Merge `generated` content as produced by [`ChangeLog::write_to()`] into the `existing` content of a changelog file.
+///
+/// Previously generated content is replaced and everything else is left untouched. If there is no previously
+/// generated content, the new content is appended.
+pub fn merge(existing: Option<&str>, generated: &str) -> String {
+    match existing.filter(|existing| !existing.trim().is_empty()) {
+        Some(existing) => match generated_range(existing) {
+            Some(range) => {
+                let mut merged = existing.to_owned();
+                merged.replace_range(range, generated);
+                merged
+            }
+            None => format!("{}\n\n{}\n", existing.trim_end(), generated),
+        },
+        None => format!("# Changelog\n\n{}\n", generated),
+    }
+}
+
+/// Insert `section`, like the one of an explicit range of commits, into the `existing` content of a changelog file right
+/// after its generated content, where it's kept when the generated content is replaced.
+///
+/// If there is no generated content, the section is appended.
+pub fn insert_section(existing: Option<&str>, section: &str) -> String {
+    let section = section.trim();
+    match existing.filter(|existing| !existing.trim().is_empty()) {
+        Some(existing) => match generated_range(existing) {
+            Some(range) => {
+                let (generated, rest) = existing.split_at(range.end);
+                let rest = rest.trim_start_matches('\n');
+                format!(
+                    "{}\n\n{}\n{}{}",
+                    generated,
+                    section,
+                    if rest.is_empty() { "" } else { "\n" },
+                    rest
+                )
+            }
+            None => format!("{}\n\n{}\n", existing.trim_end(), section),
+        },
+        None => format!("# Changelog\n\n{}\n", section),
+    }
+}
+
+/// Return true if more lines of `content` end with `\r\n` than with `\n` alone.
+pub fn uses_crlf(content: &str) -> bool {
+    let crlf = content.matches("\r\n").count();
+    crlf > content.matches('\n').count() - crlf
+}
+
+/// Return `content` with all of its lines ending in `\r\n` if `crlf` is true, or in `\n` otherwise.
+pub fn with_line_endings(content: &str, crlf: bool) -> String {
+    let content = content.replace("\r\n", "\n");
+    if crlf {
+        content.replace('\n', "\r\n")
+    } else {
+        content
+    }
+}
+
+/// Return the range of generated content in `content`, including its markers, if there is any.
+pub fn generated_range(content: &str) -> Option<Range<usize>> {
+    let start = content.find(START_MARKER)?;
+    let end = start + content[start..].find(END_MARKER)? + END_MARKER.len();
+    Some(start..end)
+}
+
+/// The sections of releases in previously generated content, as found by [`released_sections()`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReleasedSections {
+    /// The version of the first and most recent release.
+    pub newest_version: semver::Version,
+    /// The markdown of all release sections, up to the end marker.
+    pub content: String,
+}
+
+/// Return the sections of releases in `generated` content as produced by [`ChangeLog::write_to()`], starting at the
+/// first heading of a release along with its anchor, if any.
+pub fn released_sections(generated: &str) -> Option<ReleasedSections> {
+    let end = generated.rfind(END_MARKER)?;
+    let mut offset = 0;
+    let mut previous_line_start = None;
+    for line in generated[..end].split_inclusive('\n') {
+        let version = line
+            .strip_prefix("### v")
+            .and_then(|heading| heading.split_whitespace().next())
+            .and_then(|version| semver::Version::parse(version).ok());
+        if let Some(newest_version) = version {
+            let start = previous_line_start
+                .filter(|start: &usize| generated[*start..offset].starts_with("<a id="))
+                .unwrap_or(offset);
+            return Some(ReleasedSections {
+                newest_version,
+                content: generated[start..end].to_owned(),
+            });
+        }
+        previous_line_start = Some(offset);
+        offset += line.len();
+    }
+    None
+}
+
+/// Return all of `markers` found in `content` outside of its generated range, in the order of `markers`.
+pub fn hand_written_markers<'a>(content: &str, markers: &'a [String]) -> Vec<&'a str> {
+    let (before, after) = match generated_range(content) {
+        Some(range) => (&content[..range.start], &content[range.end..]),
+        None => (content, ""),
+    };
+    markers
+        .iter()
+        .map(String::as_str)
+        .filter(|marker| before.contains(marker) || after.contains(marker))
+        .collect()
+}
+
+/// Return the anchor GitHub generates for a heading with the given `text`.
+pub fn github_slug(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            slug.extend(c.to_lowercase());
+        } else if c == ' ' {
+            slug.push('-');
+        }
+    }
+    slug
+}
+
+/// Return the anchor rustdoc generates for a heading with the given `text`, which only lowercases ASCII characters.
+pub fn rustdoc_slug(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                Some(c.to_ascii_lowercase())
+            } else if c.is_ascii_whitespace() {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Keeps track of slugs to make them unique by appending a counter, like GitHub and rustdoc do for headings with the
+/// same text.
+struct Slugs {
+    anchors: Anchors,
+    counts: BTreeMap<String, usize>,
+}
+
+impl Slugs {
+    fn new(anchors: Anchors) -> Self {
+        Slugs {
+            anchors,
+            counts: Default::default(),
+        }
+    }
+
+    fn unique(&mut self, heading: &str) -> String {
+        let slug = match self.anchors {
+            Anchors::Github | Anchors::Explicit => github_slug(heading),
+            Anchors::Rustdoc => rustdoc_slug(heading),
+        };
+        let count = self.counts.entry(slug.clone()).or_insert(0);
+        let unique = if *count == 0 {
+            slug
+        } else {
+            format!("{}-{}", slug, count)
+        };
+        *count += 1;
+        unique
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod github_slug {
+        use super::github_slug;
+
+        #[test]
+        fn version_headings() {
+            assert_eq!(github_slug("Unreleased"), "unreleased");
+            assert_eq!(github_slug("v1.2.3 (2021-09-15)"), "v123-2021-09-15");
+            assert_eq!(github_slug("v0.1.0-beta.1+meta"), "v010-beta1meta");
+        }
+
+        #[test]
+        fn unicode_is_kept() {
+            assert_eq!(github_slug("Änderungen für_Alle"), "änderungen-für_alle");
+        }
+    }
+
+    mod rustdoc_slug {
+        use super::rustdoc_slug;
+
+        #[test]
+        fn version_headings() {
+            assert_eq!(rustdoc_slug("v1.2.3 (2021-09-15)"), "v123-2021-09-15");
+        }
+
+        #[test]
+        fn unicode_keeps_its_case() {
+            assert_eq!(rustdoc_slug("Änderungen für_Alle"), "Änderungen-für_alle");
+        }
+    }
+
+    mod line_endings {
+        use super::{uses_crlf, with_line_endings};
+
+        #[test]
+        fn predominant_ones_are_detected() {
+            assert!(!uses_crlf(""));
+            assert!(!uses_crlf("a\nb\r\nc\n"));
+            assert!(uses_crlf("a\r\nb\r\nc\n"));
+        }
+
+        #[test]
+        fn all_lines_are_converted() {
+            assert_eq!(with_line_endings("a\r\nb\nc", true), "a\r\nb\r\nc");
+            assert_eq!(with_line_endings("a\r\nb\nc", false), "a\nb\nc");
+        }
+    }
+
+    mod insert_section {
+        use super::{insert_section, END_MARKER, START_MARKER};
+
+        #[test]
+        fn into_nothing() {
+            assert_eq!(insert_section(None, "### a..b\n\n"), "# Changelog\n\n### a..b\n");
+        }
+
+        #[test]
+        fn into_content_without_markers_appends() {
+            assert_eq!(insert_section(Some("# Hello\n"), "### a..b\n"), "# Hello\n\n### a..b\n");
+        }
+
+        #[test]
+        fn into_content_with_markers_inserts_after_generated_content() {
+            let generated = format!("{}\nold\n{}", START_MARKER, END_MARKER);
+            assert_eq!(
+                insert_section(Some(&format!("# Hello\n\n{}\n\n### v0.1.0\n", generated)), "### a..b\n"),
+                format!("# Hello\n\n{}\n\n### a..b\n\n### v0.1.0\n", generated)
+            );
+            assert_eq!(
+                insert_section(Some(&format!("# Hello\n\n{}\n", generated)), "### a..b\n"),
+                format!("# Hello\n\n{}\n\n### a..b\n", generated)
+            );
+        }
+    }
+
+    mod merge {
+        use super::{merge, END_MARKER, START_MARKER};
+
+        #[test]
+        fn into_nothing() {
+            assert_eq!(merge(None, "new"), "# Changelog\n\nnew\n");
+        }
+
+        #[test]
+        fn into_content_without_markers_appends() {
+            assert_eq!(merge(Some("# Hello\n\nworld\n\n"), "new"), "# Hello\n\nworld\n\nnew\n");
+        }
+
+        #[test]
+        fn released_sections_start_at_the_first_release() {
+            let generated = format!(
+                "{}\n\n### Unreleased\n\n- new\n\n<a id=\"v100\"></a>\n### v1.0.0 (2021-09-09)\n\n- old\n\n### v0.1.0\n\n{}",
+                START_MARKER, END_MARKER
+            );
+            assert_eq!(
+                super::super::released_sections(&generated),
+                Some(super::super::ReleasedSections {
+                    newest_version: semver::Version::new(1, 0, 0),
+                    content: "<a id=\"v100\"></a>\n### v1.0.0 (2021-09-09)\n\n- old\n\n### v0.1.0\n\n".into()
+                })
+            );
+            let unreleased_only = format!("{}\n\n### Unreleased\n\n- new\n\n{}", START_MARKER, END_MARKER);
+            assert_eq!(super::super::released_sections(&unreleased_only), None);
+        }
+
+        #[test]
+        fn hand_written_markers_ignore_generated_content() {
+            let markers = vec!["TODO".to_string(), "<!-- fill me -->".into(), "FIXME".into()];
+            let content = format!(
+                "# Changelog\n\n<!-- fill me -->\n\n{}\n- TODO list\n{}\n\nFIXME\n",
+                START_MARKER, END_MARKER
+            );
+            assert_eq!(
+                super::super::hand_written_markers(&content, &markers),
+                vec!["<!-- fill me -->", "FIXME"]
+            );
+            assert_eq!(
+                super::super::hand_written_markers("- TODO list", &markers),
+                vec!["TODO"]
+            );
+        }
+
+        #[test]
+        fn into_content_with_markers_replaces_generated_content_only() {
+            let existing = format!("# Hello\n\n{}\nold\n{}\n\nhand-written\n", START_MARKER, END_MARKER);
+            assert_eq!(merge(Some(&existing), "new"), "# Hello\n\nnew\n\nhand-written\n");
+        }
+    }
+
+    #[test]
+    fn table_of_contents_links_to_each_section() {
+        let section = |version: Version| Section {
+            version,
+            date: None,
+            entries: Vec::new(),
+        };
+        let log = ChangeLog {
+            sections: vec![
+                section(Version::Unreleased),
+                section(Version::Semantic(semver::Version::new(1, 0, 0))),
+                section(Version::Semantic(semver::Version::new(1, 0, 0))),
+            ],
+        };
+        let mut out = String::new();
+        log.write_to(
+            &mut out,
+            &Options {
+                table_of_contents: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            format!(
+                "{}\n\n- [Unreleased](#unreleased)\n- [v1.0.0](#v100)\n- [v1.0.0](#v100-1)\n\n### Unreleased\n\n### v1.0.0\n\n### v1.0.0\n\n{}",
+                START_MARKER, END_MARKER
+            )
+        );
+    }
+
+    #[test]
+    fn explicit_anchors_precede_each_section() {
+        let log = ChangeLog {
+            sections: vec![Section {
+                version: Version::Unreleased,
+                date: None,
+                entries: Vec::new(),
+            }],
+        };
+        let mut out = String::new();
+        log.write_to(
+            &mut out,
+            &Options {
+                table_of_contents: true,
+                anchors: Anchors::Explicit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            format!(
+                "{}\n\n- [Unreleased](#unreleased)\n\n<a id=\"unreleased\"></a>\n### Unreleased\n\n{}",
+                START_MARKER, END_MARKER
+            )
+        );
+    }
+
+    #[test]
+    fn type_prefix_is_stripped_unless_kept() {
+        let entry = Entry {
+            kind: Some("feat"),
+            prefix: Some("feat(parser)".into()),
+            title: "add X".into(),
+            issues: vec!["42".into()],
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let render = |keep_type_prefix| {
+            let mut out = String::new();
+            entry
+                .write_to(
+                    &mut out,
+                    &Options {
+                        keep_type_prefix,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            out
+        };
+        assert_eq!(render(false), "- add X (#42)\n");
+        assert_eq!(render(true), "- feat(parser): add X (#42)\n");
+    }
+
+    #[test]
+    fn slugs_are_unique() {
+        let mut slugs = Slugs::new(Anchors::Github);
+        assert_eq!(slugs.unique("v1.0.0"), "v100");
+        assert_eq!(slugs.unique("v1.0.0"), "v100-1");
+        assert_eq!(slugs.unique("v1.0.0"), "v100-2");
+    }
+
+    #[test]
+    fn highlights_are_listed_first_if_enabled() {
+        let entry = |kind, title: &str, highlight| Entry {
+            kind,
+            title: title.into(),
+            highlight,
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let section = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: vec![entry(Some("feat"), "big", true), entry(Some("fix"), "small", false)],
+        };
+        let render = |highlights| {
+            let mut out = String::new();
+            section
+                .write_to(
+                    &mut out,
+                    &Options {
+                        highlights,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            out
+        };
+        assert_eq!(
+            render(true),
+            "### Unreleased\n\n#### Highlights\n\n- big\n\n#### New Features\n\n- big\n\n#### Bug Fixes\n\n- small\n\n"
+        );
+        assert_eq!(
+            render(false),
+            "### Unreleased\n\n#### New Features\n\n- big\n\n#### Bug Fixes\n\n- small\n\n"
+        );
+    }
+
+    #[test]
+    fn icons_precede_the_headings_of_their_groups() {
+        let entry = |kind| Entry {
+            kind,
+            title: "change".into(),
+            highlight: true,
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let section = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: vec![entry(Some("feat")), entry(Some("fix"))],
+        };
+        let mut out = String::new();
+        section
+            .write_to(
+                &mut out,
+                &Options {
+                    highlights: true,
+                    icons: vec![("feat", "🚀"), ("highlights", "✨")]
+                        .into_iter()
+                        .map(|(kind, icon)| (kind.to_string(), icon.to_string()))
+                        .collect(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            out,
+            "### Unreleased\n\n#### ✨ Highlights\n\n- change\n- change\n\n#### 🚀 New Features\n\n- change\n\n#### Bug Fixes\n\n- change\n\n"
+        );
+    }
+
+    #[test]
+    fn sections_without_changes_show_the_placeholder_text() {
+        let section = Section {
+            version: Version::Semantic("1.0.1".parse().unwrap()),
+            date: None,
+            entries: Vec::new(),
+        };
+        let mut out = String::new();
+        section.write_to(&mut out, &Options::default()).unwrap();
+        assert_eq!(out, "### v1.0.1\n\n");
+
+        let mut out = String::new();
+        section
+            .write_to(
+                &mut out,
+                &Options {
+                    no_changes_text: Some("Only dependencies were updated.".into()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(out, "### v1.0.1\n\nOnly dependencies were updated.\n\n");
+    }
+
+    #[test]
+    fn authors_are_listed_at_the_end_with_their_handles() {
+        let entry = |author: &str, email: &str| Entry {
+            kind: Some("fix"),
+            title: "change".into(),
+            author: author.into(),
+            author_email: email.into(),
+            ..Default::default()
+        };
+        let section = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: vec![
+                entry("Sebastian Thiel", "sebastian.thiel@icloud.com"),
+                entry("Anne", "anne@example.com"),
+                entry("Sebastian Thiel", "sebastian.thiel@icloud.com"),
+            ],
+        };
+        let mut out = String::new();
+        section
+            .write_to(
+                &mut out,
+                &Options {
+                    authors: true,
+                    author_handles: std::iter::once(("sebastian.thiel@icloud.com".to_string(), "@Byron".to_string()))
+                        .collect(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            out,
+            "### Unreleased\n\n#### Bug Fixes\n\n- change\n- change\n- change\n\n#### Authors\n\n- @Byron\n- Anne\n\n"
+        );
+    }
+
+    #[test]
+    fn summary_tallies_the_entries_of_a_section() {
+        let entry = |kind: &'static str, breaking: bool| Entry {
+            kind: Some(kind),
+            title: "change".into(),
+            breaking,
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let options = Options {
+            summary: Some("{commits} commits, {feat} features, {fix} fixes, {breaking} breaking".into()),
+            ..Default::default()
+        };
+        let section = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: vec![entry("feat", true), entry("fix", false), entry("fix", false)],
+        };
+        let mut out = String::new();
+        section.write_to(&mut out, &options).unwrap();
+        assert!(
+            out.starts_with("### Unreleased\n\n3 commits, 1 features, 2 fixes, 1 breaking\n\n#### Breaking Changes"),
+            "{}",
+            out
+        );
+        let empty = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: Vec::new(),
+        };
+        assert_eq!(
+            empty.summary(&options),
+            None,
+            "sections without changes have no summary"
+        );
+    }
+
+    #[test]
+    fn headings_are_rendered_with_their_translation_if_configured() {
+        let section = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: vec![Entry {
+                kind: Some("feat"),
+                title: "neue Sache".into(),
+                breaking: true,
+                author: "Sebastian Thiel".into(),
+                author_email: "sebastian.thiel@icloud.com".into(),
+                ..Default::default()
+            }],
+        };
+        let options = Options {
+            icons: std::iter::once(("feat".to_string(), "🚀".to_string())).collect(),
+            texts: vec![
+                ("unreleased".to_string(), "Unveröffentlicht".to_string()),
+                ("feat".to_string(), "Neue Funktionen".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let mut out = String::new();
+        section.write_to(&mut out, &options).unwrap();
+        assert_eq!(
+            out,
+            "### Unveröffentlicht\n\n#### Breaking Changes\n\n- neue Sache\n\n#### 🚀 Neue Funktionen\n\n- neue Sache\n\n"
+        );
+    }
+
+    #[test]
+    fn submodule_updates_are_listed_in_their_own_group() {
+        let entry = |title: &str, submodule_update: bool| Entry {
+            kind: Some("chore"),
+            title: title.into(),
+            submodule_update,
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let section = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: vec![entry("update vendored parser", true), entry("tidy up", false)],
+        };
+        let mut out = String::new();
+        section.write_to(&mut out, &Options::default()).unwrap();
+        assert_eq!(
+            out,
+            "### Unreleased\n\n#### Chore\n\n- tidy up\n\n#### Submodule Updates\n\n- update vendored parser\n\n"
+        );
+    }
+
+    #[test]
+    fn sections_are_named_after_their_version_in_per_version_files() {
+        let section = |version| Section {
+            version,
+            date: None,
+            entries: Vec::new(),
+        };
+        assert_eq!(
+            section(Version::Semantic("1.2.0".parse().unwrap()))
+                .file_name()
+                .as_deref(),
+            Some("1.2.0.md")
+        );
+        assert_eq!(
+            section(Version::Unreleased).file_name().as_deref(),
+            Some(UNRELEASED_FILE_NAME)
+        );
+        assert_eq!(
+            section(Version::Range {
+                from: "a".into(),
+                to: "b".into()
+            })
+            .file_name(),
+            None
+        );
+    }
+
+    #[test]
+    fn groups_are_truncated_to_their_limit() {
+        let entry = |title: &str| Entry {
+            kind: Some("fix"),
+            title: title.into(),
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let section = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: vec![entry("a"), entry("b"), entry("c")],
+        };
+        let render = |limit| {
+            let mut out = String::new();
+            section
+                .write_to(
+                    &mut out,
+                    &Options {
+                        max_entries: vec![("fix".to_string(), limit)].into_iter().collect(),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            out
+        };
+        assert_eq!(
+            render(1),
+            "### Unreleased\n\n#### Bug Fixes\n\n- a\n- ...and 2 more\n\n"
+        );
+        assert_eq!(render(3), "### Unreleased\n\n#### Bug Fixes\n\n- a\n- b\n- c\n\n");
+    }
+
+    #[test]
+    fn empty_subjects_are_skipped_or_use_the_first_body_line() {
+        let entry = |title: &str, body: Option<&str>| Entry {
+            title: title.into(),
+            body: body.map(Into::into),
+            empty_subject: title.is_empty(),
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let section = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: vec![entry("a", None), entry("", Some("\nfirst\nsecond")), entry("", None)],
+        };
+        let render = |empty_subjects| {
+            let mut out = String::new();
+            section
+                .write_to(
+                    &mut out,
+                    &Options {
+                        empty_subjects,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            out
+        };
+        assert_eq!(render(EmptySubjects::Skip), "### Unreleased\n\n#### Other\n\n- a\n\n");
+        assert_eq!(
+            render(EmptySubjects::FirstBodyLine),
+            "### Unreleased\n\n#### Other\n\n- a\n- first\n\n"
+        );
+    }
+
+    #[test]
+    fn trailers_are_stripped_from_bodies_unless_kept() {
+        let entry = Entry {
+            title: "".into(),
+            body: Some(
+                "Signed-off-by: A\nthe body\nNote: not a trailer\n\nFixes #1\nCo-authored-by: B\nChange-Id: I123"
+                    .into(),
+            ),
+            empty_subject: true,
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let mut options = Options {
+            empty_subjects: EmptySubjects::FirstBodyLine,
+            ..Default::default()
+        };
+        assert_eq!(entry.rendered_body(&options), entry.body);
+        assert_eq!(entry.rendered_title(&options), Some("Signed-off-by: A"));
+
+        options.strip_trailers = true;
+        assert_eq!(
+            entry.rendered_body(&options).as_deref(),
+            Some("the body\nNote: not a trailer")
+        );
+        assert_eq!(entry.rendered_title(&options), Some("the body"));
+
+        options.keep_trailers = vec!["co-authored-by".into()];
+        assert_eq!(
+            entry.rendered_body(&options).as_deref(),
+            Some("the body\nNote: not a trailer\n\nCo-authored-by: B")
+        );
+    }
+
+    #[test]
+    fn stripped_trailers_are_skipped_when_taking_titles_from_bodies() {
+        let log = ChangeLog {
+            sections: vec![Section {
+                version: Version::Unreleased,
+                date: None,
+                entries: vec![Entry {
+                    body: Some("Signed-off-by: A\nthe body\n\nmore of it".into()),
+                    empty_subject: true,
+                    ..Default::default()
+                }],
+            }],
+        };
+        let render = |strip_trailers| {
+            let mut out = String::new();
+            log.write_to(
+                &mut out,
+                &Options {
+                    empty_subjects: EmptySubjects::FirstBodyLine,
+                    strip_trailers,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            out
+        };
+        assert!(render(false).contains("\n- Signed-off-by: A\n"));
+        let stripped = render(true);
+        assert!(stripped.contains("\n- the body\n"), "{}", stripped);
+        assert!(
+            !stripped.contains("more of it"),
+            "the rest of the body isn't rendered by the built-in markdown"
+        );
+    }
+
+    #[test]
+    fn changed_paths_are_limited() {
+        let entry = Entry {
+            kind: Some("fix"),
+            title: "a".into(),
+            issues: vec!["1".into()],
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            changed_paths: vec!["Cargo.toml".into(), "a/".into(), "b/".into()],
+            ..Default::default()
+        };
+        let render = |changed_paths| {
+            let mut out = String::new();
+            entry
+                .write_to(
+                    &mut out,
+                    &Options {
+                        changed_paths,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            out
+        };
+        assert_eq!(render(0), "- a (#1)\n");
+        assert_eq!(render(2), "- a (#1) (changed `Cargo.toml`, `a/` and 1 more)\n");
+        assert_eq!(render(3), "- a (#1) (changed `Cargo.toml`, `a/`, `b/`)\n");
+    }
+
+    #[test]
+    fn regressions_link_to_the_commits_introducing_them() {
+        let entry = Entry {
+            kind: Some("fix"),
+            title: "a".into(),
+            issues: vec!["1".into()],
+            regressions: vec!["abc1234".into()],
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let render = |links| {
+            let mut out = String::new();
+            entry
+                .write_to(
+                    &mut out,
+                    &Options {
+                        links,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            out
+        };
+        assert_eq!(render(None), "- a (#1) (fixes regression introduced in abc1234)\n");
+        assert_eq!(
+            render(Some(Links {
+                forge: crate::changelog::forge::Forge::GitHub,
+                base_url: "https://github.com/Byron/gitoxide".into()
+            })),
+            "- a ([#1](https://github.com/Byron/gitoxide/issues/1)) (fixes regression introduced in [abc1234](https://github.com/Byron/gitoxide/commit/abc1234))\n"
+        );
+    }
+
+    #[test]
+    fn dependency_updates_are_grouped_and_collapsed() {
+        let entry = |title: &str, dependency_update| Entry {
+            kind: Some("chore"),
+            title: title.into(),
+            dependency_update,
+            author: "Sebastian Thiel".into(),
+            author_email: "sebastian.thiel@icloud.com".into(),
+            ..Default::default()
+        };
+        let section = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: vec![entry("a", false), entry("bump x", true), entry("bump y", true)],
+        };
+        let render = |dependency_updates, collapse_dependency_updates| {
+            let mut out = String::new();
+            section
+                .write_to(
+                    &mut out,
+                    &Options {
+                        dependency_updates,
+                        collapse_dependency_updates,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            out
+        };
+        assert_eq!(
+            render(DependencyUpdates::Group, None),
+            "### Unreleased\n\n#### Chore\n\n- a\n\n#### Dependencies\n\n- bump x\n- bump y\n\n"
+        );
+        assert_eq!(
+            render(DependencyUpdates::Group, Some(1)),
+            "### Unreleased\n\n#### Chore\n\n- a\n\n#### Dependencies\n\n- Updated 2 dependencies\n\n"
+        );
+        assert_eq!(
+            render(DependencyUpdates::Keep, Some(1)),
+            "### Unreleased\n\n#### Chore\n\n- a\n- bump x\n- bump y\n\n"
+        );
+    }
+
+    #[test]
+    fn bumps_of_the_same_dependency_are_coalesced() {
+        let entry = |title: &str| Entry {
+            kind: Some("build"),
+            title: title.into(),
+            dependency_update: true,
+            author: "dependabot[bot]".into(),
+            author_email: "support@github.com".into(),
+            ..Default::default()
+        };
+        let section = Section {
+            version: Version::Unreleased,
+            date: None,
+            entries: vec![
+                entry("bump serde from 1.0.4 to 1.0.5"),
+                entry("bump regex from 1.5.0 to 1.5.1"),
+                entry("update toml"),
+                entry("Bump serde from 1.0.1 to 1.0.4"),
+                entry("bump serde from 1.0.1 to 1.0.4 in /tests"),
+            ],
+        };
+        let mut out = String::new();
+        section
+            .write_to(
+                &mut out,
+                &Options {
+                    dependency_updates: DependencyUpdates::Group,
+                    coalesce_dependency_bumps: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            out,
+            "### Unreleased\n\n#### Dependencies\n\n- bumped serde from 1.0.1 to 1.0.5\n- bump regex from 1.5.0 to 1.5.1\n- update toml\n- bump serde from 1.0.1 to 1.0.4 in /tests\n\n"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 mod options;
-use options::{Args, ChangeLog, SmartRelease, SubCommands};
+use options::{Args, AuditTags, ChangeLog, Contributors, ListCrates, Phase, SmartRelease, SubCommands};
 
+use anyhow::Context;
 use cargo_smart_release::command;
 
 fn main() -> anyhow::Result<()> {
@@ -13,17 +14,68 @@ fn main() -> anyhow::Result<()> {
             crates,
             dependencies,
             allow_dirty,
-        }) => command::changelog(
-            command::changelog::Options {
+            max_commits,
+            truncate_history,
+            table_of_contents,
+            check,
+            changelog_template,
+            changelog_merge_prerelease,
+            changelog_strip_trailers,
+            keep_released_sections,
+            range,
+            changelog_date_today,
+            exclude_commit,
+            preview_all,
+            lint,
+            allow,
+            locale,
+            notes_from_pr_labels,
+        }) => {
+            let options = command::changelog::Options {
                 dry_run: !write,
                 allow_dirty,
                 dependencies,
-            },
-            crates,
-        )?,
+                max_commits,
+                truncate_history,
+                table_of_contents,
+                check,
+                preview_all,
+                merge_prereleases: changelog_merge_prerelease,
+                keep_released_sections,
+                strip_trailers: changelog_strip_trailers,
+                notes_from_pr_labels,
+                date_today: changelog_date_today,
+            };
+            match lint {
+                Some(lint_range) => {
+                    if range.is_some() || !crates.is_empty() {
+                        anyhow::bail!(
+                            "--lint checks all commits in its range and can't be used along with --range or crates"
+                        )
+                    }
+                    command::lint(
+                        options,
+                        &lint_range,
+                        &allow
+                            .iter()
+                            .map(|pattern| {
+                                regex::Regex::new(pattern)
+                                    .with_context(|| format!("Invalid pattern '{}' of commits to allow", pattern))
+                            })
+                            .collect::<anyhow::Result<Vec<_>>>()?,
+                    )?
+                }
+                None => command::changelog(options, crates, changelog_template, range, exclude_commit, locale, &[])?,
+            }
+        }
+        SubCommands::ListCrates(ListCrates {}) => command::list_crates()?,
+        SubCommands::AuditTags(AuditTags { crates }) => command::audit_tags(crates)?,
+        SubCommands::Contributors(Contributors { write, crates }) => command::contributors(!write, crates)?,
         SubCommands::SmartRelease(SmartRelease {
             execute,
+            yes,
             verbose,
+            color_diff,
             bump,
             bump_dependencies,
             crates,
@@ -31,39 +83,115 @@ fn main() -> anyhow::Result<()> {
             ignore_instability,
             skip_publish,
             skip_tag,
+            commit_sign,
+            force_tag,
             skip_push,
+            no_push_commits,
+            no_push_tags,
+            atomic_push,
             dangerously_pass_no_verify,
             no_auto_publish_of_stable_crates,
             no_conservative_pre_release_version_handling,
             dry_run_cargo_publish,
+            dry_run_cargo_publish_jobs,
             update_crates_index,
+            skip_crates_index_update,
             no_bump_on_demand,
+            semver_checks,
             skip_dependencies,
+            workspace,
+            exclude,
+            exclude_commit,
+            only,
+            bump_dependencies_only,
             no_multi_crate_release,
             no_isolate_dependencies_from_breaking_changes,
-        }) => command::release(
-            command::release::Options {
-                dry_run: !execute,
-                verbose: execute || verbose,
-                conservative_pre_release_version_handling: !no_conservative_pre_release_version_handling,
-                bump_when_needed: !no_bump_on_demand,
-                multi_crate_release: !no_multi_crate_release,
-                isolate_dependencies_from_breaking_changes: !no_isolate_dependencies_from_breaking_changes,
-                allow_dirty,
-                ignore_instability,
-                skip_publish,
-                skip_tag,
-                skip_push,
-                skip_dependencies,
-                dry_run_cargo_publish,
-                no_verify: dangerously_pass_no_verify,
-                allow_auto_publish_of_stable_crates: !no_auto_publish_of_stable_crates,
-                update_crates_index,
-            },
-            crates,
-            bump.unwrap_or_else(|| "keep".into()),
-            bump_dependencies.unwrap_or_else(|| "keep".into()),
-        )?,
+            restart,
+            release_metadata,
+            publish_arg,
+            bump_files,
+            publish_delay,
+            after_publish_sleep_for_index,
+            changelog_date_today,
+            tag_name_only,
+            explain,
+            dependencies_graph,
+            tags_are_immutable,
+            max_commits,
+            truncate_history,
+            publish_only,
+            crates_from,
+            no_update_dependents_of_kept_versions,
+            no_tag_kept_versions,
+            phase,
+        }) => {
+            let (listed_crates, explicit_versions) = match crates_from {
+                Some(path) => command::crates_from_file(&path)?,
+                None => Default::default(),
+            };
+            command::release(
+                command::release::Options {
+                    dry_run: !execute,
+                    yes,
+                    verbose: execute || verbose,
+                    color_diff,
+                    conservative_pre_release_version_handling: !no_conservative_pre_release_version_handling,
+                    bump_when_needed: !no_bump_on_demand,
+                    semver_checks,
+                    multi_crate_release: !no_multi_crate_release,
+                    isolate_dependencies_from_breaking_changes: !no_isolate_dependencies_from_breaking_changes,
+                    allow_dirty,
+                    ignore_instability,
+                    skip_publish,
+                    skip_tag,
+                    commit_sign,
+                    force_tag,
+                    skip_push,
+                    skip_push_commits: no_push_commits,
+                    skip_push_tags: no_push_tags,
+                    atomic_push,
+                    skip_dependencies,
+                    workspace,
+                    only,
+                    bump_dependencies_only,
+                    dry_run_cargo_publish,
+                    dry_run_cargo_publish_jobs,
+                    no_verify: dangerously_pass_no_verify,
+                    allow_auto_publish_of_stable_crates: !no_auto_publish_of_stable_crates,
+                    update_crates_index,
+                    skip_crates_index_update,
+                    restart,
+                    release_metadata,
+                    publish_delay: publish_delay.unwrap_or_default(),
+                    wait_for_index: after_publish_sleep_for_index,
+                    date_today: changelog_date_today,
+                    tag_name_only,
+                    explain,
+                    dependencies_graph,
+                    tags_are_immutable,
+                    max_commits,
+                    truncate_history,
+                    publish_only: publish_only.is_some(),
+                    update_dependents_of_kept_versions: !no_update_dependents_of_kept_versions,
+                    tag_kept_versions: !no_tag_kept_versions,
+                    phase: match phase {
+                        None => command::release::Phase::All,
+                        Some(Phase::Prepare(_)) => command::release::Phase::Prepare,
+                        Some(Phase::Publish(_)) => command::release::Phase::Publish,
+                    },
+                },
+                publish_only.into_iter().chain(crates).chain(listed_crates).collect(),
+                bump.unwrap_or_else(|| "keep".into()),
+                bump_dependencies.unwrap_or_else(|| "keep".into()),
+                command::release::Inputs {
+                    excluded: exclude,
+                    excluded_commits: exclude_commit,
+                    publish_args: publish_arg,
+                    bump_files,
+                    version_transforms: vec![Box::new(explicit_versions)],
+                },
+            )?
+        }
     };
 
     Ok(())
@@ -1,4 +1,7 @@
+use std::{path::PathBuf, time::Duration};
+
 use argh::FromArgs;
+use cargo_smart_release::{changelog::parse_date, command::release::GraphFormat};
 
 #[derive(FromArgs)]
 #[argh(name = "cargo smart-release")]
@@ -15,12 +18,57 @@ pub struct Args {
 pub enum SubCommands {
     SmartRelease(SmartRelease),
     Changelog(ChangeLog),
+    ListCrates(ListCrates),
+    AuditTags(AuditTags),
+    Contributors(Contributors),
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "list-crates")]
+/// List all workspace crates with their version, whether they are publishable and the prefix of their tags.
+///
+/// This only shows what smart-release sees and never changes anything.
+pub struct ListCrates {}
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "audit-tags")]
+/// Report versions of crates which are released according to their changelog, but have no tag.
+///
+/// This never changes anything, and fails if any tag is missing.
+pub struct AuditTags {
+    /// the name of the crates to audit.
+    ///
+    /// Defaults to all publishable workspace members if unset.
+    #[argh(positional)]
+    pub crates: Vec<String>,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "contributors")]
+/// Write a CONTRIBUTORS.md file next to the manifest of crates, listing the authors of all changes to the crate in its
+/// history.
+///
+/// Authors are deduplicated by email address and listed with their handle if one is configured in `author-handles`.
+/// Use --write to actually write the files, otherwise they are printed.
+pub struct Contributors {
+    /// actually write the contributors files.
+    #[argh(switch, short = 'w')]
+    pub write: bool,
+
+    /// the name of the crates to write the contributors file for.
+    ///
+    /// Defaults to the top-level workspace crate if unset.
+    #[argh(positional)]
+    pub crates: Vec<String>,
 }
+
 #[derive(FromArgs)]
 #[argh(subcommand, name = "changelog")]
 /// Generate changelogs from commit histories, none-destructively.
 ///
-/// Use --write to actually write generated changelogs
+/// Use --write to actually write generated changelogs. Each changelog gets a section for every release tag of its crate
+/// in the history, which back-fills complete changelogs of crates that never had one, and re-running it yields the same
+/// result.
 pub struct ChangeLog {
     /// actually write the changelog to the respective files
     #[argh(switch, short = 'w')]
@@ -39,6 +87,103 @@ pub struct ChangeLog {
     #[argh(switch)]
     pub dependencies: bool,
 
+    /// fail if the commit history to traverse has more than the given amount of commits.
+    ///
+    /// This is a safety valve to avoid walking enormous histories by accident, for instance in CI.
+    #[argh(option)]
+    pub max_commits: Option<usize>,
+
+    /// when the commit history exceeds --max-commits, stop traversing it with a warning instead of failing.
+    #[argh(switch)]
+    pub truncate_history: bool,
+
+    /// render a table of contents linking to each release at the top of the generated part of the changelog.
+    ///
+    /// It is regenerated along with all other generated content, which is useful for crates with many releases.
+    #[argh(switch)]
+    pub table_of_contents: bool,
+
+    /// fail if the generated content of any changelog differs from what would be generated now, without writing anything.
+    ///
+    /// Hand-written content outside of the generated section is ignored, which makes this suitable for CI.
+    #[argh(switch)]
+    pub check: bool,
+
+    /// a file with a template to render the generated part of changelogs with, instead of the built-in markdown.
+    ///
+    /// It uses the tinytemplate syntax and receives the crate name along with all sections, their entries and
+    /// contributors.
+    #[argh(option)]
+    pub changelog_template: Option<PathBuf>,
+
+    /// merge the changes of pre-releases like `1.0.0-rc.1` into the section of the release of their version, like `1.0.0`,
+    /// instead of listing them in sections of their own.
+    #[argh(switch)]
+    pub changelog_merge_prerelease: bool,
+
+    /// remove trailers like `Signed-off-by: X` or `Fixes #1` from the bodies of changes, except for those listed in the
+    /// `keep-trailers` configuration.
+    ///
+    /// The built-in markdown doesn't render bodies, so it only affects the `body` of entries in --changelog-template
+    /// output and the first body line used as title of changes with an empty subject.
+    #[argh(switch)]
+    pub changelog_strip_trailers: bool,
+
+    /// keep the sections of releases already in the generated part of changelogs as they are, and only generate the
+    /// sections of newer releases and of unreleased changes.
+    ///
+    /// Can't be used with --changelog-template or --table-of-contents.
+    #[argh(switch)]
+    pub keep_released_sections: bool,
+
+    /// print the changes in the given range of commits, like `v1.0.0..main`, instead of generating changelogs from tags.
+    ///
+    /// With --write, the section is inserted after the generated part of each changelog to keep it when regenerating.
+    /// The start of the range must be an ancestor of its end. Can't be used with --check.
+    #[argh(option)]
+    pub range: Option<String>,
+
+    /// the day to consider today, like '2021-09-15', when dating the section of the range given with --range.
+    ///
+    /// Without it, the day of the 'SOURCE_DATE_EPOCH' environment variable is used if set, or the current day.
+    #[argh(option, from_str_fn(parse_date))]
+    pub changelog_date_today: Option<time::Date>,
+
+    /// the full or abbreviated id of a commit whose changes to leave out of changelogs. Can be specified multiple times.
+    #[argh(option)]
+    pub exclude_commit: Vec<String>,
+
+    /// print the unreleased changes of all publishable workspace crates as one markdown document, without writing anything.
+    ///
+    /// Crates can't be specified along with it. Can't be used with --write, --check or --range.
+    #[argh(switch)]
+    pub preview_all: bool,
+
+    /// list the commits in the given range, like 'v1.0.0..main', whose message doesn't follow the conventional commit
+    /// format, and fail if there are any, instead of generating changelogs.
+    ///
+    /// Commits classified by the configured rules and release commits are considered compliant.
+    #[argh(option)]
+    pub lint: Option<String>,
+
+    /// a regular expression matching the titles of commits which are allowed not to follow the conventional commit
+    /// format when using --lint, like '^Merge '. Can be specified multiple times.
+    #[argh(option)]
+    pub allow: Vec<String>,
+
+    /// the locale, like 'de', whose translations of headings from the `translations` configuration to render into
+    /// changelogs instead of the english ones.
+    #[argh(option)]
+    pub locale: Option<String>,
+
+    /// classify changes squash-merged from GitHub pull requests, whose subject ends in '(#N)', by the labels of these,
+    /// as configured in `pr-labels`, like 'enhancement' or 'breaking'.
+    ///
+    /// Labels are fetched with 'curl' using the token in GITHUB_TOKEN or GH_TOKEN, if set. If GitHub can't be reached,
+    /// changes are classified by their commit message with a warning.
+    #[argh(switch)]
+    pub notes_from_pr_labels: bool,
+
     /// the name of the crates to generate a changelog for.
     ///
     /// Defaults to the top-level workspace crate if unset.
@@ -56,16 +201,29 @@ pub struct SmartRelease {
     #[argh(switch, short = 'n')]
     pub execute: bool,
 
+    /// don't ask for confirmation of the release plan before releasing with --execute.
+    ///
+    /// This is required if stdin isn't a terminal, like on CI.
+    #[argh(switch, short = 'y')]
+    pub yes: bool,
+
     /// provide more detailed messages on the INFO log level in dry-run mode.
     ///
     /// Note --verbose is implied with --execute.
     #[argh(switch, short = 'v')]
     pub verbose: bool,
 
+    /// in dry-run mode, print the changes to each manifest as unified diff, colored if stdout is a terminal and
+    /// NO_COLOR isn't set.
+    #[argh(switch)]
+    pub color_diff: bool,
+
     /// as dependencies are automatically picked up for release, put all manifest changes into a single commit.
     ///
     /// If this flag is set, each dependency will yield its own commit with respective manifest changes, possibly
     /// adding a lot of additional commits even though the release could have looked like coherent.
+    /// Each crate is then tagged on its own commit, and commits are created in the order crates are published in.
+    /// Crates depending on each other in a cycle are still released in a single commit as they are published together.
     #[argh(switch)]
     pub no_multi_crate_release: bool,
 
@@ -75,11 +233,25 @@ pub struct SmartRelease {
     #[argh(switch)]
     pub no_bump_on_demand: bool,
 
+    /// run 'cargo semver-checks' on each crate to release that was published before, and raise its bump to a breaking
+    /// one if breaking changes to its API are detected, even if no commit was marked as breaking. API changes which only
+    /// require a new minor version raise it to a minor bump.
+    ///
+    /// 'cargo-semver-checks' must be installed.
+    #[argh(switch)]
+    pub semver_checks: bool,
+
     /// additionally run 'cargo publish --dry-run' when --execute is not set. This can be useful to see which local
     /// crates do not build with the released versions of their workspace dependencies anymore.
     #[argh(switch)]
     pub dry_run_cargo_publish: bool,
 
+    /// the maximum amount of 'cargo publish --dry-run' invocations to run at once with --dry-run-cargo-publish, which
+    /// defaults to the amount of available CPUs. All of them run before anything else and their failures are reported
+    /// together.
+    #[argh(option)]
+    pub dry_run_cargo_publish_jobs: Option<usize>,
+
     /// allow publishes to take place on a dirty working tree. Really not recommended alongside --execute.
     #[argh(switch)]
     pub allow_dirty: bool,
@@ -89,6 +261,13 @@ pub struct SmartRelease {
     #[argh(switch, short = 'u')]
     pub update_crates_index: bool,
 
+    /// never update the crates-index, not even while waiting for published versions with
+    /// --after-publish-sleep-for-index, but determine published versions with the index as present locally.
+    ///
+    /// This allows releasing in offline environments with a pre-populated index.
+    #[argh(switch)]
+    pub skip_crates_index_update: bool,
+
     /// disallow to also publish stable crates when discoverying changed crates, bumping their version according to -d <spec>.
     #[argh(switch)]
     pub no_auto_publish_of_stable_crates: bool,
@@ -115,16 +294,176 @@ pub struct SmartRelease {
     #[argh(switch)]
     pub skip_tag: bool,
 
+    /// sign the release commit with the key configured in 'user.signingkey', or the default key of the committer.
+    ///
+    /// If signing fails, the release is aborted before anything is tagged or published.
+    #[argh(switch)]
+    pub commit_sign: bool,
+
+    /// move tags of the new versions to the release commit if they exist already, instead of refusing to release.
+    ///
+    /// This can happen if a previous release was aborted after tagging, without its state to resume it.
+    #[argh(switch)]
+    pub force_tag: bool,
+
     /// don't push tags and the HEAD branch after any successful run of `cargo publish`.
     #[argh(switch)]
     pub skip_push: bool,
 
+    /// don't push the HEAD branch with the release commit, but push tags unless --no-push-tags is set as well.
+    #[argh(switch)]
+    pub no_push_commits: bool,
+
+    /// don't push tags, but push the HEAD branch unless --no-push-commits is set as well.
+    ///
+    /// This is useful to push tags only after CI validated the release commit.
+    #[argh(switch)]
+    pub no_push_tags: bool,
+
+    /// push the HEAD branch and all tags in a single atomic `git push`, so that either all of them or none are updated.
+    ///
+    /// Without it, the branch and tags are pushed separately which may leave a release partially pushed if one of them
+    /// is rejected.
+    #[argh(switch)]
+    pub atomic_push: bool,
+
     /// do not take into consideration any dependencies of the crates to publish.
     ///
     /// This flag is useful when various `--skip-X` are specified in order to bump versions only, without publishing.
     #[argh(switch)]
     pub skip_dependencies: bool,
 
+    /// release all publishable workspace members, that is those without 'publish = false', instead of the given crates.
+    #[argh(switch)]
+    pub workspace: bool,
+
+    /// the name of a crate to exclude from the release when --workspace is set. Can be specified multiple times.
+    #[argh(option)]
+    pub exclude: Vec<String>,
+
+    /// the full or abbreviated id of a commit whose changes don't count when determining if a crate changed since its
+    /// last release, and which is left out of release metadata. Can be specified multiple times.
+    #[argh(option)]
+    pub exclude_commit: Vec<String>,
+
+    /// release exactly the given crates, without automatically including changed dependencies or bumping dependents.
+    ///
+    /// This is like --skip-dependencies and --no-isolate-dependencies-from-breaking-changes combined, and fails if
+    /// a workspace dependency of the given crates isn't published in a compatible version.
+    #[argh(switch)]
+    pub only: bool,
+
+    /// release the given crates only if one of their workspace dependencies is released, ignoring their own changes.
+    ///
+    /// They are bumped like their dependencies with --bump-dependencies, which is useful to isolate releases that
+    /// merely pick up dependency updates. Can't be used with --bump.
+    #[argh(switch)]
+    pub bump_dependencies_only: bool,
+
+    /// an additional argument to pass to 'cargo publish', like '--no-default-features'. Can be specified multiple times,
+    /// like '--publish-arg --features --publish-arg "a b"', and each is passed as is.
+    ///
+    /// Crates can set their own with 'publish-args' in '[package.metadata.smart-release]', which takes precedence.
+    /// Flags controlled by cargo smart-release itself, like '--no-verify' or '--dry-run', are rejected.
+    #[argh(option)]
+    pub publish_arg: Vec<String>,
+
+    /// a glob pattern relative to the directory of each crate to bump, like 'README.md', of files in which to replace
+    /// its current version with the new one as well. Can be specified multiple times.
+    ///
+    /// Crates can configure files along with patterns matching the version in them with 'bump-files' in
+    /// '[package.metadata.smart-release]'.
+    #[argh(option)]
+    pub bump_files: Vec<String>,
+
+    /// write a 'release-<version>.toml' file with the version, tag, release commit, date and included commits of
+    /// each released crate to '<target-dir>/smart-release/<crate>/' for use by other tools.
+    #[argh(switch)]
+    pub release_metadata: bool,
+
+    /// the time to wait between publishing successive crates, like '5s', '500ms' or '1m', to respect rate limits of
+    /// crates.io. Plain numbers are seconds.
+    #[argh(option, from_str_fn(parse_duration))]
+    pub publish_delay: Option<Duration>,
+
+    /// after publishing each crate, update the crates.io index until the new version is visible in it, waiting at most
+    /// the given time, like '5m' or '90s'.
+    ///
+    /// This assures dependents can be verified against the new version when they are published next.
+    #[argh(option, from_str_fn(parse_duration))]
+    pub after_publish_sleep_for_index: Option<Duration>,
+
+    /// the day to consider today, like '2021-09-15', when dating the release for reproducible results.
+    ///
+    /// Without it, the day of the 'SOURCE_DATE_EPOCH' environment variable is used if set, or the current day.
+    #[argh(option, from_str_fn(parse_date))]
+    pub changelog_date_today: Option<time::Date>,
+
+    /// print the names of the tags that would be created for the computed versions of all crates to release, one
+    /// per line, and exit without changing anything.
+    #[argh(switch)]
+    pub tag_name_only: bool,
+
+    /// print why each crate is released with its new version, including the commits since its last release along
+    /// with their type, and the safety bumps of dependents, and exit without changing anything.
+    #[argh(switch)]
+    pub explain: bool,
+
+    /// print the dependency graph of all crates to release and the dependents receiving a safety bump or an updated
+    /// dependency requirement, marking dependencies with a breaking bump, and exit without changing anything.
+    ///
+    /// Use 'text' for a list of each crate with its dependencies, or 'dot' for Graphviz, like
+    /// '--dependencies-graph dot | dot -Tsvg > release.svg'.
+    #[argh(option, from_str_fn(parse_graph_format))]
+    pub dependencies_graph: Option<GraphFormat>,
+
+    /// fail if a release tag of any workspace crate points to a commit which isn't reachable from HEAD anymore.
+    ///
+    /// This guards against releasing on top of rewritten history, like after a force-push or rebase that orphaned
+    /// the commits of previous releases.
+    #[argh(switch)]
+    pub tags_are_immutable: bool,
+
+    /// fail if the commit history to traverse has more than the given amount of commits.
+    ///
+    /// The history is only traversed when needed, like for --explain, --release-metadata or --tags-are-immutable.
+    #[argh(option)]
+    pub max_commits: Option<usize>,
+
+    /// when the commit history exceeds --max-commits, stop traversing it with a warning instead of failing.
+    #[argh(switch)]
+    pub truncate_history: bool,
+
+    /// a file listing the crates to release, one per line and optionally followed by the version to release it at, like
+    /// 'my-crate 1.2.0', in addition to the ones given as arguments. Empty lines and everything after '#' are ignored.
+    ///
+    /// Versions given this way take precedence over computed ones and those of a version-hook.
+    #[argh(option)]
+    pub crates_from: Option<PathBuf>,
+
+    /// only publish the given crate at the version in its manifest and push its existing release tag.
+    ///
+    /// This is for releases which were committed and tagged, but failed to publish, with the tagged commit checked out.
+    /// Versions aren't computed and manifests aren't edited.
+    #[argh(option)]
+    pub publish_only: Option<String>,
+
+    /// don't update the version requirements of dependents on crates whose version is kept, like with '--bump keep'.
+    ///
+    /// By default, their requirements are updated to the kept version just like for bumped crates.
+    #[argh(switch)]
+    pub no_update_dependents_of_kept_versions: bool,
+
+    /// don't tag crates whose version is kept, like with '--bump keep', which are tagged with it by default.
+    #[argh(switch)]
+    pub no_tag_kept_versions: bool,
+
+    /// ignore the state of a previous release that failed midway and start over instead of resuming it.
+    ///
+    /// Without it, crates that were already published or tagged in the previous run will not be published or tagged again.
+    #[argh(switch)]
+    pub restart: bool,
+
     /// pass --no-verify to 'cargo publish' which should only be a last resort when fixing up packages that
     /// otherwise wouldn't publish, but need to be publish to resolve the situation.
     #[argh(switch)]
@@ -142,6 +481,8 @@ pub struct SmartRelease {
     ///
     /// Can be 'major', 'minor' or 'patch', or 'keep' which doesn't alter the version.
     /// If unspecified, the current version will be kept, useful if versions are specified manually.
+    /// Kept versions are otherwise treated like bumped ones: requirements of dependents are updated to them and they
+    /// are tagged, unless --no-update-dependents-of-kept-versions or --no-tag-kept-versions are given.
     #[argh(option, short = 'b')]
     pub bump: Option<String>,
 
@@ -157,4 +498,80 @@ pub struct SmartRelease {
     /// Defaults to the top-level workspace crate if unset.
     #[argh(positional)]
     pub crates: Vec<String>,
+
+    #[argh(subcommand)]
+    pub phase: Option<Phase>,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+pub enum Phase {
+    Prepare(Prepare),
+    Publish(Publish),
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "prepare")]
+/// Only bump versions in manifests and create the release commit, without tagging, publishing or pushing.
+///
+/// Use 'publish' once the release commit was reviewed and merged.
+pub struct Prepare {}
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "publish")]
+/// Only publish, tag and push crates of a release created with 'prepare', using the versions in their manifests.
+///
+/// Tags are created for the current HEAD commit.
+pub struct Publish {}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let unit_start = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let amount: u64 = value[..unit_start]
+        .parse()
+        .map_err(|_| format!("'{}' must start with a number, like '5s'", value))?;
+    match &value[unit_start..] {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "" | "s" => Ok(Duration::from_secs(amount)),
+        "m" => amount
+            .checked_mul(60)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("'{}' is too long", value)),
+        unit => Err(format!("Unknown unit '{}' in '{}' - use 'ms', 's' or 'm'", unit, value)),
+    }
+}
+
+fn parse_graph_format(value: &str) -> Result<GraphFormat, String> {
+    match value {
+        "text" => Ok(GraphFormat::Text),
+        "dot" => Ok(GraphFormat::Dot),
+        _ => Err(format!("Unknown graph format '{}' - use 'text' or 'dot'", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::parse_duration;
+
+    #[test]
+    fn durations_with_and_without_units() {
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("5"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(
+            parse_duration("5h"),
+            Err("Unknown unit 'h' in '5h' - use 'ms', 's' or 'm'".into())
+        );
+        assert_eq!(
+            parse_duration("s"),
+            Err("'s' must start with a number, like '5s'".into())
+        );
+        assert_eq!(
+            parse_duration("18446744073709551615m"),
+            Err("'18446744073709551615m' is too long".into()),
+            "overflows are an error instead of a panic"
+        );
+    }
 }
@@ -0,0 +1,55 @@
+use anyhow::bail;
+
+use crate::{
+    command::changelog_impl::{changelog_path, git},
+    utils::{package_by_name, parse_tag_version, tag_name, tag_prefix},
+};
+
+/// Print the versions which are released according to the changelogs of `crates`, or of all publishable workspace
+/// members if none are given, but have no tag, and fail if there is any.
+///
+/// This is purely diagnostic and never changes anything.
+pub fn audit_tags(crates: Vec<String>) -> anyhow::Result<()> {
+    let ctx = if crates.is_empty() {
+        crate::Context::for_workspace(&[])?
+    } else {
+        crate::Context::new(crates)?
+    };
+    let mut num_missing = 0;
+    for crate_name in &ctx.crate_names {
+        let package = package_by_name(&ctx.meta, crate_name)?;
+        let path = changelog_path(package)?;
+        let markdown = match std::fs::read_to_string(&path) {
+            Ok(markdown) => markdown,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                log::info!("Skipping '{}' as it has no changelog at '{}'", crate_name, path);
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let prefix = tag_prefix(package, &ctx.repo);
+        let tagged_versions: Vec<_> = git::tags(package, &ctx)?
+            .iter()
+            .filter_map(|tag| parse_tag_version(prefix, git::strip_tag_path(tag.name.as_bstr())))
+            .collect();
+        for version in crate::changelog::released_versions(&markdown) {
+            if !tagged_versions.contains(&version) {
+                println!(
+                    "{} v{} has no tag '{}'",
+                    crate_name,
+                    version,
+                    tag_name(package, &version.to_string(), &ctx.repo)
+                );
+                num_missing += 1;
+            }
+        }
+    }
+    if num_missing != 0 {
+        bail!(
+            "{} version(s) released according to changelogs have no tag",
+            num_missing
+        );
+    }
+    log::info!("All versions released according to changelogs have a tag");
+    Ok(())
+}
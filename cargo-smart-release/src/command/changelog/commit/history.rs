@@ -1,16 +1,106 @@
+use std::fmt;
+
 use git_repository as git;
 
 use crate::command::changelog_impl::commit::Message;
 
 /// A head reference will all commits that are 'governed' by it, that is are in its exclusive ancestry.
 pub struct Segment<'a> {
-    pub _head: git::refs::Reference,
+    pub head: git::refs::Reference,
     /// only relevant history items, that is those that change code in the respective crate.
     pub history: Vec<&'a Item>,
+    /// The ids of the items in `history` which only update the commits of submodules in the respective crate, if these
+    /// are to be grouped.
+    pub submodule_updates: std::collections::BTreeSet<git::hash::ObjectId>,
 }
 
 pub struct Item {
     pub id: git::hash::ObjectId,
-    pub _message: Message,
-    pub tree_data: Vec<u8>,
+    pub message: Message,
+    /// The name of the author of the commit.
+    pub author: String,
+    /// The email address of the author of the commit.
+    pub author_email: String,
+    /// The time at which the commit was committed.
+    pub commit_time: git::actor::Time,
+    /// The number of the pull request the commit was squash-merged from, as indicated by a subject line ending in `(#N)`.
+    pub pull_request: Option<u64>,
+    /// The ids of the parents of the commit.
+    pub parents: Vec<git::hash::ObjectId>,
+    /// The id of the tree of the commit.
+    pub tree_id: git::hash::ObjectId,
+    /// The entries of the tree of the commit at each of the paths returned by `indexed_paths()`, in the same order, or
+    /// `None` where there is no such entry.
+    pub path_entries: Vec<Option<crate::git::TreeEntry>>,
+}
+
+/// A noteworthy condition encountered while traversing the commit history, collected so it can be presented along with
+/// all others at the end of a run instead of being logged right away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// The message of the commit with the given `id` isn't valid UTF-8, so the commit was ignored.
+    UndecodableMessage { id: git::hash::ObjectId },
+    /// The traversal stopped after `num_commits` as requested with `--truncate-history`.
+    Truncated { num_commits: usize },
+    /// The tag named `ignored` of `crate_name` points to the same commit as the one named `used`, which takes precedence.
+    TagOnSameCommit {
+        crate_name: String,
+        ignored: String,
+        used: String,
+    },
+    /// The `tags` of `crate_name` are on branches which are ignored during traversal.
+    TagsOnIgnoredBranches { crate_name: String, tags: Vec<String> },
+    /// The subject of the commit with the given `id` is marked as breaking with `!`, but as the `BREAKING CHANGE` footer
+    /// is required and missing, the change isn't considered breaking.
+    BreakingMarkerWithoutFooter { id: git::hash::ObjectId },
+    /// The labels of pull requests couldn't be fetched due to `error`, so the remaining changes are classified by their
+    /// commit message only.
+    PullRequestLabelsUnavailable { error: String },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::UndecodableMessage { id } => {
+                write!(f, "Commit message of {} could not be decoded to UTF-8 - ignored", id)
+            }
+            Warning::Truncated { num_commits } => write!(
+                f,
+                "Stopped traversing the commit history after {} commits as requested by --max-commits",
+                num_commits
+            ),
+            Warning::TagOnSameCommit {
+                crate_name,
+                ignored,
+                used,
+            } => write!(
+                f,
+                "{}: Ignoring tag '{}' as '{}' points to the same commit and takes precedence",
+                crate_name, ignored, used
+            ),
+            Warning::TagsOnIgnoredBranches { crate_name, tags } => write!(
+                f,
+                "{}: The following tags were on branches which are ignored during traversal: {}",
+                crate_name,
+                tags.join(", ")
+            ),
+            Warning::BreakingMarkerWithoutFooter { id } => write!(
+                f,
+                "Commit {} is marked as breaking with '!' but has no 'BREAKING CHANGE' footer - it's not considered breaking",
+                id
+            ),
+            Warning::PullRequestLabelsUnavailable { error } => write!(
+                f,
+                "Classifying remaining changes by their commit message only as labels of pull requests are unavailable: {}",
+                error
+            ),
+        }
+    }
+}
+
+/// Log all `warnings` together, if there are any.
+pub fn log_warnings(warnings: &[Warning]) {
+    for warning in warnings {
+        log::warn!("{}", warning);
+    }
 }
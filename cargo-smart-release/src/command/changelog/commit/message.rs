@@ -1,14 +1,19 @@
-use git_conventional::Type;
+use std::collections::BTreeMap;
+
 use git_repository as git;
 use git_repository::bstr::{BStr, ByteSlice};
 
-use crate::command::changelog_impl::commit::Message;
+use crate::{
+    command::changelog_impl::commit::Message,
+    config::{Bump, ClassifyRule, PrLabel, UnknownTypes},
+};
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq, Eq))]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Addition {
     /// The plain issue ID, like "123".
     IssueId(String),
+    /// The full or abbreviated id of the commit which introduced a regression fixed by this one, like "abc1234".
+    RegressionOf(String),
 }
 
 mod additions {
@@ -57,6 +62,49 @@ mod additions {
         (title, additions)
     }
 
+    /// Keywords introducing a footer with issue references, like `Fixes #1, #2`, as understood by most forges.
+    const ISSUE_KEYWORDS: &[&str] = &[
+        "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+    ];
+
+    /// Return all issues referenced in lines of `body` starting with one of the `ISSUE_KEYWORDS`, like `Fixes #1, #2`
+    /// or `Closes: #3`, in order of appearance.
+    pub fn from_footers(body: &str) -> Vec<Addition> {
+        body.lines()
+            .filter_map(|line| {
+                let line = line.trim_start();
+                let keyword_end = line.find(|c: char| c == ':' || c.is_whitespace())?;
+                ISSUE_KEYWORDS
+                    .iter()
+                    .any(|k| k.eq_ignore_ascii_case(&line[..keyword_end]))
+                    .then(|| &line[keyword_end..])
+            })
+            .flat_map(|references| {
+                references.split('#').skip(1).filter_map(|reference| {
+                    let id_end = reference
+                        .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+                        .unwrap_or(reference.len());
+                    (id_end != 0).then(|| Addition::IssueId(reference[..id_end].to_owned()))
+                })
+            })
+            .collect()
+    }
+
+    /// Return the commits referenced in `Fixes: <commit>` footers of `body`, like `Fixes: abc1234 ("title")`, as the
+    /// commits which introduced the regressions fixed by it.
+    pub fn regressions_from_footers(body: &str) -> Vec<Addition> {
+        body.lines()
+            .filter_map(|line| {
+                let (token, value) = line.trim_start().split_once(':')?;
+                let id = value.split_whitespace().next()?;
+                (token.eq_ignore_ascii_case("fixes")
+                    && (7..=40).contains(&id.len())
+                    && id.chars().all(|c| c.is_ascii_hexdigit()))
+                .then(|| Addition::RegressionOf(id.to_owned()))
+            })
+            .collect()
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -68,6 +116,29 @@ mod additions {
             assert_eq!(a, vec![]);
         }
 
+        #[test]
+        fn issues_from_footers() {
+            assert_eq!(
+                from_footers("body mentions #4\n\nFixes #1, #2 and #3\ncloses: #other\nSigned-off-by: foo #5"),
+                vec![
+                    Addition::IssueId("1".into()),
+                    Addition::IssueId("2".into()),
+                    Addition::IssueId("3".into()),
+                    Addition::IssueId("other".into())
+                ]
+            );
+        }
+
+        #[test]
+        fn regressions_from_footers() {
+            let body = "Fixes: abc1234 (\"feat: the culprit\")\nFixes: #2\nFixes: 12345\nRefs: def5678";
+            assert_eq!(
+                super::regressions_from_footers(body),
+                vec![Addition::RegressionOf("abc1234".into())]
+            );
+            assert_eq!(from_footers(body), vec![Addition::IssueId("2".into())]);
+        }
+
         #[test]
         fn strip_multiple_issue_numbers() {
             let (nt, a) = strip("(#other) foo (#123) hello (#42)".into());
@@ -84,43 +155,249 @@ mod additions {
     }
 }
 
+/// How to parse commit messages with [`Message::parse()`].
+#[derive(Debug, Clone, Copy)]
+pub struct ParseOptions<'a> {
+    /// Conventional commit types mapped to their canonical name, like `feature` to `feat`.
+    pub type_aliases: &'a BTreeMap<String, String>,
+    /// The rules to classify messages by their subject if they don't follow the conventional commit format.
+    pub rules: &'a [ClassifyRule],
+    /// Whether messages with a conventional commit type that isn't known are classified by `rules` as well.
+    pub unknown_types: UnknownTypes,
+    /// If set, a `!` in the subject only marks a breaking change along with a `BREAKING CHANGE` footer.
+    pub require_breaking_footer: bool,
+}
+
+impl Default for ParseOptions<'_> {
+    /// Parse conventional commit messages without type aliases or rules, listing unknown types as `Other`.
+    fn default() -> Self {
+        static NO_TYPE_ALIASES: BTreeMap<String, String> = BTreeMap::new();
+        ParseOptions {
+            type_aliases: &NO_TYPE_ALIASES,
+            rules: &[],
+            unknown_types: UnknownTypes::Other,
+            require_breaking_footer: false,
+        }
+    }
+}
+
 impl From<&'_ str> for Message {
     fn from(m: &str) -> Self {
-        let (title, kind, body, breaking, breaking_description) = git_conventional::Commit::parse(m)
-            .map(|c: git_conventional::Commit| {
-                (
-                    c.description().into(),
-                    Some(c.type_()),
-                    c.body().map(Into::into),
-                    c.breaking(),
-                    c.breaking_description()
-                        .and_then(|d| if d == c.description() { None } else { Some(d) }),
-                )
-            })
-            .unwrap_or_else(|_| {
-                let m = git::objs::commit::MessageRef::from_bytes(m.as_bytes());
+        Message::parse(m, ParseOptions::default())
+    }
+}
+
+impl Message {
+    /// Parse `m` like its `From<&str>` implementation, but map conventional commit types to their canonical name
+    /// using `type_aliases` first, so `feature` can be treated like `feat`.
+    ///
+    /// Messages not following the conventional commit format are classified by the first of the `rules` matching their
+    /// subject, just like those with a type that isn't known if `unknown_types` says so.
+    /// Messages whose first line is blank have an empty title and keep everything else as their body.
+    pub fn parse(
+        m: &str,
+        ParseOptions {
+            type_aliases,
+            rules,
+            unknown_types,
+            require_breaking_footer,
+        }: ParseOptions<'_>,
+    ) -> Self {
+        let subject_is_blank = m.lines().next().unwrap_or_default().trim().is_empty();
+        let mut breaking_marker_without_footer = false;
+        let mut scope = None;
+        let summary = || {
+            git::objs::commit::MessageRef::from_bytes(m.as_bytes())
+                .summary()
+                .as_ref()
+                .to_string()
+        };
+        let resolve_alias = |kind: &str| type_aliases.get(kind).map(String::as_str).unwrap_or(kind).to_owned();
+        let (title, kind, prefix, body, breaking, breaking_description, highlight, dependency_update) =
+            if subject_is_blank {
                 (
-                    m.summary().as_ref().to_string().into(),
+                    "".into(),
+                    None,
                     None,
-                    m.body().map(|b| b.without_trailer().to_str_lossy()),
+                    Some(m.trim()).filter(|b| !b.is_empty()).map(Into::into),
                     false,
                     None,
+                    false,
+                    false,
                 )
-            });
-        let (title, additions) = additions::strip(title);
+            } else {
+                git_conventional::Commit::parse(m)
+                    .map(|c: git_conventional::Commit| {
+                        breaking_marker_without_footer = c.breaking() && !c.footers().iter().any(|f| f.breaking());
+                        scope = c
+                            .scope()
+                            .map(|s| s.as_str().trim().to_owned())
+                            .filter(|s| !s.is_empty());
+                        let (title, kind, prefix, breaking_by_rule) = if unknown_types == UnknownTypes::Unconventional
+                            && is_unknown_type(&resolve_alias(c.type_().as_str()))
+                        {
+                            classify(summary(), rules)
+                        } else {
+                            (
+                                c.description().to_owned(),
+                                Some(c.type_().as_str().to_owned()),
+                                m.lines()
+                                    .next()
+                                    .and_then(|l| l.split_once(':'))
+                                    .map(|(p, _)| p.trim().to_owned()),
+                                false,
+                            )
+                        };
+                        (
+                            title.into(),
+                            kind,
+                            prefix,
+                            c.body().map(Into::into),
+                            breaking_by_rule
+                                || (c.breaking() && !(require_breaking_footer && breaking_marker_without_footer)),
+                            c.breaking_description()
+                                .and_then(|d| if d == c.description() { None } else { Some(d) }),
+                            c.footers().iter().any(|f| is_highlight(f.token().as_str(), f.value())),
+                            c.type_().as_str() == "deps" || c.scope().map(|s| s.as_str() == "deps").unwrap_or_default(),
+                        )
+                    })
+                    .unwrap_or_else(|_| {
+                        let (title, kind, prefix, breaking) = classify(summary(), rules);
+                        let m = git::objs::commit::MessageRef::from_bytes(m.as_bytes());
+                        (
+                            title.into(),
+                            kind,
+                            prefix,
+                            m.body().map(|b| b.without_trailer().to_str_lossy()),
+                            breaking,
+                            None,
+                            match m.body() {
+                                Some(b) => b
+                                    .trailers()
+                                    .any(|t| is_highlight(&t.token.to_str_lossy(), &t.value.to_str_lossy())),
+                                None => false,
+                            },
+                            false,
+                        )
+                    })
+            };
+        let (title, mut additions) = additions::strip(title);
+        for addition in m
+            .split_once('\n')
+            .map(|(_, body)| {
+                let mut footer_additions = additions::from_footers(body);
+                footer_additions.extend(additions::regressions_from_footers(body));
+                footer_additions
+            })
+            .unwrap_or_default()
+        {
+            if !additions.contains(&addition) {
+                additions.push(addition);
+            }
+        }
+        let canonical_kind = |kind: &str| to_static(Some(&resolve_alias(kind)));
+        let mut footer_kinds = footer_types(m)
+            .filter_map(canonical_kind)
+            .filter(|kind| *kind != "other");
+        let kind = kind.as_deref().and_then(canonical_kind).or_else(|| footer_kinds.next());
+        let mut other_kinds = Vec::new();
+        for other_kind in footer_kinds {
+            if Some(other_kind) != kind && !other_kinds.contains(&other_kind) {
+                other_kinds.push(other_kind);
+            }
+        }
         Message {
+            empty_subject: title.trim().is_empty(),
             title: title.into_owned(),
-            kind: to_static(kind),
+            kind,
+            other_kinds,
+            prefix,
+            scope,
             body: body.map(|b| b.into_owned()),
             breaking,
             breaking_description: breaking_description.map(ToOwned::to_owned),
+            breaking_marker_without_footer,
+            highlight,
+            release: is_release_commit(m),
+            dependency_update,
             additions,
         }
     }
 }
 
-fn to_static(kind: Option<git_conventional::Type<'_>>) -> Option<&'static str> {
-    kind.map(|kind| match kind.as_str() {
+impl Message {
+    /// Classify the message by the `labels` of the pull request it was squash-merged from, using the section of the first
+    /// label in `rules` which has one as type, and marking it as breaking if any of them needs a major bump.
+    ///
+    /// The classification by the commit message is kept as far as none of the `labels` overrides it.
+    pub fn classify_by_labels(&mut self, labels: &[String], rules: &BTreeMap<String, PrLabel>) {
+        let rules: Vec<_> = labels
+            .iter()
+            .filter_map(|label| rules.get(&label.to_lowercase()))
+            .collect();
+        if let Some(section) = rules.iter().find_map(|rule| rule.section.as_deref()) {
+            self.kind = to_static(Some(section));
+            let kind = self.kind;
+            self.other_kinds.retain(|other_kind| Some(*other_kind) != kind);
+        }
+        if rules.iter().any(|rule| rule.breaking) {
+            self.breaking = true;
+        }
+    }
+}
+
+/// Classify `summary` with the first of `rules` matching it, returning the title, type, prefix and whether it's breaking.
+///
+/// The match becomes the prefix if it's at the start of `summary`, and `summary` is the title as is if no rule matches.
+fn classify(summary: String, rules: &[ClassifyRule]) -> (String, Option<String>, Option<String>, bool) {
+    let (rule, found) = match rules
+        .iter()
+        .find_map(|rule| rule.pattern.find(&summary).map(|found| (rule, found)))
+    {
+        Some(rule_and_match) => rule_and_match,
+        None => return (summary, None, None, false),
+    };
+    let breaking = rule.bump == Some(Bump::Major);
+    let kind = Some(rule.section.clone());
+    if found.start() == 0 && found.end() < summary.len() {
+        let prefix = found.as_str().trim().trim_end_matches(':').trim_end().to_owned();
+        (summary[found.end()..].trim().to_owned(), kind, Some(prefix), breaking)
+    } else {
+        (summary, kind, None, breaking)
+    }
+}
+
+/// Return true if the conventional commit type `kind` isn't one we know, and would thus be listed as `other`.
+fn is_unknown_type(kind: &str) -> bool {
+    kind != "other" && to_static(Some(kind)) == Some("other")
+}
+
+/// Return all conventional commit types listed in `Type: feat, fix` footers of the body of message `m`, in order.
+fn footer_types(m: &str) -> impl Iterator<Item = &str> {
+    m.lines()
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(token, _)| token.trim().eq_ignore_ascii_case("type"))
+        .map(|(_, value)| value)
+        .flat_map(|value| value.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|kind| !kind.is_empty())
+}
+
+/// Return true if the body of message `m` has the trailer which marks commits created by a release, like `cargo-smart-release: true`.
+fn is_release_commit(m: &str) -> bool {
+    m.lines()
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .any(|(token, value)| token.trim() == super::RELEASE_TRAILER_TOKEN && value.trim() == "true")
+}
+
+/// Return true if the footer with `token` and `value` is `Highlight: true`, ignoring case.
+fn is_highlight(token: &str, value: &str) -> bool {
+    token.eq_ignore_ascii_case("highlight") && value.trim().eq_ignore_ascii_case("true")
+}
+
+fn to_static(kind: Option<&str>) -> Option<&'static str> {
+    kind.map(|kind| match kind {
         "feat" => "feat",
         "fix" => "fix",
         "revert" => "revert",
@@ -144,11 +421,7 @@ mod tests {
             Message::from("hi"),
             Message {
                 title: "hi".into(),
-                body: None,
-                kind: None,
-                breaking: false,
-                breaking_description: None,
-                additions: vec![]
+                ..Default::default()
             }
         )
     }
@@ -160,10 +433,7 @@ mod tests {
             Message {
                 title: "hi ho foo".into(),
                 body: Some("body".into()),
-                kind: None,
-                breaking: false,
-                breaking_description: None,
-                additions: vec![]
+                ..Default::default()
             }
         )
     }
@@ -175,14 +445,43 @@ mod tests {
             Message {
                 title: "hi".into(),
                 body: Some("body\nother".into()),
-                kind: None,
-                breaking: false,
-                breaking_description: None,
-                additions: vec![Addition::IssueId("14123".into())]
+                additions: vec![Addition::IssueId("14123".into())],
+                ..Default::default()
             }
         )
     }
 
+    #[test]
+    fn conventional_with_type_alias() {
+        let aliases = vec![("feature".to_string(), "feat".to_string())].into_iter().collect();
+        assert_eq!(
+            Message::parse(
+                "feature: hi",
+                ParseOptions {
+                    type_aliases: &aliases,
+                    ..Default::default()
+                }
+            ),
+            Message {
+                title: "hi".into(),
+                kind: Some("feat"),
+                prefix: Some("feature".into()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            Message::parse(
+                "bugfix: hi",
+                ParseOptions {
+                    type_aliases: &aliases,
+                    ..Default::default()
+                }
+            )
+            .kind,
+            Some("other")
+        );
+    }
+
     #[test]
     fn conventional_with_additions() {
         assert_eq!(
@@ -191,10 +490,334 @@ mod tests {
                 title: "hi".into(),
                 body: Some("the body".into()),
                 kind: Some("feat"),
+                prefix: Some("feat!".into()),
                 breaking: true,
                 breaking_description: Some("breaks".into()),
-                additions: vec![Addition::IssueId("123".into())]
+                additions: vec![Addition::IssueId("123".into())],
+                ..Default::default()
             }
         )
     }
+
+    #[test]
+    fn highlight_footer() {
+        assert!(Message::from("feat: hi\n\nthe body\n\nHighlight: true").highlight);
+        assert!(Message::from("hi\n\nthe body\n\nhighlight: TRUE\nSigned-off-by: foo").highlight);
+        assert!(!Message::from("feat: hi\n\nHighlight: false").highlight);
+        assert!(!Message::from("hi\n\nHighlight: true is in the body\nof this commit").highlight);
+    }
+
+    #[test]
+    fn release_commits() {
+        assert!(Message::from("Release a v1.0.0\n\ncargo-smart-release: true").release);
+        assert!(
+            Message::from("Release a v1.0.0, safety bump 2 crates\n\nSAFETY BUMP: b v2.0.0, c v2.0.0\n\ncargo-smart-release: true")
+                .release
+        );
+        assert!(!Message::from("Release a v1.0.0").release);
+        assert!(
+            !Message::from("cargo-smart-release: true").release,
+            "the subject is no trailer"
+        );
+    }
+
+    #[test]
+    fn dependency_updates() {
+        assert!(Message::from("build(deps): bump serde from 1.0.0 to 1.0.1").dependency_update);
+        assert!(Message::from("chore(deps): update rust crate log").dependency_update);
+        assert!(Message::from("deps: bump log").dependency_update);
+        assert!(!Message::from("fix(parser): deps are handled").dependency_update);
+        assert!(!Message::from("bump deps").dependency_update);
+    }
+
+    #[test]
+    fn type_footer_with_multiple_kinds() {
+        let msg = Message::from("feat: hi\n\nthe body\n\nType: fix, feat docs");
+        assert_eq!(msg.kind, Some("feat"));
+        assert_eq!(msg.other_kinds, vec!["fix", "docs"]);
+
+        let msg = Message::from("hi\n\ntype: fix,perf");
+        assert_eq!(
+            msg.kind,
+            Some("fix"),
+            "the first footer type is used if the subject isn't conventional"
+        );
+        assert_eq!(msg.other_kinds, vec!["perf"]);
+
+        let msg = Message::from("fix: hi\n\nType: unknown words");
+        assert_eq!(msg.kind, Some("fix"));
+        assert!(msg.other_kinds.is_empty(), "unknown types are ignored");
+    }
+
+    #[test]
+    fn non_conventional_classified_by_rules() {
+        let rule = |pattern: &str, section: &str, bump| ClassifyRule {
+            pattern: regex::Regex::new(pattern).unwrap(),
+            section: section.into(),
+            bump,
+        };
+        let rules = vec![
+            rule(r"^\[FIX\]", "fix", Some(Bump::Patch)),
+            rule(r"^\[BREAKING\]", "feat", Some(Bump::Major)),
+            rule(r"JIRA-\d+", "feat", None),
+        ];
+        assert_eq!(
+            Message::parse(
+                "[FIX] crash on start (#12)",
+                ParseOptions {
+                    rules: &rules,
+                    ..Default::default()
+                }
+            ),
+            Message {
+                title: "crash on start".into(),
+                kind: Some("fix"),
+                prefix: Some("[FIX]".into()),
+                additions: vec![Addition::IssueId("12".into())],
+                ..Default::default()
+            }
+        );
+        let m = Message::parse(
+            "[BREAKING] remove X",
+            ParseOptions {
+                rules: &rules,
+                ..Default::default()
+            },
+        );
+        assert_eq!((m.kind, m.breaking, m.title.as_str()), (Some("feat"), true, "remove X"));
+        let m = Message::parse(
+            "add Y for JIRA-123",
+            ParseOptions {
+                rules: &rules,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            (m.kind, m.prefix, m.title.as_str()),
+            (Some("feat"), None, "add Y for JIRA-123")
+        );
+        assert_eq!(
+            Message::parse(
+                "fix: [FIX] hi",
+                ParseOptions {
+                    rules: &rules,
+                    ..Default::default()
+                }
+            )
+            .prefix
+            .as_deref(),
+            Some("fix")
+        );
+        assert_eq!(
+            Message::parse(
+                "unrelated",
+                ParseOptions {
+                    rules: &rules,
+                    ..Default::default()
+                }
+            )
+            .kind,
+            None
+        );
+    }
+
+    #[test]
+    fn non_conventional_classified_by_gitmoji() {
+        let mut config = crate::config::WorkspaceChangelog::default();
+        assert!(config.gitmoji_rules().is_empty(), "gitmojis are only used if enabled");
+        config.gitmoji = true;
+        config.gitmoji_types.insert(
+            "🦀".into(),
+            crate::config::GitmojiType {
+                section: "perf".into(),
+                bump: None,
+            },
+        );
+        config.gitmoji_types.insert(
+            "🐛".into(),
+            crate::config::GitmojiType {
+                section: "feat".into(),
+                bump: Some(Bump::Major),
+            },
+        );
+        let rules = config.gitmoji_rules();
+        let parse = |m: &str| {
+            let m = Message::parse(
+                m,
+                ParseOptions {
+                    rules: &rules,
+                    ..Default::default()
+                },
+            );
+            (m.kind, m.prefix, m.title, m.breaking)
+        };
+        assert_eq!(
+            parse("✨ add X"),
+            (Some("feat"), Some("✨".into()), "add X".into(), false)
+        );
+        assert_eq!(
+            parse(":sparkles: add X"),
+            (Some("feat"), Some(":sparkles".into()), "add X".into(), false),
+            "the trailing colon is removed like the one of conventional prefixes"
+        );
+        assert_eq!(
+            parse("⚡\u{fe0f} faster"),
+            (Some("perf"), Some("⚡\u{fe0f}".into()), "faster".into(), false),
+            "the variation selector is optional"
+        );
+        assert_eq!(
+            parse("💥 remove X"),
+            (Some("feat"), Some("💥".into()), "remove X".into(), true)
+        );
+        assert_eq!(
+            parse("🦀 rewrite"),
+            (Some("perf"), Some("🦀".into()), "rewrite".into(), false)
+        );
+        assert_eq!(
+            parse("🐛 crash"),
+            (Some("feat"), Some("🐛".into()), "crash".into(), true),
+            "configured types take precedence"
+        );
+        assert_eq!(parse("✨add X").0, None, "the gitmoji must be separated from the title");
+        assert_eq!(parse("fix: ✨ hi").0, Some("fix"));
+    }
+
+    #[test]
+    fn breaking_marker_may_require_a_footer() {
+        let parse = |m: &str, require_breaking_footer| {
+            let m = Message::parse(
+                m,
+                ParseOptions {
+                    require_breaking_footer,
+                    ..Default::default()
+                },
+            );
+            (m.breaking, m.breaking_marker_without_footer)
+        };
+        assert_eq!(parse("feat!: hi", false), (true, true));
+        assert_eq!(parse("feat!: hi", true), (false, true));
+        assert_eq!(parse("feat!: hi\n\nBREAKING CHANGE: breaks", true), (true, false));
+        assert_eq!(parse("feat: hi\n\nBREAKING-CHANGE: breaks", true), (true, false));
+        assert_eq!(parse("feat: hi", true), (false, false));
+    }
+
+    #[test]
+    fn scope() {
+        assert_eq!(Message::from("feat(macros): hi").scope.as_deref(), Some("macros"));
+        assert_eq!(Message::from("fix(parser)!: hi").scope.as_deref(), Some("parser"));
+        assert_eq!(Message::from("fix(): hi").scope.as_deref(), None);
+        assert_eq!(Message::from("fix: hi").scope.as_deref(), None);
+        assert_eq!(Message::from("hi (#1)").scope.as_deref(), None);
+    }
+
+    #[test]
+    fn unknown_types_keep_their_scope() {
+        let rules = vec![ClassifyRule {
+            pattern: regex::Regex::new(r"^wip(\(\w+\))?:").unwrap(),
+            section: "chore".into(),
+            bump: None,
+        }];
+        let aliases = vec![("feature".to_string(), "feat".to_string())].into_iter().collect();
+        let parse = |m: &str, unknown_types| {
+            let m = Message::parse(
+                m,
+                ParseOptions {
+                    type_aliases: &aliases,
+                    rules: &rules,
+                    unknown_types,
+                    ..Default::default()
+                },
+            );
+            (m.kind, m.prefix, m.title, m.scope)
+        };
+        assert_eq!(
+            parse("wip(parser): hi", UnknownTypes::Other),
+            (
+                Some("other"),
+                Some("wip(parser)".into()),
+                "hi".into(),
+                Some("parser".into())
+            )
+        );
+        assert_eq!(
+            parse("wip(parser): hi", UnknownTypes::Unconventional),
+            (
+                Some("chore"),
+                Some("wip(parser)".into()),
+                "hi".into(),
+                Some("parser".into())
+            ),
+            "the type is found by the classification rules, and the scope is kept"
+        );
+        assert_eq!(
+            parse("draft(parser)!: hi", UnknownTypes::Unconventional),
+            (None, None, "draft(parser)!: hi".into(), Some("parser".into())),
+            "without a matching rule, the entire subject is the title"
+        );
+        assert_eq!(
+            parse("feature(parser): hi", UnknownTypes::Unconventional).0,
+            Some("feat"),
+            "aliases are resolved first"
+        );
+        assert_eq!(parse("other: hi", UnknownTypes::Unconventional).0, Some("other"));
+    }
+
+    #[test]
+    fn empty_subject() {
+        assert_eq!(
+            Message::from("\nthe body\nand more\n\nFixes #1"),
+            Message {
+                title: "".into(),
+                body: Some("the body\nand more\n\nFixes #1".into()),
+                empty_subject: true,
+                additions: vec![Addition::IssueId("1".into())],
+                ..Default::default()
+            }
+        );
+        assert!(Message::from("fix: (#1)\n\nthe body").empty_subject);
+        assert!(!Message::from("hi\n\n").empty_subject);
+    }
+
+    #[test]
+    fn issues_from_title_and_footers_without_duplicates() {
+        assert_eq!(
+            Message::from("fix: hi (#1)\n\nthe body\n\nFixes #1, #2").additions,
+            vec![Addition::IssueId("1".into()), Addition::IssueId("2".into())]
+        );
+    }
+
+    #[test]
+    fn classified_by_pull_request_labels() {
+        let mut rules = crate::config::WorkspaceChangelog::default();
+        rules.pr_labels.insert(
+            "Perf".into(),
+            PrLabel {
+                section: Some("perf".into()),
+                breaking: false,
+            },
+        );
+        let rules = rules.pr_label_rules();
+        let classify = |m: &str, labels: &[&str]| {
+            let mut m = Message::from(m);
+            m.classify_by_labels(&labels.iter().map(|l| l.to_string()).collect::<Vec<_>>(), &rules);
+            (m.kind, m.breaking)
+        };
+        assert_eq!(classify("add X (#1)", &["enhancement"]), (Some("feat"), false));
+        assert_eq!(
+            classify("add X (#1)", &["Breaking", "question", "bug"]),
+            (Some("fix"), true),
+            "labels are compared case-insensitively and unknown ones are ignored"
+        );
+        assert_eq!(classify("fix: X (#1)", &["PERF"]), (Some("perf"), false));
+        assert_eq!(
+            classify("fix: X (#1)", &["question"]),
+            (Some("fix"), false),
+            "the commit message is used without known labels"
+        );
+        assert_eq!(
+            classify("feat!: X (#1)", &["documentation"]),
+            (Some("docs"), true),
+            "labels don't make changes non-breaking"
+        );
+    }
 }
@@ -4,8 +4,8 @@ use std::borrow::Cow;
 
 pub mod history;
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq, Eq))]
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(Default, PartialEq, Eq))]
 pub struct Message {
     /// The cleared, plain title with any `additions` removed.
     pub title: String,
@@ -13,17 +13,44 @@ pub struct Message {
     pub body: Option<String>,
     /// If set, the git-conventional scope to help organizing changes.
     pub kind: Option<&'static str>,
+    /// Further conventional commit types the change belongs to as listed in a `Type: feat, fix` footer, without `kind`.
+    pub other_kinds: Vec<&'static str>,
+    /// The git-conventional prefix of the title as written, like `feat(parser)`, without the trailing colon.
+    pub prefix: Option<String>,
+    /// The conventional commit scope, like `macros` for `feat(macros): X`, if there is one, whether the type is known or not.
+    pub scope: Option<String>,
     /// If set, this is a breaking change as indicated git-conventional.
     pub breaking: bool,
     /// If set, this commit message body contains a specific description of the breaking change.
     pub breaking_description: Option<String>,
+    /// If set, the subject is marked as breaking with `!`, like `feat!: X`, but there is no `BREAKING CHANGE` footer.
+    ///
+    /// Such changes are only `breaking` if the footer isn't required.
+    pub breaking_marker_without_footer: bool,
+    /// If set, the commit message has a `Highlight: true` footer to feature the change in the changelog.
+    pub highlight: bool,
+    /// If set, the subject line of the commit message is empty, leaving the `title` empty as well.
+    pub empty_subject: bool,
+    /// If set, the commit updates dependencies as indicated by the `deps` type or scope, like `build(deps): bump X`.
+    pub dependency_update: bool,
+    /// If set, the commit was created by a release as indicated by its `cargo-smart-release: true` trailer, and its changes
+    /// are ignored.
+    pub release: bool,
     /// all dditional information parsed from the title.
     pub additions: Vec<message::Addition>,
 }
 
+/// The token of the trailer which marks commits created by a release, with `true` as value.
+pub const RELEASE_TRAILER_TOKEN: &str = "cargo-smart-release";
+
 pub struct History {
     pub head: git_repository::refs::Reference,
     pub items: Vec<history::Item>,
+    /// Warnings encountered while traversing the history.
+    pub warnings: Vec<history::Warning>,
+    /// Commits which are part of the history but whose changes are to be ignored, as requested with `--exclude-commit`
+    /// or because they were created by a release.
+    pub excluded: std::collections::BTreeSet<git_repository::hash::ObjectId>,
 }
 
-mod message;
+pub mod message;
@@ -1,102 +1,512 @@
-use std::{cell::RefCell, collections::BTreeMap, iter::FromIterator, path::PathBuf, time::Instant};
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    path::PathBuf,
+    time::Instant,
+};
 
 use anyhow::bail;
+use cargo_metadata::{camino::Utf8PathBuf, Package};
 use git_repository as git;
 use git_repository::{
     bstr::{BStr, ByteSlice},
     easy::head,
-    prelude::{CacheAccessExt, ObjectAccessExt, ReferenceAccessExt, ReferenceExt},
+    prelude::{CacheAccessExt, ObjectAccessExt, ObjectIdExt, ReferenceAccessExt, ReferenceExt},
 };
 
 use crate::{
-    command::changelog_impl::commit,
-    utils::{component_to_bytes, is_tag_name, is_tag_version, package_by_name, tag_prefix},
+    command::{
+        changelog::Options,
+        changelog_impl::{commit, github},
+    },
+    config::SubmoduleUpdates,
+    git::TreeEntry,
+    utils::{
+        component_to_bytes, is_tag_name, is_tag_version, package_by_name, parse_tag_version, tag_name, tag_prefix,
+    },
 };
 
-pub fn commit_history(repo: &git::Easy) -> anyhow::Result<Option<commit::History>> {
-    let start = Instant::now();
-    let prev = repo.object_cache_size(64 * 1024)?;
-    let reference = match repo.head()?.peeled()?.kind {
+/// Traverse the history of the current branch, ignoring the changes of commits whose full or abbreviated id is in
+/// `excluded_commits`.
+pub fn commit_history(
+    ctx: &crate::Context,
+    options: Options,
+    excluded_commits: &[String],
+) -> anyhow::Result<Option<commit::History>> {
+    let repo = &ctx.repo;
+    let reference = match crate::git::head(ctx)?.peeled()?.kind {
         head::Kind::Detached { .. } => bail!("Refusing to operate on a detached head."),
         head::Kind::Unborn { .. } => return Ok(None),
         head::Kind::Symbolic(r) => r.attach(repo),
     };
+    let (items, warnings) = walk(ctx, reference.id().detach(), &BTreeSet::new(), options)?;
+    Ok(Some(commit::History {
+        head: reference.detach(),
+        excluded: resolve_excluded_commits(&items, excluded_commits)?,
+        items,
+        warnings,
+    }))
+}
+
+/// Resolve the full or abbreviated commit ids in `excluded_commits` against the commits in `items`, failing if one of
+/// them doesn't match exactly one commit. Commits created by a release are always excluded.
+pub fn resolve_excluded_commits(
+    items: &[commit::history::Item],
+    excluded_commits: &[String],
+) -> anyhow::Result<BTreeSet<git::hash::ObjectId>> {
+    let mut excluded = resolve_commits(items, excluded_commits)?;
+    excluded.extend(items.iter().filter(|item| item.message.release).map(|item| item.id));
+    Ok(excluded)
+}
 
+/// Resolve the full or abbreviated commit ids in `excluded_commits` against the commits in `items`, failing if one of
+/// them doesn't match exactly one commit.
+pub fn resolve_commits(
+    items: &[commit::history::Item],
+    excluded_commits: &[String],
+) -> anyhow::Result<BTreeSet<git::hash::ObjectId>> {
+    let mut excluded = BTreeSet::new();
+    for prefix in excluded_commits {
+        if prefix.len() < 4 || prefix.len() > 40 || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!(
+                "'{}' is not a full or abbreviated commit id with at least 4 hexadecimal characters",
+                prefix
+            );
+        }
+        let prefix = prefix.to_ascii_lowercase();
+        let mut matches = items.iter().filter(|item| item.id.to_string().starts_with(&prefix));
+        match (matches.next(), matches.next()) {
+            (Some(item), None) => {
+                excluded.insert(item.id);
+            }
+            (None, _) => bail!(
+                "The commit to exclude '{}' is not part of the traversed history",
+                prefix
+            ),
+            (Some(_), Some(_)) => bail!(
+                "The commit to exclude '{}' is ambiguous - use more characters of its id",
+                prefix
+            ),
+        }
+    }
+    Ok(excluded)
+}
+
+/// Return all commits reachable from `to` but not from `from`, like `git log from..to`, along with the item for `from`
+/// itself which serves as boundary to determine the changes of the last commit in the range, and all warnings.
+pub fn commit_range(
+    ctx: &crate::Context,
+    from: git::hash::ObjectId,
+    to: git::hash::ObjectId,
+    options: Options,
+) -> anyhow::Result<(
+    Vec<commit::history::Item>,
+    commit::history::Item,
+    Vec<commit::history::Warning>,
+)> {
+    let excluded = from
+        .attach(&ctx.repo)
+        .ancestors()?
+        .all()
+        .map(|id| id.map(|id| id.detach()))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+    let (items, mut warnings) = walk(ctx, to, &excluded, options)?;
+    let paths = indexed_paths(ctx)?;
+    let boundary = match to_item(
+        ctx,
+        from.attach(&ctx.repo),
+        &mut PathLookup::new(ctx, &paths),
+        &mut warnings,
+    )? {
+        Some(item) => item,
+        None => bail!("Could not decode the commit message of '{}'", from),
+    };
+    Ok((items, boundary, warnings))
+}
+
+/// Collect all commits reachable from `tip` which are not `excluded`, in traversal order, along with all warnings.
+fn walk(
+    ctx: &crate::Context,
+    tip: git::hash::ObjectId,
+    excluded: &BTreeSet<git::hash::ObjectId>,
+    Options {
+        max_commits,
+        truncate_history,
+        ..
+    }: Options,
+) -> anyhow::Result<(Vec<commit::history::Item>, Vec<commit::history::Warning>)> {
+    let repo = &ctx.repo;
+    let start = Instant::now();
+    let prev = repo.object_cache_size(64 * 1024)?;
+
+    let paths = indexed_paths(ctx)?;
+    let mut lookup = PathLookup::new(ctx, &paths);
     let mut items = Vec::new();
-    for commit_id in reference.id().ancestors()?.all() {
+    let mut warnings = Vec::new();
+    let mut num_commits = 0;
+    for commit_id in tip.attach(repo).ancestors()?.all() {
         let commit_id = commit_id?;
-        let (message, tree_id) = {
-            let object = commit_id.object()?;
-            let commit = object.commit()?;
-            (commit.message.to_vec(), commit.tree())
-        };
-
-        let message = match message.to_str() {
-            Err(_) => {
-                log::warn!(
-                    "Commit message of {} could not be decoded to UTF-8 - ignored",
-                    commit_id.as_ref()
-                );
-                continue;
+        if excluded.contains(commit_id.as_ref()) {
+            continue;
+        }
+        if max_commits == Some(num_commits) {
+            if truncate_history {
+                warnings.push(commit::history::Warning::Truncated { num_commits });
+                break;
             }
-            Ok(m) => m,
-        };
-        items.push(commit::history::Item {
-            id: commit_id.detach(),
-            _message: commit::Message::from(message),
-            tree_data: repo.find_object(tree_id)?.data.to_owned(),
-        });
+            bail!(
+                "The commit history has more than {} commits. Use --truncate-history to ignore the remaining ones or increase --max-commits.",
+                num_commits
+            );
+        }
+        num_commits += 1;
+        items.extend(to_item(ctx, commit_id, &mut lookup, &mut warnings)?);
     }
     repo.object_cache_size(prev)?;
 
     let elapsed = start.elapsed();
     log::trace!(
-        "Cached commit history of {} commits and trees in {}s ({:.0} items/s)",
+        "Cached commit history of {} commits and trees in {}s ({:.0} items/s), reusing {} entries of nested paths",
         items.len(),
         elapsed.as_secs_f32(),
-        items.len() as f32 / elapsed.as_secs_f32()
+        items.len() as f32 / elapsed.as_secs_f32(),
+        lookup.num_reused
     );
-    Ok(Some(commit::History {
-        head: reference.detach(),
-        items,
+    Ok((items, warnings))
+}
+
+/// Return the repository-relative directories of all workspace members which aren't at the repository root along with
+/// their extra paths, sorted and without duplicates.
+///
+/// The entries at these paths are resolved once per commit when traversing the history, so the trees of commits don't
+/// have to be kept around to see which crates they change.
+pub fn indexed_paths(ctx: &crate::Context) -> anyhow::Result<Vec<Utf8PathBuf>> {
+    let mut paths = BTreeSet::new();
+    for package in ctx
+        .meta
+        .packages
+        .iter()
+        .filter(|p| ctx.meta.workspace_members.iter().any(|m| m == &p.id))
+    {
+        paths.extend(ctx.repo_relative_path(package).map(ToOwned::to_owned));
+        paths.extend(ctx.extra_repo_relative_paths(package)?);
+    }
+    Ok(paths.into_iter().collect())
+}
+
+/// Resolves the entries at the indexed paths in the trees of commits, see `indexed_paths()`.
+///
+/// Paths with more components than the configured `path-lookup-threshold` reuse their entry of the previously resolved
+/// commit if their top-level entry didn't change, as all trees below it are the same then. Otherwise, like for all other
+/// paths, the trees below their top-level entry are looked up in the repository.
+struct PathLookup<'a> {
+    paths: Vec<Vec<&'a [u8]>>,
+    threshold: usize,
+    /// The id of the top-level entry and the resolved entry of each path in the previously resolved commit, for paths
+    /// beyond the threshold.
+    previous: Vec<Option<(git::hash::ObjectId, Option<TreeEntry>)>>,
+    /// The amount of entries which were reused instead of being looked up.
+    num_reused: usize,
+}
+
+impl<'a> PathLookup<'a> {
+    fn new(ctx: &crate::Context, paths: &'a [Utf8PathBuf]) -> Self {
+        PathLookup {
+            paths: paths
+                .iter()
+                .map(|path| path.components().map(component_to_bytes).collect())
+                .collect(),
+            threshold: ctx.config.changelog.path_lookup_threshold.unwrap_or(1),
+            previous: vec![None; paths.len()],
+            num_reused: 0,
+        }
+    }
+
+    /// Return the entry at each path in the tree with `tree_id`, in the order of the paths, or `None` if there is none.
+    fn entries(&mut self, tree_id: git::hash::ObjectId, repo: &git::Easy) -> anyhow::Result<Vec<Option<TreeEntry>>> {
+        // Objects borrow a shared buffer, so the top-level entries are taken from the tree before looking up the trees
+        // below them.
+        let top_level: Vec<_> = {
+            let tree = repo.find_object(tree_id)?;
+            self.paths
+                .iter()
+                .map(|components| {
+                    components.first().and_then(|first| {
+                        top_level_entries(&tree.data)
+                            .find(|e| e.filename == *first)
+                            .map(|e| (e.oid.to_owned(), e.mode))
+                    })
+                })
+                .collect()
+        };
+        let mut entries = Vec::with_capacity(self.paths.len());
+        for ((components, previous), top_level) in self.paths.iter().zip(self.previous.iter_mut()).zip(top_level) {
+            let entry = match top_level {
+                None => None,
+                Some(entry) if components.len() == 1 => Some(entry),
+                Some((id, _)) => match *previous {
+                    Some((previous_id, reused)) if previous_id == id => {
+                        self.num_reused += 1;
+                        reused
+                    }
+                    _ => {
+                        let entry = lookup_path(id, &components[1..], repo)?;
+                        if components.len() > self.threshold {
+                            *previous = Some((id, entry));
+                        }
+                        entry
+                    }
+                },
+            };
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+/// Turn the commit with `commit_id` into a history item with the entries at the paths of `lookup` in its tree, or
+/// return `None` and add to `warnings` if its message isn't valid UTF-8.
+fn to_item(
+    ctx: &crate::Context,
+    commit_id: git::easy::Oid<'_, git::Easy>,
+    lookup: &mut PathLookup<'_>,
+    warnings: &mut Vec<commit::history::Warning>,
+) -> anyhow::Result<Option<commit::history::Item>> {
+    let (message, parents, tree_id, commit_time, author, author_email) = {
+        let object = commit_id.object()?;
+        let commit = object.commit()?;
+        (
+            commit.message.to_vec(),
+            commit.parents().collect(),
+            commit.tree(),
+            commit.committer.time,
+            commit.author.name.to_str_lossy().into_owned(),
+            commit.author.email.to_str_lossy().into_owned(),
+        )
+    };
+
+    let id = commit_id.detach();
+    let message = match message.to_str() {
+        Err(_) => {
+            warnings.push(commit::history::Warning::UndecodableMessage { id });
+            return Ok(None);
+        }
+        Ok(m) => m,
+    };
+    let require_breaking_footer = ctx.config.changelog.require_breaking_footer;
+    let pull_request = github::pull_request_number(message);
+    let message = commit::Message::parse(
+        message,
+        commit::message::ParseOptions {
+            type_aliases: &ctx.config.changelog.type_aliases,
+            rules: &ctx.config.changelog.classify,
+            unknown_types: ctx.config.changelog.unknown_types,
+            require_breaking_footer,
+        },
+    );
+    if require_breaking_footer && message.breaking_marker_without_footer {
+        warnings.push(commit::history::Warning::BreakingMarkerWithoutFooter { id });
+    }
+    let path_entries = lookup.entries(tree_id, &ctx.repo)?;
+    Ok(Some(commit::history::Item {
+        id,
+        message,
+        commit_time,
+        author,
+        author_email,
+        pull_request,
+        parents,
+        tree_id,
+        path_entries,
     }))
 }
 
-/// Return the head reference followed by all tags affecting `crate_name` as per our tag name rules, ordered by ancestry.
+/// Return all `items` which change `crate_name` and aren't `excluded`, using `boundary` as the parent of the last item,
+/// along with the ids of those which only update its submodules if these are to be grouped.
+#[allow(clippy::type_complexity)]
+pub fn changes_in_range<'h>(
+    crate_name: &str,
+    ctx: &crate::Context,
+    items: &'h [commit::history::Item],
+    boundary: &'h commit::history::Item,
+    excluded: &BTreeSet<git::hash::ObjectId>,
+) -> anyhow::Result<(Vec<&'h commit::history::Item>, BTreeSet<git::hash::ObjectId>)> {
+    let package = package_by_name(&ctx.meta, crate_name)?;
+    let filters = filters(package, ctx)?;
+    let scopes = scopes_of(crate_name, ctx);
+    let mut changes = Vec::new();
+    let mut submodule_updates = BTreeSet::new();
+    for (index, item) in items.iter().enumerate() {
+        if excluded.contains(&item.id) {
+            continue;
+        }
+        let parent = items.get(index + 1).unwrap_or(boundary);
+        match attribution(&filters, &scopes, item, Some(parent), ctx)? {
+            Attribution::None => {}
+            Attribution::Change => changes.push(item),
+            Attribution::SubmoduleUpdate => {
+                changes.push(item);
+                submodule_updates.insert(item.id);
+            }
+        }
+    }
+    Ok((changes, submodule_updates))
+}
+
+/// Return the names of all workspace members whose changes since the release of their current version were all made by
+/// commits in `excluded`, as seen in the traversed history `items` of `HEAD`.
+///
+/// Commits only change a crate if they differ from each of their parents, so merges don't count the changes brought in
+/// by their other parents, which are judged by their own commits instead. Crates whose released commit isn't part of
+/// `items`, for instance as the history was truncated, are considered changed.
+pub fn crates_changed_by_excluded_commits_only(
+    ctx: &crate::Context,
+    items: &[commit::history::Item],
+    excluded: &BTreeSet<git::hash::ObjectId>,
+) -> anyhow::Result<BTreeSet<String>> {
+    let items_by_id: BTreeMap<_, _> = items.iter().map(|item| (item.id, item)).collect();
+    let mut crate_names = BTreeSet::new();
+    for package in ctx
+        .meta
+        .packages
+        .iter()
+        .filter(|p| ctx.meta.workspace_members.iter().any(|m| m == &p.id))
+    {
+        let version_tag_name = tag_name(package, &package.version.to_string(), &ctx.repo);
+        let released = match ctx.repo.try_find_reference(&version_tag_name)? {
+            Some(mut tag) => tag.peel_to_id_in_place()?.detach(),
+            None => continue,
+        };
+        if !items_by_id.contains_key(&released) {
+            continue;
+        }
+        let mut released_ancestors = BTreeSet::new();
+        let mut queue = vec![released];
+        while let Some(id) = queue.pop() {
+            if released_ancestors.insert(id) {
+                queue.extend(
+                    items_by_id
+                        .get(&id)
+                        .into_iter()
+                        .flat_map(|item| item.parents.iter().copied()),
+                );
+            }
+        }
+        let filters = filters(package, ctx)?;
+        let changed_by_included_commit = items
+            .iter()
+            .filter(|item| !released_ancestors.contains(&item.id) && !excluded.contains(&item.id))
+            .any(|item| changes_all_parents(&filters, item, &items_by_id));
+        if !changed_by_included_commit {
+            crate_names.insert(package.name.clone());
+        }
+    }
+    Ok(crate_names)
+}
+
+/// Return true if the paths matched by `filters` differ between `item` and each of its parents, looked up in
+/// `items_by_id`, or if it has no parents and contains them. Parents which weren't traversed count as different.
+fn changes_all_parents(
+    filters: &[Filter],
+    item: &commit::history::Item,
+    items_by_id: &BTreeMap<git::hash::ObjectId, &commit::history::Item>,
+) -> bool {
+    if item.parents.is_empty() {
+        return filters.iter().any(|filter| filter.has_changed(item, None));
+    }
+    item.parents.iter().all(|id| match items_by_id.get(id) {
+        Some(parent) => filters.iter().any(|filter| match filter.entries(item, Some(parent)) {
+            Some((current, parent)) => current != parent,
+            None => item.tree_id != parent.tree_id,
+        }),
+        None => true,
+    })
+}
+
+/// Return the conventional commit scopes which are mapped to `crate_name` in the configuration.
+fn scopes_of<'a>(crate_name: &str, ctx: &'a crate::Context) -> BTreeSet<&'a str> {
+    ctx.config
+        .changelog
+        .scopes
+        .iter()
+        .filter(|(_, crate_names)| crate_names.iter().any(|name| name == crate_name))
+        .map(|(scope, _)| scope.as_str())
+        .collect()
+}
+
+/// Return true if the commit message of `item` has one of the given `scopes`.
+fn has_scope(item: &commit::history::Item, scopes: &BTreeSet<&str>) -> bool {
+    matches!(item.message.scope.as_deref(), Some(scope) if scopes.contains(scope))
+}
+
+/// Return all tags of `package` as per our tag name rules, peeled to the objects they point to.
+pub fn tags(package: &Package, ctx: &crate::Context) -> anyhow::Result<Vec<git::refs::Reference>> {
+    let refs = ctx.repo.references()?;
+    Ok(match tag_prefix(package, &ctx.repo) {
+        Some(prefix) => refs
+            .prefixed(PathBuf::from(format!("refs/tags/{}-", prefix)))?
+            .peeled()
+            .filter_map(|r| r.ok().map(|r| r.detach()))
+            .filter(|r| is_tag_name(prefix, strip_tag_path(r.name.as_bstr())))
+            .collect::<Vec<_>>(),
+        None => refs
+            .prefixed("refs/tags")?
+            .peeled()
+            .filter_map(|r| r.ok().map(|r| r.detach()))
+            .filter(|r| is_tag_version(strip_tag_path(r.name.as_bstr())))
+            .collect(),
+    })
+}
+
+/// Return the head reference followed by all tags affecting `crate_name` as per our tag name rules, ordered by ancestry,
+/// along with warnings about tags that were ignored.
 pub fn ref_segments<'h>(
     crate_name: &str,
     ctx: &crate::Context,
     history: &'h commit::History,
-) -> anyhow::Result<Vec<commit::history::Segment<'h>>> {
+) -> anyhow::Result<(Vec<commit::history::Segment<'h>>, Vec<commit::history::Warning>)> {
+    ref_segments_until(crate_name, ctx, history, None)
+}
+
+/// Like [`ref_segments()`], but stop at the first tag of a version up to `newest_documented_version` whose segment is
+/// kept empty, as the changes of it and all older releases are documented already.
+pub fn ref_segments_until<'h>(
+    crate_name: &str,
+    ctx: &crate::Context,
+    history: &'h commit::History,
+    newest_documented_version: Option<&semver::Version>,
+) -> anyhow::Result<(Vec<commit::history::Segment<'h>>, Vec<commit::history::Warning>)> {
     let meta = &ctx.meta;
     let package = package_by_name(meta, crate_name)?;
     let tag_prefix = tag_prefix(package, &ctx.repo);
     let start = Instant::now();
-    let mut tags_by_commit = {
-        let refs = ctx.repo.references()?;
-        match tag_prefix {
-            Some(prefix) => BTreeMap::from_iter(
-                refs.prefixed(PathBuf::from(format!("refs/tags/{}-", prefix)))?
-                    .peeled()
-                    .filter_map(|r| r.ok().map(|r| r.detach()))
-                    .filter(|r| is_tag_name(prefix, strip_tag_path(r.name.as_bstr())))
-                    .map(|r| {
-                        let t = r.peeled.expect("already peeled");
-                        (t, r)
-                    }),
-            ),
-            None => BTreeMap::from_iter(
-                refs.prefixed("refs/tags")?
-                    .peeled()
-                    .filter_map(|r| r.ok().map(|r| r.detach()))
-                    .filter(|r| is_tag_version(strip_tag_path(r.name.as_bstr())))
-                    .map(|r| {
-                        let t = r.peeled.expect("already peeled");
-                        (t, r)
-                    }),
-            ),
+    let tags = tags(package, ctx)?;
+    let mut warnings = Vec::new();
+    let mut tags_by_commit = BTreeMap::<_, git::refs::Reference>::new();
+    for tag in tags {
+        let commit_id = tag.peeled.expect("already peeled");
+        match tags_by_commit.entry(commit_id) {
+            Entry::Vacant(entry) => {
+                entry.insert(tag);
+            }
+            Entry::Occupied(mut entry) => {
+                // Prefer the highest version, and the greatest name if versions are equal, to be independent of ref order.
+                let order = |r: &git::refs::Reference| {
+                    let name = r.name.as_bstr();
+                    (parse_tag_version(tag_prefix, strip_tag_path(name)), name.to_owned())
+                };
+                let ignored = if order(&tag) > order(entry.get()) {
+                    entry.insert(tag)
+                } else {
+                    tag
+                };
+                warnings.push(commit::history::Warning::TagOnSameCommit {
+                    crate_name: crate_name.to_owned(),
+                    ignored: strip_tag_path(ignored.name.as_bstr()).to_str_lossy().into_owned(),
+                    used: strip_tag_path(entry.get().name.as_bstr()).to_str_lossy().into_owned(),
+                });
+            }
         }
-    };
+    }
 
     let elapsed = start.elapsed();
     log::trace!(
@@ -110,105 +520,73 @@ pub fn ref_segments<'h>(
     let start = Instant::now();
     let mut segments = Vec::new();
     let mut segment = commit::history::Segment {
-        _head: history.head.to_owned(),
+        head: history.head.to_owned(),
         history: vec![],
+        submodule_updates: Default::default(),
     };
 
-    let dir = ctx.repo_relative_path(package);
-    enum Filter<'a> {
-        None,
-        Fast(&'a [u8]),
-        Slow(Vec<&'a [u8]>),
-    }
-    let filter = dir
-        .map(|dir| {
-            let mut components = dir.components().collect::<Vec<_>>();
-            match components.len() {
-                0 => unreachable!("BUG: it's None if empty"),
-                1 => Filter::Fast(components.pop().map(component_to_bytes).expect("exactly one")),
-                _ => Filter::Slow(components.into_iter().map(component_to_bytes).collect()),
-            }
-        })
-        .unwrap_or(Filter::None);
+    let filters = filters(package, ctx)?;
+    let scopes = scopes_of(crate_name, ctx);
 
+    let mut stopped_early = false;
     let mut items = history.items.iter().peekable();
     while let Some(item) = items.next() {
         match tags_by_commit.remove(&item.id) {
-            None => match filter {
-                Filter::None => segment.history.push(item),
-                Filter::Fast(comp) => {
-                    let current = git::objs::TreeRefIter::from_bytes(&item.tree_data)
-                        .filter_map(Result::ok)
-                        .find(|e| e.filename == comp);
-                    let parent = items.peek().and_then(|parent| {
-                        git::objs::TreeRefIter::from_bytes(&parent.tree_data)
-                            .filter_map(Result::ok)
-                            .find(|e| e.filename == comp)
-                    });
-                    match (current, parent) {
-                        (Some(current), Some(parent)) => {
-                            if current.oid != parent.oid {
-                                segment.history.push(item)
-                            }
-                        }
-                        (Some(_), None) => segment.history.push(item),
-                        (None, Some(_)) | (None, None) => {}
-                    };
+            None => {
+                if history.excluded.contains(&item.id) {
+                    continue;
                 }
-                Filter::Slow(ref components) => {
-                    let prev = ctx.repo.object_cache_size(1024 * 1024)?;
-                    let current_data = RefCell::new(item.tree_data.clone());
-                    let current = git::easy::TreeRef::from_id_and_data(
-                        item.id,
-                        std::cell::Ref::map(current_data.borrow(), |v| v.as_slice()),
-                        &ctx.repo,
-                    )
-                    .lookup_path(components.iter().copied())?;
-                    let parent = match items.peek() {
-                        Some(parent) => {
-                            let parent_data = RefCell::new(parent.tree_data.clone());
-                            git::easy::TreeRef::from_id_and_data(
-                                parent.id,
-                                std::cell::Ref::map(parent_data.borrow(), |v| v.as_slice()),
-                                &ctx.repo,
-                            )
-                            .lookup_path(components.iter().copied())?
-                        }
-                        None => None,
-                    };
-                    match (current, parent) {
-                        (Some(current), Some(parent)) => {
-                            if current.oid != parent.oid {
-                                segment.history.push(item)
-                            }
-                        }
-                        (Some(_), None) => segment.history.push(item),
-                        (None, Some(_)) | (None, None) => {}
-                    };
-                    ctx.repo.object_cache_size(prev)?;
+                match attribution(&filters, &scopes, item, items.peek().copied(), ctx)? {
+                    Attribution::None => {}
+                    Attribution::Change => segment.history.push(item),
+                    Attribution::SubmoduleUpdate => {
+                        segment.history.push(item);
+                        segment.submodule_updates.insert(item.id);
+                    }
                 }
-            },
-            Some(next_ref) => segments.push(std::mem::replace(
-                &mut segment,
-                commit::history::Segment {
-                    _head: next_ref,
-                    history: vec![item],
-                },
-            )),
+            }
+            Some(next_ref) => {
+                let is_documented = newest_documented_version
+                    .zip(parse_tag_version(tag_prefix, strip_tag_path(next_ref.name.as_bstr())))
+                    .filter(|(newest, version)| version <= newest)
+                    .is_some();
+                if is_documented {
+                    segments.push(std::mem::replace(
+                        &mut segment,
+                        commit::history::Segment {
+                            head: next_ref,
+                            history: Vec::new(),
+                            submodule_updates: Default::default(),
+                        },
+                    ));
+                    stopped_early = true;
+                    break;
+                }
+                segments.push(std::mem::replace(
+                    &mut segment,
+                    commit::history::Segment {
+                        head: next_ref,
+                        history: if history.excluded.contains(&item.id) {
+                            Vec::new()
+                        } else {
+                            vec![item]
+                        },
+                        submodule_updates: Default::default(),
+                    },
+                ))
+            }
         }
     }
     segments.push(segment);
 
-    if !tags_by_commit.is_empty() {
-        log::warn!(
-            "{}: The following tags were on branches which are ignored during traversal: {}",
-            crate_name,
-            tags_by_commit
+    if !tags_by_commit.is_empty() && !stopped_early {
+        warnings.push(commit::history::Warning::TagsOnIgnoredBranches {
+            crate_name: crate_name.to_owned(),
+            tags: tags_by_commit
                 .into_values()
                 .map(|v| v.name.as_bstr().to_str_lossy().into_owned())
-                .collect::<Vec<_>>()
-                .join(", ")
-        )
+                .collect(),
+        });
     }
 
     let elapsed = start.elapsed();
@@ -223,12 +601,435 @@ pub fn ref_segments<'h>(
         num_commits as f32 / elapsed.as_secs_f32()
     );
 
-    Ok(segments)
+    Ok((segments, warnings))
+}
+
+/// Return the top-level paths changed by each of `items` compared to the item following it, or to `boundary` for the
+/// last one, keyed by commit id.
+pub fn changed_paths(
+    items: &[commit::history::Item],
+    boundary: Option<&commit::history::Item>,
+    repo: &git::Easy,
+) -> anyhow::Result<BTreeMap<git::hash::ObjectId, Vec<String>>> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let parent = items.get(index + 1).or(boundary);
+            Ok((item.id, changed_top_level_paths(item, parent, repo)?))
+        })
+        .collect()
+}
+
+/// Return the names of all top-level entries of the tree of `item` which differ from the ones of its `parent`, sorted
+/// and with directories ending in `/`.
+fn changed_top_level_paths(
+    item: &commit::history::Item,
+    parent: Option<&commit::history::Item>,
+    repo: &git::Easy,
+) -> anyhow::Result<Vec<String>> {
+    let entries = |item: &commit::history::Item| -> anyhow::Result<BTreeMap<String, (git::hash::ObjectId, bool)>> {
+        Ok(top_level_entries(&repo.find_object(item.tree_id)?.data)
+            .map(|e| {
+                let is_tree = e.mode == git::objs::tree::EntryMode::Tree;
+                let mut name = e.filename.to_str_lossy().into_owned();
+                if is_tree {
+                    name.push('/');
+                }
+                (name, (e.oid.to_owned(), is_tree))
+            })
+            .collect())
+    };
+    let current = entries(item)?;
+    let parent = parent.map(entries).transpose()?.unwrap_or_default();
+    Ok(current
+        .keys()
+        .chain(parent.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|name| current.get(*name) != parent.get(*name))
+        .cloned()
+        .collect())
+}
+
+/// Return all decodable top-level entries of the tree whose encoded form is `tree_data`.
+fn top_level_entries(tree_data: &[u8]) -> impl Iterator<Item = git::objs::tree::EntryRef<'_>> {
+    git::objs::TreeRefIter::from_bytes(tree_data).filter_map(Result::ok)
+}
+
+/// Return the filters matching changes to the directory of `package`, or the entire tree if it's at the repository root,
+/// and its extra paths.
+fn filters(package: &Package, ctx: &crate::Context) -> anyhow::Result<Vec<Filter>> {
+    let dir = match ctx.repo_relative_path(package) {
+        Some(dir) => dir.to_owned(),
+        None => return Ok(vec![Filter::None]),
+    };
+    let paths = indexed_paths(ctx)?;
+    std::iter::once(dir)
+        .chain(ctx.extra_repo_relative_paths(package)?)
+        .map(|path| match paths.binary_search(&path) {
+            Ok(index) => Ok(Filter::Path(index)),
+            Err(_) => bail!("BUG: path '{}' of crate '{}' wasn't indexed", path, package.name),
+        })
+        .collect()
+}
+
+/// How a commit relates to a crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Attribution {
+    /// The commit doesn't change the crate.
+    None,
+    /// The commit changes the crate.
+    Change,
+    /// The commit only updates the commits of submodules within the crate, which are to be grouped.
+    SubmoduleUpdate,
+}
+
+/// Return how `item` relates to the crate with `scopes` whose paths are matched by `filters`, as compared to `parent`.
+fn attribution(
+    filters: &[Filter],
+    scopes: &BTreeSet<&str>,
+    item: &commit::history::Item,
+    parent: Option<&commit::history::Item>,
+    ctx: &crate::Context,
+) -> anyhow::Result<Attribution> {
+    if has_scope(item, scopes) {
+        return Ok(Attribution::Change);
+    }
+    if !filters.iter().any(|filter| filter.has_changed(item, parent)) {
+        return Ok(Attribution::None);
+    }
+    let submodule_updates = ctx.config.submodule_updates;
+    if submodule_updates == SubmoduleUpdates::Change {
+        return Ok(Attribution::Change);
+    }
+    for filter in filters {
+        if !filter.only_submodules_changed(item, parent, &ctx.repo)? {
+            return Ok(Attribution::Change);
+        }
+    }
+    Ok(match submodule_updates {
+        SubmoduleUpdates::Ignore => Attribution::None,
+        SubmoduleUpdates::Change | SubmoduleUpdates::Group => Attribution::SubmoduleUpdate,
+    })
+}
+
+/// What to look at to see if a commit changes a crate.
+enum Filter {
+    /// The entire tree, for crates at the repository root.
+    None,
+    /// The entry at the indexed path with the given position, see `indexed_paths()`.
+    Path(usize),
+}
+
+impl Filter {
+    /// Return true if the path we filter for differs between `item` and its `parent`.
+    fn has_changed(&self, item: &commit::history::Item, parent: Option<&commit::history::Item>) -> bool {
+        match self.entries(item, parent) {
+            Some((Some((current, _)), Some((parent, _)))) => current != parent,
+            Some((Some(_), None)) | None => true,
+            Some((None, Some(_))) | Some((None, None)) => false,
+        }
+    }
+
+    /// Return true if the only differences of the path we filter for between `item` and its `parent` are the commits of
+    /// submodules.
+    fn only_submodules_changed(
+        &self,
+        item: &commit::history::Item,
+        parent: Option<&commit::history::Item>,
+        repo: &git::Easy,
+    ) -> anyhow::Result<bool> {
+        use git::objs::tree::EntryMode::Tree;
+        let (current, parent) = match self.entries(item, parent) {
+            Some(entries) => entries,
+            None => (Some((item.tree_id, Tree)), parent.map(|parent| (parent.tree_id, Tree))),
+        };
+        crate::git::only_submodules_differ(repo, current, parent)
+    }
+
+    /// Return the entries of the path we filter for in `item` and its `parent`, or `None` if the entire tree is of
+    /// interest.
+    #[allow(clippy::type_complexity)]
+    fn entries(
+        &self,
+        item: &commit::history::Item,
+        parent: Option<&commit::history::Item>,
+    ) -> Option<(Option<TreeEntry>, Option<TreeEntry>)> {
+        match *self {
+            Filter::None => None,
+            Filter::Path(index) => Some((
+                item.path_entries[index],
+                parent.and_then(|parent| parent.path_entries[index]),
+            )),
+        }
+    }
+}
+
+/// Return the entry at `components` in the tree with `id`, or `None` if there is none or `id` isn't a tree.
+fn lookup_path(id: git::hash::ObjectId, components: &[&[u8]], repo: &git::Easy) -> anyhow::Result<Option<TreeEntry>> {
+    Ok(match repo.find_object(id)?.try_into_tree() {
+        Ok(tree) => tree.lookup_path(components.iter().copied())?.map(|e| (e.oid, e.mode)),
+        Err(_) => None,
+    })
 }
 
-fn strip_tag_path(fullname: &BStr) -> &BStr {
+pub fn strip_tag_path(fullname: &BStr) -> &BStr {
     fullname
         .strip_prefix(b"refs/tags/")
         .expect("prefix iteration works")
         .as_bstr()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{
+        changes_in_range, commit_history, commit_range, crates_changed_by_excluded_commits_only, indexed_paths,
+        ref_segments_until, resolve_excluded_commits,
+    };
+    use crate::{command::changelog_impl::commit::history::Item, fixture::Workspace, git::resolve_commit};
+
+    fn titles<'a>(items: impl IntoIterator<Item = &'a Item>) -> Vec<&'a str> {
+        items.into_iter().map(|item| item.message.title.as_str()).collect()
+    }
+
+    #[test]
+    fn changes_of_crates_in_a_range_of_commits() {
+        let ws = Workspace::tri_depth("commit-range");
+        ws.write("a/src/lib.rs", "// before the range\n");
+        ws.commit("fix: change a before the range");
+        ws.write("b/src/lib.rs", "// in the range\n");
+        ws.commit("feat: change b");
+        ws.write("a/src/lib.rs", "// in the range\n");
+        ws.commit("fix: change a");
+        let ctx = ws.context(&["a"]);
+        let from = resolve_commit(&ctx, "HEAD~2").unwrap();
+        let to = resolve_commit(&ctx, "HEAD").unwrap();
+
+        let (items, boundary, warnings) = commit_range(&ctx, from, to, Default::default()).unwrap();
+        assert_eq!(
+            titles(&items),
+            ["change a", "change b"],
+            "the newest commit comes first and the start of the range isn't part of it"
+        );
+        assert_eq!(
+            boundary.id, from,
+            "the start of the range is the parent of the oldest change"
+        );
+        assert!(warnings.is_empty());
+
+        let changes = |crate_name, excluded: &BTreeSet<_>| {
+            let (changes, submodule_updates) = changes_in_range(crate_name, &ctx, &items, &boundary, excluded).unwrap();
+            assert!(submodule_updates.is_empty());
+            titles(changes)
+        };
+        assert_eq!(changes("a", &BTreeSet::new()), ["change a"]);
+        assert_eq!(changes("b", &BTreeSet::new()), ["change b"]);
+        assert_eq!(changes("c", &BTreeSet::new()), Vec::<&str>::new());
+        assert_eq!(
+            changes("a", &Some(to).into_iter().collect()),
+            Vec::<&str>::new(),
+            "excluded commits are ignored"
+        );
+    }
+
+    #[test]
+    fn excluded_commits_are_resolved_by_their_full_or_abbreviated_id() {
+        let ws = Workspace::tri_depth("resolve-excluded-commits");
+        ws.commit("Release a v0.8.1\n\ncargo-smart-release: true");
+        let release = ws.git(&["rev-parse", "HEAD"]);
+        ws.commit("fix: a");
+        let fix = ws.git(&["rev-parse", "HEAD"]);
+        let ctx = ws.context(&["a"]);
+        let items = commit_history(&ctx, Default::default(), &[])
+            .unwrap()
+            .expect("born")
+            .items;
+        let resolve = |ids: &[&str]| {
+            resolve_excluded_commits(&items, &ids.iter().map(|id| id.to_string()).collect::<Vec<_>>())
+                .map(|excluded| excluded.into_iter().map(|id| id.to_string()).collect::<BTreeSet<_>>())
+        };
+
+        assert_eq!(
+            resolve(&[]).unwrap(),
+            Some(release.clone()).into_iter().collect(),
+            "release commits are always excluded"
+        );
+        assert_eq!(
+            resolve(&[&fix[..7].to_uppercase()]).unwrap(),
+            vec![release, fix.clone()].into_iter().collect()
+        );
+        assert_eq!(resolve(&[&fix]).unwrap().len(), 2);
+        for (invalid, message) in [
+            (
+                "abc",
+                "'abc' is not a full or abbreviated commit id with at least 4 hexadecimal characters",
+            ),
+            (
+                "xyz1234",
+                "'xyz1234' is not a full or abbreviated commit id with at least 4 hexadecimal characters",
+            ),
+            (
+                "0000000000",
+                "The commit to exclude '0000000000' is not part of the traversed history",
+            ),
+        ] {
+            assert_eq!(resolve(&[invalid]).unwrap_err().to_string(), message);
+        }
+
+        let mut duplicated = commit_history(&ctx, Default::default(), &[])
+            .unwrap()
+            .expect("born")
+            .items;
+        duplicated.extend(
+            commit_history(&ctx, Default::default(), &[])
+                .unwrap()
+                .expect("born")
+                .items,
+        );
+        assert_eq!(
+            resolve_excluded_commits(&duplicated, &[fix[..7].to_owned()])
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "The commit to exclude '{}' is ambiguous - use more characters of its id",
+                &fix[..7]
+            )
+        );
+    }
+
+    #[test]
+    fn segments_end_at_the_newest_documented_release() {
+        let ws = Workspace::tri_depth("ref-segments-until");
+        for version in ["0.1.0", "0.2.0", "0.3.0"] {
+            ws.write("a/src/lib.rs", &format!("// {}\n", version));
+            ws.commit(&format!("fix: a {}", version));
+            ws.git(&["tag", &format!("a-v{}", version)]);
+        }
+        ws.write("a/src/lib.rs", "// unreleased\n");
+        ws.commit("fix: unreleased");
+        let ctx = ws.context(&["a"]);
+        let history = commit_history(&ctx, Default::default(), &[]).unwrap().expect("born");
+        let segments = |newest_documented_version: Option<&semver::Version>| {
+            let (segments, warnings) = ref_segments_until("a", &ctx, &history, newest_documented_version).unwrap();
+            assert!(warnings.is_empty(), "older tags aren't reported as ignored");
+            segments
+                .iter()
+                .map(|segment| {
+                    (
+                        segment.head.name.as_bstr().to_string(),
+                        titles(segment.history.iter().copied()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(segments(None).len(), 4, "head and all releases");
+        assert_eq!(
+            segments(Some(&semver::Version::new(0, 2, 0))),
+            [
+                (history.head.name.as_bstr().to_string(), vec!["unreleased"]),
+                ("refs/tags/a-v0.3.0".to_string(), vec!["a 0.3.0"]),
+                ("refs/tags/a-v0.2.0".to_string(), vec![]),
+            ],
+            "the history of documented releases isn't attributed"
+        );
+    }
+
+    #[test]
+    fn crates_changed_by_excluded_commits_only_compare_merges_to_all_parents() {
+        let ws = Workspace::tri_depth("changed-by-excluded-commits-only");
+        ws.git(&["tag", "a-v0.8.0"]);
+        let branch = ws.git(&["symbolic-ref", "--short", "HEAD"]);
+        ws.write("a/src/lib.rs", "// excluded\n");
+        ws.commit("fix: excluded change of a");
+        let excluded = ws.git(&["rev-parse", "HEAD"]);
+        ws.git(&["checkout", "-q", "-b", "side"]);
+        ws.write("a/src/lib.rs", "// excluded on the side\n");
+        ws.commit("fix: excluded change of a on the side");
+        let excluded_on_the_side = ws.git(&["rev-parse", "HEAD"]);
+        ws.git(&["checkout", "-q", &branch]);
+        ws.write("b/src/lib.rs", "// not a change of a\n");
+        ws.commit("fix: b");
+        ws.git(&["merge", "-q", "--no-ff", "-m", "merge side", "side"]);
+        let crates = |excluded: &[&str]| {
+            let ctx = ws.context(&["a"]);
+            let items = commit_history(&ctx, Default::default(), &[])
+                .unwrap()
+                .expect("born")
+                .items;
+            let excluded = excluded.iter().map(|id| resolve_commit(&ctx, id).unwrap()).collect();
+            crates_changed_by_excluded_commits_only(&ctx, &items, &excluded).unwrap()
+        };
+
+        assert_eq!(
+            crates(&[excluded.as_str(), excluded_on_the_side.as_str()]),
+            Some("a".to_string()).into_iter().collect(),
+            "the merge takes a from the side and doesn't change it, and unreleased crates aren't listed"
+        );
+        assert!(
+            crates(&[excluded.as_str()]).is_empty(),
+            "the change on the side counts even though it was merged"
+        );
+
+        ws.write("a/src/lib.rs", "// included\n");
+        ws.commit("fix: a");
+        assert!(crates(&[excluded.as_str(), excluded_on_the_side.as_str()]).is_empty());
+    }
+
+    /// A workspace whose crate `a` has the nested extra path `shared/deep/er`, with `num_commits` commits alternating
+    /// between changes to `b`, which leave the top-level directory of the extra path as is, and changes to the extra path.
+    fn workspace_with_nested_extra_path(name: &str, num_commits: usize) -> Workspace {
+        let ws = Workspace::tri_depth(name);
+        ws.configure(
+            "a",
+            "[package.metadata.smart-release.changelog]\nextra-paths = [\"../shared/deep/er\"]",
+        );
+        std::fs::create_dir_all(ws.root.join("shared/deep/er")).unwrap();
+        for index in 0..num_commits {
+            match index % 3 {
+                0 => ws.write("shared/deep/er/file", &format!("{}\n", index)),
+                _ => ws.write("b/src/lib.rs", &format!("// {}\n", index)),
+            }
+            ws.commit(&format!("fix: change {}", index));
+        }
+        ws
+    }
+
+    fn path_entries(ctx: &crate::Context) -> Vec<Vec<Option<crate::git::TreeEntry>>> {
+        commit_history(ctx, Default::default(), &[])
+            .unwrap()
+            .expect("born")
+            .items
+            .into_iter()
+            .map(|item| item.path_entries)
+            .collect()
+    }
+
+    #[test]
+    fn nested_paths_resolve_to_the_same_entries_whether_reused_or_looked_up() {
+        let ws = workspace_with_nested_extra_path("path-lookup-threshold", 6);
+        let mut ctx = ws.context(&["a"]);
+        let reused = path_entries(&ctx);
+        ctx.config.changelog.path_lookup_threshold = Some(usize::MAX);
+        let looked_up = path_entries(&ctx);
+
+        assert_eq!(reused, looked_up);
+        let extra_path = indexed_paths(&ctx)
+            .unwrap()
+            .iter()
+            .position(|path| path.as_str() == "shared/deep/er")
+            .expect("indexed");
+        assert_eq!(
+            reused
+                .iter()
+                .map(|entries| entries[extra_path].map(|(id, _)| id))
+                .collect::<BTreeSet<_>>()
+                .len(),
+            3,
+            "each of the two changes to the extra path is seen, along with it not existing initially"
+        );
+    }
+}
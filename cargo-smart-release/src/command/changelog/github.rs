@@ -0,0 +1,148 @@
+//! Classification of changes by the labels of the GitHub pull requests they were squash-merged from.
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use anyhow::{anyhow, bail};
+use serde::Deserialize;
+
+use crate::{changelog::forge::Links, config::PrLabel};
+
+/// Fetches the labels of pull requests from the REST API of GitHub with `curl`, authenticating with the token in the
+/// `GITHUB_TOKEN` or `GH_TOKEN` environment variable if one is set.
+pub struct PrLabels {
+    /// The URL of the repository in the API, like `https://api.github.com/repos/Byron/gitoxide`.
+    api_url: String,
+    token: Option<String>,
+    /// The section of each label and whether it's breaking, keyed by its lowercase name.
+    pub rules: BTreeMap<String, PrLabel>,
+    /// The labels of each pull request fetched so far, keyed by its number.
+    cache: BTreeMap<u64, Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct PullRequest {
+    labels: Vec<Label>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    name: String,
+}
+
+impl PrLabels {
+    /// Create a client for the repository changelogs link to, failing if it isn't hosted on GitHub.
+    pub fn from_context(ctx: &crate::Context) -> anyhow::Result<Self> {
+        let config = &ctx.config.changelog;
+        let api_url = super::link_remote_url(ctx, config.link_remote.as_deref())?
+            .and_then(|url| Links::from_remote_url(&url, config.forge))
+            .and_then(|links| links.github_api_url())
+            .ok_or_else(|| {
+                anyhow!("--notes-from-pr-labels needs a repository hosted on GitHub to fetch labels from")
+            })?;
+        Ok(PrLabels {
+            api_url,
+            token: std::env::var("GITHUB_TOKEN")
+                .or_else(|_| std::env::var("GH_TOKEN"))
+                .ok()
+                .filter(|token| !token.is_empty()),
+            rules: config.pr_label_rules(),
+            cache: BTreeMap::new(),
+        })
+    }
+
+    /// Return the names of the labels of the pull request with the given `number`, or no labels if there is no such
+    /// pull request as the number may refer to an issue. Each pull request is only fetched once.
+    ///
+    /// It's an error if GitHub can't be reached or doesn't respond as expected.
+    pub fn labels(&mut self, number: u64) -> anyhow::Result<Vec<String>> {
+        if let Some(labels) = self.cache.get(&number) {
+            return Ok(labels.clone());
+        }
+        let labels = self.fetch(number)?;
+        self.cache.insert(number, labels.clone());
+        Ok(labels)
+    }
+
+    fn fetch(&self, number: u64) -> anyhow::Result<Vec<String>> {
+        let url = format!("{}/pulls/{}", self.api_url, number);
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--location", "--max-time", "10"])
+            .args(["--header", "Accept: application/vnd.github+json"])
+            .args(["--header", "@-", "--write-out", "\n%{http_code}"])
+            .arg(&url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| anyhow!("Could not run curl: {}", err))?;
+        // The token is passed as header through stdin to keep it out of the list of processes.
+        let headers = match &self.token {
+            Some(token) => format!("Authorization: Bearer {}\n", token),
+            None => String::new(),
+        };
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(headers.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "Could not fetch '{}': {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let output = String::from_utf8_lossy(&output.stdout);
+        let (body, status) = output.rsplit_once('\n').unwrap_or(("", &output));
+        match status {
+            "200" => Ok(serde_json::from_str::<PullRequest>(body)
+                .map_err(|err| anyhow!("Could not parse the pull request at '{}': {}", url, err))?
+                .labels
+                .into_iter()
+                .map(|label| label.name)
+                .collect()),
+            "404" => Ok(Vec::new()),
+            status => bail!("GitHub responded to '{}' with status {}", url, status),
+        }
+    }
+}
+
+/// Return the number of the pull request a commit with message `m` was squash-merged from, as indicated by a subject line
+/// ending in `(#N)` like GitHub creates it.
+pub fn pull_request_number(m: &str) -> Option<u64> {
+    let subject = m.lines().next()?.trim_end().strip_suffix(')')?;
+    let (_, number) = subject.rsplit_once("(#")?;
+    number.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pull_request_number, PrLabels};
+
+    #[test]
+    fn labels_of_each_pull_request_are_fetched_once() {
+        let mut client = PrLabels {
+            api_url: "http://127.0.0.1:0/repos/a/b".into(),
+            token: None,
+            rules: Default::default(),
+            cache: Default::default(),
+        };
+        client.cache.insert(1, vec!["bug".into()]);
+        assert_eq!(client.labels(1).unwrap(), ["bug"]);
+        assert!(client.labels(2).is_err(), "unknown pull requests are fetched");
+        assert!(!client.cache.contains_key(&2), "failures aren't cached");
+    }
+
+    #[test]
+    fn pull_request_number_from_subject() {
+        assert_eq!(pull_request_number("feat: add X (#12)\n\nbody (#3)"), Some(12));
+        assert_eq!(pull_request_number("add X (#1) (#42) "), Some(42));
+        assert_eq!(pull_request_number("fix (#1) and more"), None);
+        assert_eq!(pull_request_number("add X (#other)"), None);
+        assert_eq!(pull_request_number("add X\n\nbody (#3)"), None);
+    }
+}
@@ -1,29 +1,958 @@
-use crate::command::changelog::Options;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::PathBuf,
+};
 
-mod commit;
-mod git;
+use anyhow::{bail, Context as _};
+use cargo_metadata::{
+    camino::{Utf8Component, Utf8Path, Utf8PathBuf},
+    Package,
+};
+use git_repository::{bstr::ByteSlice, hash::ObjectId};
 
-pub fn changelog(options: Options, crates: Vec<String>) -> anyhow::Result<()> {
-    let ctx = crate::Context::new(crates)?;
+use crate::{
+    changelog::{self, enrich::ChangelogEnricher, forge::Links, write, ChangeLog},
+    command::changelog::Options,
+    config::LineEndings,
+    traverse::{SkipReason, Skipped},
+    utils::{package_by_name, parse_tag_version, tag_prefix, will},
+};
+
+pub(in crate::command) mod commit;
+pub(in crate::command) mod git;
+mod github;
+mod reverts;
+
+/// Generate changelogs for `crates`, rendering them with the template in the file at `template` if set.
+///
+/// If `range` is set, like `v1.0.0..main`, only the changes in this range of commits are printed instead.
+/// With `options.preview_all`, the unreleased changes of all publishable workspace crates are printed instead.
+/// The changes of commits whose full or abbreviated id is in `excluded_commits` are ignored.
+/// Each changelog is passed through all `enrichers` in order before it is rendered.
+pub fn changelog(
+    options: Options,
+    crates: Vec<String>,
+    template: Option<PathBuf>,
+    range: Option<String>,
+    excluded_commits: Vec<String>,
+    locale: Option<String>,
+    enrichers: &[Box<dyn ChangelogEnricher>],
+) -> anyhow::Result<()> {
+    if options.check && !options.dry_run {
+        bail!("--check never writes changelogs and can't be used along with --write")
+    }
+    if range.is_some() && options.check {
+        bail!("--range renders the changes in the given range and can't be used along with --check")
+    }
+    if options.keep_released_sections && (template.is_some() || options.table_of_contents) {
+        bail!("--keep-released-sections only renders new sections and can't be used along with --changelog-template or --table-of-contents")
+    }
+    if options.preview_all {
+        if !crates.is_empty() {
+            bail!("--preview-all shows all publishable workspace crates, which is why crates can't be specified along with it")
+        }
+        if options.check || !options.dry_run || range.is_some() {
+            bail!("--preview-all prints unreleased changes and can't be used along with --write, --check or --range")
+        }
+    }
+    let template = template
+        .map(|path| {
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("Could not read changelog template at '{}'", path.display()))
+        })
+        .transpose()?;
+    let ctx = if options.preview_all {
+        crate::Context::for_workspace(&[])?
+    } else {
+        crate::Context::new(crates)?
+    };
+    generate(
+        &ctx,
+        options,
+        template.as_deref(),
+        range,
+        &excluded_commits,
+        locale.as_deref(),
+        enrichers,
+    )
+}
+
+/// Generate changelogs for the crates of `ctx` with `template` if set, as described in [`changelog()`].
+fn generate(
+    ctx: &crate::Context,
+    options: Options,
+    template: Option<&str>,
+    range: Option<String>,
+    excluded_commits: &[String],
+    locale: Option<&str>,
+    enrichers: &[Box<dyn ChangelogEnricher>],
+) -> anyhow::Result<()> {
+    let mut texts = translations(&ctx.config.changelog, locale)?;
+    let mut skipped = ctx.skipped.clone();
     let crate_names = if options.dependencies {
-        crate::traverse::dependencies(&ctx, false, true)?
+        let (crate_names, skipped_dependencies) = crate::traverse::dependencies(ctx, false, true)?;
+        skipped.extend(skipped_dependencies);
+        crate_names
     } else {
         ctx.crate_names.clone()
     };
-    assure_working_tree_is_unchanged(options)?;
-    let history = match git::commit_history(&ctx.repo)? {
+    let write_options = write::Options {
+        table_of_contents: options.table_of_contents,
+        keep_type_prefix: ctx.config.changelog.keep_type_prefix,
+        highlights: ctx.config.changelog.highlights,
+        max_entries: ctx.config.changelog.max_entries.clone(),
+        icons: ctx.config.changelog.icons.clone(),
+        authors: ctx.config.changelog.authors,
+        author_handles: ctx.config.changelog.author_handles.clone(),
+        empty_subjects: ctx.config.changelog.empty_subjects,
+        changed_paths: ctx.config.changelog.changed_paths,
+        dependency_updates: ctx.config.changelog.dependency_updates,
+        coalesce_dependency_bumps: ctx.config.changelog.coalesce_dependency_bumps,
+        collapse_dependency_updates: ctx.config.changelog.collapse_dependency_updates,
+        anchors: ctx.config.changelog.anchors,
+        strip_trailers: options.strip_trailers,
+        keep_trailers: ctx.config.changelog.keep_trailers.clone(),
+        no_changes_text: texts
+            .remove("no-changes")
+            .or_else(|| ctx.config.changelog.no_changes_text.clone()),
+        texts,
+        summary: ctx.config.changelog.summary.clone(),
+        links: link_remote_url(ctx, ctx.config.changelog.link_remote.as_deref())?
+            .and_then(|url| Links::from_remote_url(&url, ctx.config.changelog.forge)),
+    };
+    let mut generation = Generation {
+        ctx,
+        options,
+        enrichers,
+        pr_labels: if options.notes_from_pr_labels {
+            Some(github::PrLabels::from_context(ctx)?)
+        } else {
+            None
+        },
+    };
+    if let Some(range) = range {
+        assure_working_tree_is_unchanged(options)?;
+        return print_or_write_range(
+            &mut generation,
+            &range,
+            &crate_names,
+            template,
+            excluded_commits,
+            &write_options,
+        );
+    }
+    if !options.preview_all {
+        assure_working_tree_is_unchanged(options)?;
+    }
+    let history = match git::commit_history(ctx, options, excluded_commits)? {
         None => return Ok(()),
         Some(history) => history,
     };
+    let changed_paths = if write_options.changed_paths > 0 {
+        git::changed_paths(&history.items, None, &ctx.repo)?
+    } else {
+        Default::default()
+    };
+    if options.preview_all {
+        let unchanged = print_preview(
+            &mut generation,
+            &crate_names,
+            &history,
+            &changed_paths,
+            template,
+            &write_options,
+        )?;
+        skipped.extend(unchanged);
+        crate::traverse::log_skipped(&skipped, &[]);
+        return Ok(());
+    }
+    let mut warnings = history.warnings.clone();
+    let mut outdated = Vec::new();
     for crate_name in &crate_names {
-        let _segments = git::ref_segments(crate_name, &ctx, &history)?;
+        let package = package_by_name(&ctx.meta, crate_name)?;
+        let path = changelog_path(package)?;
+        let existing = match std::fs::read_to_string(&path) {
+            Ok(content) => Some(content),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err.into()),
+        };
+        let crlf = uses_crlf(ctx.config.changelog.line_endings, existing.as_deref());
+        // Line endings are normalized so they don't matter until the changelog is written.
+        let existing = existing.map(|existing| write::with_line_endings(&existing, false));
+        let released = existing
+            .as_deref()
+            .filter(|_| options.keep_released_sections)
+            .and_then(|existing| write::generated_range(existing).map(|range| &existing[range]))
+            .and_then(write::released_sections);
+        let write_options = match crate::config::package(package)?.changelog.no_changes_text {
+            Some(no_changes_text) => write::Options {
+                no_changes_text: Some(no_changes_text),
+                ..write_options.clone()
+            },
+            None => write_options.clone(),
+        };
+        let log = crate_changelog(
+            &mut generation,
+            package,
+            &history,
+            &changed_paths,
+            released.as_ref(),
+            &write_options,
+            &mut warnings,
+        )?;
+
+        if let Some(directory) = changelog_directory(package)? {
+            if options.keep_released_sections {
+                bail!(
+                    "--keep-released-sections can't be used for '{}' as it has a changelog directory with one file per release",
+                    crate_name
+                );
+            }
+            let files = per_version_files(&log, template, crate_name, &write_options)?;
+            let directory_for_display = directory.strip_prefix(&ctx.root).unwrap_or(&directory);
+            if options.check {
+                if !directory_is_up_to_date(&directory, &files) {
+                    log::warn!(
+                        "Changelog of '{}' in '{}' is out of date",
+                        crate_name,
+                        directory_for_display
+                    );
+                    outdated.push(crate_name.as_str());
+                }
+                continue;
+            }
+            log::info!(
+                "{} write {} file(s) to changelog directory '{}'",
+                will(options.dry_run),
+                files.len(),
+                directory_for_display
+            );
+            if !options.dry_run {
+                write_directory(&directory, &files, ctx.config.changelog.line_endings)?;
+            }
+            continue;
+        }
+
+        let generated = render(&log, template, crate_name, &write_options, released.as_ref())?;
+        if options.check {
+            let up_to_date = existing
+                .as_deref()
+                .and_then(|existing| write::generated_range(existing).map(|range| &existing[range]))
+                == Some(generated.as_str());
+            if !up_to_date {
+                log::warn!(
+                    "Changelog of '{}' at '{}' is out of date",
+                    crate_name,
+                    path.strip_prefix(&ctx.root).unwrap_or(&path)
+                );
+                outdated.push(crate_name.as_str());
+            }
+            continue;
+        }
+        log::info!(
+            "{} write {} section(s) to changelog at '{}'",
+            will(options.dry_run),
+            log.sections.len(),
+            path.strip_prefix(&ctx.root).unwrap_or(&path)
+        );
+        if !options.dry_run {
+            std::fs::write(
+                &path,
+                write::with_line_endings(&write::merge(existing.as_deref(), &generated), crlf),
+            )?;
+        }
+    }
+
+    commit::history::log_warnings(&warnings);
+    crate::traverse::log_skipped(&skipped, &crate_names);
+    if !outdated.is_empty() {
+        bail!(
+            "The changelogs of {} are out of date - run 'cargo changelog --write' to update them",
+            outdated.join(", ")
+        );
     }
+    Ok(())
+}
 
+/// What all changelogs generated by one invocation have in common.
+struct Generation<'a> {
+    ctx: &'a crate::Context,
+    options: Options,
+    /// Each changelog is passed through all of them in order before it is rendered.
+    enrichers: &'a [Box<dyn ChangelogEnricher>],
+    /// If set, classifies the changes in generated sections by the labels of their pull requests.
+    pr_labels: Option<github::PrLabels>,
+}
+
+/// Generate the changelog of `package` from the releases in `history`, leaving out those documented in `released`
+/// already, and pass it through all enrichers of `generation`. Warnings about its tags are added to `warnings`.
+fn crate_changelog(
+    generation: &mut Generation<'_>,
+    package: &Package,
+    history: &commit::History,
+    changed_paths: &BTreeMap<ObjectId, Vec<String>>,
+    released: Option<&write::ReleasedSections>,
+    write_options: &write::Options,
+    warnings: &mut Vec<commit::history::Warning>,
+) -> anyhow::Result<ChangeLog> {
+    let ctx = generation.ctx;
+    let newest_documented_version = released.map(|released| &released.newest_version);
+    let (mut segments, segment_warnings) =
+        git::ref_segments_until(&package.name, ctx, history, newest_documented_version)?;
+    warnings.extend(segment_warnings);
+    if ctx.config.changelog.net_reverts {
+        reverts::net_out_segments(ctx, &mut segments)?;
+    }
+    let messages = classify_by_pr_labels(
+        segments.iter().flat_map(|segment| segment.history.iter().copied()),
+        &mut generation.pr_labels,
+        warnings,
+    );
+    let mut log = from_segments(
+        tag_prefix(package, &ctx.repo),
+        &segments,
+        &messages,
+        changed_paths,
+        generation.options.merge_prereleases,
+        newest_documented_version,
+        write_options.no_changes_text.is_some(),
+    )?;
+    changelog::enrich::apply(generation.enrichers, &package.name, &mut log)?;
+    Ok(log)
+}
+
+/// Render `log` of `crate_name` with `template` if set, or as markdown followed by the `released` sections as they are.
+fn render(
+    log: &ChangeLog,
+    template: Option<&str>,
+    crate_name: &str,
+    write_options: &write::Options,
+    released: Option<&write::ReleasedSections>,
+) -> anyhow::Result<String> {
+    Ok(match template {
+        Some(template) => changelog::template::render(
+            template,
+            &changelog::template::Context::new(crate_name, log, write_options),
+        )
+        .with_context(|| format!("Could not render changelog template for '{}'", crate_name))
+        .map(|generated| write::with_line_endings(&generated, false))?,
+        None => {
+            let mut generated = String::new();
+            log.write_to(&mut generated, write_options)?;
+            if let Some(released) = released {
+                let end = generated.len() - write::END_MARKER.len();
+                generated.insert_str(end, &released.content);
+            }
+            generated
+        }
+    })
+}
+
+/// Return true if a changelog with `existing` content should be written with CRLF line endings as per `line_endings`.
+fn uses_crlf(line_endings: LineEndings, existing: Option<&str>) -> bool {
+    match line_endings {
+        LineEndings::Auto => existing.filter(|existing| write::uses_crlf(existing)).is_some(),
+        LineEndings::Lf => false,
+        LineEndings::Crlf => true,
+    }
+}
+
+/// Return the file with unreleased changes in `directory` if it exists even though it's not one of `files`.
+fn stale_unreleased_file(directory: &Utf8Path, files: &[(String, String)]) -> Option<Utf8PathBuf> {
+    Some(directory.join(write::UNRELEASED_FILE_NAME))
+        .filter(|path| path.is_file() && !files.iter().any(|(name, _)| name == write::UNRELEASED_FILE_NAME))
+}
+
+/// Return true if `directory` contains all `files` by name with the same content, ignoring line endings, and no stale file
+/// with unreleased changes.
+fn directory_is_up_to_date(directory: &Utf8Path, files: &[(String, String)]) -> bool {
+    stale_unreleased_file(directory, files).is_none()
+        && files.iter().all(|(name, content)| {
+            std::fs::read_to_string(directory.join(name))
+                .map(|existing| write::with_line_endings(&existing, false) == *content)
+                .unwrap_or(false)
+        })
+}
+
+/// Write all `files` by name into `directory`, keeping the line endings of existing files as per `line_endings`, and
+/// remove a stale file with unreleased changes.
+fn write_directory(directory: &Utf8Path, files: &[(String, String)], line_endings: LineEndings) -> anyhow::Result<()> {
+    let stale_unreleased = stale_unreleased_file(directory, files);
+    std::fs::create_dir_all(directory)?;
+    for (name, content) in files {
+        let path = directory.join(name);
+        let existing = std::fs::read_to_string(&path).ok();
+        std::fs::write(
+            &path,
+            write::with_line_endings(content, uses_crlf(line_endings, existing.as_deref())),
+        )?;
+    }
+    if let Some(stale_unreleased) = stale_unreleased {
+        std::fs::remove_file(stale_unreleased)?;
+    }
+    Ok(())
+}
+
+/// Print all commits in `range`, like `v1.0.0..main`, whose message doesn't follow the conventional commit format nor
+/// matches any of the configured classification rules, and fail if there are any.
+///
+/// Commits whose title matches one of the `allowed` patterns and commits created by releases are ignored.
+pub fn lint(options: Options, range: &str, allowed: &[regex::Regex]) -> anyhow::Result<()> {
+    if options.check || !options.dry_run || options.preview_all {
+        bail!("--lint only checks commit messages and can't be used along with --write, --check or --preview-all")
+    }
+    let ctx = crate::Context::for_workspace(&[])?;
+    let (_, _, from_id, to_id) = resolve_range(&ctx, range)?;
+    let (items, _, warnings) = git::commit_range(&ctx, from_id, to_id, options)?;
+    let offending: Vec<_> = items
+        .iter()
+        .filter(|item| {
+            let message = &item.message;
+            message.kind.is_none()
+                && !message.release
+                && !allowed.iter().any(|pattern| pattern.is_match(&message.title))
+        })
+        .collect();
+    for item in &offending {
+        let id = item.id.to_string();
+        println!("{} {}", &id[..7], item.message.title);
+    }
+    commit::history::log_warnings(&warnings);
+    if !offending.is_empty() {
+        bail!(
+            "{} of {} commits in '{}' don't follow the conventional commit format",
+            offending.len(),
+            items.len(),
+            range
+        );
+    }
+    log::info!(
+        "All {} commits in '{}' follow the conventional commit format",
+        items.len(),
+        range
+    );
+    Ok(())
+}
+
+/// Return the translated texts of `locale` as configured in `config`, or no texts if no locale is given.
+fn translations(
+    config: &crate::config::WorkspaceChangelog,
+    locale: Option<&str>,
+) -> anyhow::Result<BTreeMap<String, String>> {
+    let locale = match locale {
+        Some(locale) => locale,
+        None => return Ok(Default::default()),
+    };
+    let texts = match config.translations.get(locale) {
+        Some(texts) => texts.clone(),
+        None if config.translations.is_empty() => bail!(
+            "There are no translations for locale '{}' as the changelog configuration has no translations",
+            locale
+        ),
+        None => bail!(
+            "There are no translations for locale '{}' in the changelog configuration, only for {}",
+            locale,
+            config
+                .translations
+                .keys()
+                .map(|locale| format!("'{}'", locale))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    if let Some(key) = texts.keys().find(|key| {
+        key.as_str() != "no-changes"
+            && !write::TEXT_KEYS.contains(&key.as_str())
+            && !write::GROUPS.iter().any(|(kind, _)| kind == key)
+    }) {
+        bail!(
+            "The translations for locale '{}' contain the unknown text '{}'",
+            locale,
+            key
+        )
+    }
+    Ok(texts)
+}
+
+/// Split `range`, like `v1.0.0..main`, into its start and end along with the commits they point to, and assure the
+/// start is an ancestor of the end.
+fn resolve_range<'a>(ctx: &crate::Context, range: &'a str) -> anyhow::Result<(&'a str, &'a str, ObjectId, ObjectId)> {
+    let (from, to) = match range.split_once("..") {
+        Some((from, to)) if !from.is_empty() && !to.is_empty() && !to.starts_with('.') => (from, to),
+        _ => bail!("The range '{}' must be given as '<from>..<to>'", range),
+    };
+    let (from_id, to_id) = (
+        crate::git::resolve_commit(ctx, from)?,
+        crate::git::resolve_commit(ctx, to)?,
+    );
+    if !crate::git::is_ancestor(ctx, &from_id, &to_id)? {
+        bail!(
+            "'{}' is not an ancestor of '{}' and can't be the start of the range",
+            from,
+            to
+        );
+    }
+    Ok((from, to, from_id, to_id))
+}
+
+/// Print a section with the changes of each of `crate_names` in `range`, like `v1.0.0..main`, to stdout, or insert it
+/// into the changelog of each crate unless `options.dry_run` is set.
+fn print_or_write_range(
+    generation: &mut Generation<'_>,
+    range: &str,
+    crate_names: &[String],
+    template: Option<&str>,
+    excluded_commits: &[String],
+    write_options: &write::Options,
+) -> anyhow::Result<()> {
+    let (ctx, options) = (generation.ctx, generation.options);
+    let (from, to, from_id, to_id) = resolve_range(ctx, range)?;
+    let (items, boundary, mut warnings) = git::commit_range(ctx, from_id, to_id, options)?;
+    let excluded = git::resolve_excluded_commits(&items, excluded_commits)?;
+    let date = crate::utils::today(options.date_today)?;
+    let changed_paths = if write_options.changed_paths > 0 {
+        git::changed_paths(&items, Some(&boundary), &ctx.repo)?
+    } else {
+        Default::default()
+    };
+
+    let mut out = String::new();
+    for crate_name in crate_names {
+        let (mut changes, submodule_updates) = git::changes_in_range(crate_name, ctx, &items, &boundary, &excluded)?;
+        if ctx.config.changelog.net_reverts {
+            reverts::net_out(ctx, &mut changes)?;
+        }
+        let messages = classify_by_pr_labels(changes.iter().copied(), &mut generation.pr_labels, &mut warnings);
+        let mut log = ChangeLog {
+            sections: vec![changelog::Section {
+                version: changelog::Version::Range {
+                    from: from.to_owned(),
+                    to: to.to_owned(),
+                },
+                date: Some(date),
+                entries: changes
+                    .into_iter()
+                    .map(|item| to_entry(item, &messages, &changed_paths, &submodule_updates))
+                    .collect(),
+            }],
+        };
+        changelog::enrich::apply(generation.enrichers, crate_name, &mut log)?;
+        if options.dry_run {
+            write_unmarked(
+                &mut out,
+                crate_name,
+                &log,
+                template,
+                write_options,
+                crate_names.len() > 1,
+            )?;
+        } else {
+            let mut section = String::new();
+            write_unmarked(&mut section, crate_name, &log, template, write_options, false)?;
+            insert_range_section(
+                package_by_name(&ctx.meta, crate_name)?,
+                &log.sections[0],
+                &section,
+                ctx,
+                write_options,
+            )?;
+        }
+    }
+    print!("{}", out);
+    commit::history::log_warnings(&warnings);
     Ok(())
 }
 
+/// Insert `rendered`, the rendered `section`, into the changelog of `package`, right after its generated content so it's
+/// kept when the changelog is regenerated, unless a section for the same range exists already, no matter its date.
+fn insert_range_section(
+    package: &Package,
+    section: &changelog::Section,
+    rendered: &str,
+    ctx: &crate::Context,
+    write_options: &write::Options,
+) -> anyhow::Result<()> {
+    if changelog_directory(package)?.is_some() {
+        bail!(
+            "--range can't write to the changelog of '{}' as it has a changelog directory with one file per release",
+            package.name
+        );
+    }
+    let path = changelog_path(package)?;
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(content) => Some(content),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+    let crlf = uses_crlf(ctx.config.changelog.line_endings, existing.as_deref());
+    let existing = existing.map(|existing| write::with_line_endings(&existing, false));
+    let path_for_display = path.strip_prefix(&ctx.root).unwrap_or(&path);
+    let undated_heading = changelog::Section {
+        version: section.version.clone(),
+        date: None,
+        entries: Vec::new(),
+    }
+    .heading(write_options);
+    let heading_line = format!("### {}", undated_heading);
+    if existing
+        .as_deref()
+        .filter(|existing| {
+            existing.lines().map(str::trim_end).any(|line| {
+                line == heading_line
+                    || line
+                        .strip_prefix(&heading_line)
+                        .filter(|date| date.starts_with(" ("))
+                        .is_some()
+            })
+        })
+        .is_some()
+    {
+        bail!(
+            "The changelog at '{}' contains a section for {} already",
+            path_for_display,
+            undated_heading
+        );
+    }
+    log::info!(
+        "Will write section {} to changelog at '{}'",
+        section.heading(write_options),
+        path_for_display
+    );
+    std::fs::write(
+        &path,
+        write::with_line_endings(&write::insert_section(existing.as_deref(), rendered), crlf),
+    )?;
+    Ok(())
+}
+
+/// Print the unreleased changes of each of `crate_names` as found in `history` to stdout, each under a heading with the
+/// crate name. Crates without unreleased changes are left out and returned.
+fn print_preview(
+    generation: &mut Generation<'_>,
+    crate_names: &[String],
+    history: &commit::History,
+    changed_paths: &BTreeMap<ObjectId, Vec<String>>,
+    template: Option<&str>,
+    write_options: &write::Options,
+) -> anyhow::Result<Vec<Skipped>> {
+    let ctx = generation.ctx;
+    let mut warnings = history.warnings.clone();
+    let mut unchanged = Vec::new();
+    let mut out = String::new();
+    for crate_name in crate_names {
+        let package = package_by_name(&ctx.meta, crate_name)?;
+        let (mut segments, segment_warnings) = git::ref_segments(crate_name, ctx, history)?;
+        warnings.extend(segment_warnings);
+        if ctx.config.changelog.net_reverts {
+            reverts::net_out_segments(ctx, segments.get_mut(..1).unwrap_or_default())?;
+        }
+        let messages = classify_by_pr_labels(
+            segments
+                .iter()
+                .take(1)
+                .flat_map(|segment| segment.history.iter().copied()),
+            &mut generation.pr_labels,
+            &mut warnings,
+        );
+        let mut log = from_segments(
+            tag_prefix(package, &ctx.repo),
+            &segments[..segments.len().min(1)],
+            &messages,
+            changed_paths,
+            false,
+            None,
+            false,
+        )?;
+        changelog::enrich::apply(generation.enrichers, crate_name, &mut log)?;
+        if log.sections.is_empty() {
+            unchanged.push(Skipped::new(crate_name, SkipReason::Unchanged));
+            continue;
+        }
+        write_unmarked(&mut out, crate_name, &log, template, write_options, true)?;
+    }
+    print!("{}", out);
+    commit::history::log_warnings(&warnings);
+    Ok(unchanged)
+}
+
+/// Append the sections of `log` to `out` without our markers, rendered with `template` if set, and preceded by a heading
+/// with `crate_name` if `with_crate_heading` is true.
+fn write_unmarked(
+    out: &mut String,
+    crate_name: &str,
+    log: &ChangeLog,
+    template: Option<&str>,
+    write_options: &write::Options,
+    with_crate_heading: bool,
+) -> anyhow::Result<()> {
+    if with_crate_heading {
+        writeln!(out, "## {}\n", crate_name)?;
+    }
+    match template {
+        Some(template) => {
+            let rendered = changelog::template::render_unmarked(
+                template,
+                &changelog::template::Context::new(crate_name, log, write_options),
+            )
+            .with_context(|| format!("Could not render changelog template for '{}'", crate_name))?;
+            writeln!(out, "{}\n", rendered)?;
+        }
+        None => {
+            for section in &log.sections {
+                section.write_to(out, write_options)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Turn the `segments` of a crate as produced by [`git::ref_segments()`] into a changelog, dropping segments without changes,
+/// with its tags named after `tag_prefix` as returned by [`tag_prefix()`].
+///
+/// With `merge_prereleases`, the changes of pre-releases are merged into the release of their version.
+/// Segments of releases up to `newest_documented_version` are skipped as their sections are taken from the changelog as is.
+/// Releases without changes are kept if `keep_empty_releases` is set, to be rendered with a placeholder.
+fn from_segments(
+    tag_prefix: Option<&str>,
+    segments: &[commit::history::Segment<'_>],
+    messages: &BTreeMap<ObjectId, commit::Message>,
+    changed_paths: &BTreeMap<ObjectId, Vec<String>>,
+    merge_prereleases: bool,
+    newest_documented_version: Option<&semver::Version>,
+    keep_empty_releases: bool,
+) -> anyhow::Result<ChangeLog> {
+    let mut sections = Vec::new();
+    for (segment_index, segment) in segments.iter().enumerate() {
+        let (version, date) = if segment_index == 0 {
+            (changelog::Version::Unreleased, None)
+        } else {
+            let tag_name = segment
+                .head
+                .name
+                .as_bstr()
+                .strip_prefix(b"refs/tags/")
+                .expect("segments other than the first one are headed by tags");
+            let version = match parse_tag_version(tag_prefix, tag_name.into()) {
+                Some(version) => version,
+                None => bail!("Could not parse version of tag '{}'", tag_name.to_str_lossy()),
+            };
+            let date = segment
+                .history
+                .first()
+                .map(|item| to_date(item.commit_time))
+                .transpose()?;
+            if matches!(newest_documented_version, Some(newest) if version <= *newest) {
+                continue;
+            }
+            (changelog::Version::Semantic(version), date)
+        };
+        sections.push(changelog::Section {
+            version,
+            date,
+            entries: segment
+                .history
+                .iter()
+                .map(|item| to_entry(item, messages, changed_paths, &segment.submodule_updates))
+                .collect(),
+        });
+    }
+    let mut log = ChangeLog { sections };
+    if merge_prereleases {
+        log.merge_prereleases();
+    }
+    log.sections.retain(|section| {
+        !section.entries.is_empty() || (keep_empty_releases && section.version != changelog::Version::Unreleased)
+    });
+    Ok(log)
+}
+
+/// Return the URL of the remote named `link_remote` to link to, or of `origin` if unset.
+///
+/// It's an error if `link_remote` is set but there is no such remote, while a missing `origin` just means there are no links.
+fn link_remote_url(ctx: &crate::Context, link_remote: Option<&str>) -> anyhow::Result<Option<git_url::Url>> {
+    let remote = match link_remote {
+        Some(remote) => remote,
+        None => return crate::git::remote_url(ctx, "origin"),
+    };
+    let remotes = crate::git::remotes(ctx)?;
+    if !remotes.iter().any(|name| name == remote) {
+        bail!(
+            "The remote '{}' configured in 'link-remote' doesn't exist, the remotes are: {}",
+            remote,
+            if remotes.is_empty() {
+                "none".into()
+            } else {
+                remotes.join(", ")
+            }
+        );
+    }
+    crate::git::remote_url(ctx, remote)
+}
+
+/// Return the directory to write one file per release of `package` into, if one is configured in its manifest.
+pub(in crate::command) fn changelog_directory(package: &Package) -> anyhow::Result<Option<Utf8PathBuf>> {
+    let directory = match crate::config::package(package)?.changelog.directory {
+        Some(directory) => directory,
+        None => return Ok(None),
+    };
+    let path = Utf8Path::new(&directory);
+    if directory.is_empty() || path.is_absolute() || path.components().any(|c| c == Utf8Component::ParentDir) {
+        bail!(
+            "The changelog directory '{}' of '{}' must be a relative path within the crate's directory",
+            directory,
+            package.name
+        );
+    }
+    Ok(Some(
+        package
+            .manifest_path
+            .parent()
+            .expect("parent of a file is always present")
+            .join(path),
+    ))
+}
+
+/// Render each section of `log` on its own, keyed by the name of its file in a directory with one file per release,
+/// using `template` if set.
+fn per_version_files(
+    log: &ChangeLog,
+    template: Option<&str>,
+    crate_name: &str,
+    write_options: &write::Options,
+) -> anyhow::Result<Vec<(String, String)>> {
+    log.sections
+        .iter()
+        .filter_map(|section| section.file_name().map(|name| (name, section)))
+        .map(|(name, section)| {
+            let mut content = match template {
+                Some(template) => {
+                    let log = ChangeLog {
+                        sections: vec![section.clone()],
+                    };
+                    let content = changelog::template::render_unmarked(
+                        template,
+                        &changelog::template::Context::new(crate_name, &log, write_options),
+                    )
+                    .with_context(|| format!("Could not render changelog template for '{}'", crate_name))?;
+                    write::with_line_endings(&content, false)
+                }
+                None => {
+                    let mut content = String::new();
+                    section.write_to(&mut content, write_options)?;
+                    content
+                }
+            };
+            content.truncate(content.trim_end().len());
+            content.push('\n');
+            Ok((name, content))
+        })
+        .collect()
+}
+
+/// Return the path to the changelog of `package`, using the file name configured in its manifest or `CHANGELOG.md`.
+pub(in crate::command) fn changelog_path(package: &Package) -> anyhow::Result<Utf8PathBuf> {
+    let file_name = crate::config::package(package)?.changelog.file_name;
+    match file_name.as_deref() {
+        None => Ok(package.manifest_path.with_file_name("CHANGELOG.md")),
+        Some(name) if !name.is_empty() && name != ".." && !name.contains(['/', '\\']) => {
+            Ok(package.manifest_path.with_file_name(name))
+        }
+        Some(name) => bail!(
+            "The changelog file name '{}' of '{}' must be a file name without directories",
+            name,
+            package.name
+        ),
+    }
+}
+
+/// Turn `item` into a changelog entry with its message in `messages` if it's there, along with its paths in
+/// `changed_paths` if there are any, marked as submodule update if it's one of `submodule_updates`.
+fn to_entry(
+    item: &commit::history::Item,
+    messages: &BTreeMap<ObjectId, commit::Message>,
+    changed_paths: &BTreeMap<ObjectId, Vec<String>>,
+    submodule_updates: &BTreeSet<ObjectId>,
+) -> changelog::Entry {
+    let message = messages.get(&item.id).unwrap_or(&item.message);
+    changelog::Entry {
+        id: item.id,
+        kind: message.kind,
+        other_kinds: message.other_kinds.clone(),
+        prefix: message.prefix.clone(),
+        title: message.title.clone(),
+        body: message.body.clone(),
+        breaking: message.breaking,
+        breaking_description: message.breaking_description.clone(),
+        highlight: message.highlight,
+        empty_subject: message.empty_subject,
+        dependency_update: message.dependency_update,
+        submodule_update: submodule_updates.contains(&item.id),
+        issues: message
+            .additions
+            .iter()
+            .filter_map(|addition| match addition {
+                commit::message::Addition::IssueId(id) => Some(id.clone()),
+                commit::message::Addition::RegressionOf(_) => None,
+            })
+            .collect(),
+        regressions: message
+            .additions
+            .iter()
+            .filter_map(|addition| match addition {
+                commit::message::Addition::RegressionOf(id) => Some(id.clone()),
+                commit::message::Addition::IssueId(_) => None,
+            })
+            .collect(),
+        author: item.author.clone(),
+        author_email: item.author_email.clone(),
+        changed_paths: changed_paths.get(&item.id).cloned().unwrap_or_default(),
+    }
+}
+
+/// Return the messages of those of `items` squash-merged from pull requests by the id of their commit, classified by the
+/// labels of these if `pr_labels` is set.
+///
+/// If the labels can't be fetched, `pr_labels` is unset and a warning is added to `warnings`, so all remaining changes
+/// are classified by their commit message.
+fn classify_by_pr_labels<'a>(
+    items: impl IntoIterator<Item = &'a commit::history::Item>,
+    pr_labels: &mut Option<github::PrLabels>,
+    warnings: &mut Vec<commit::history::Warning>,
+) -> BTreeMap<ObjectId, commit::Message> {
+    let mut messages = BTreeMap::new();
+    for item in items {
+        let client = match pr_labels.as_mut() {
+            Some(client) => client,
+            None => break,
+        };
+        let number = match item.pull_request {
+            Some(number) => number,
+            None => continue,
+        };
+        match client.labels(number) {
+            Ok(labels) => {
+                let mut message = item.message.clone();
+                message.classify_by_labels(&labels, &client.rules);
+                messages.insert(item.id, message);
+            }
+            Err(err) => {
+                warnings.push(commit::history::Warning::PullRequestLabelsUnavailable { error: err.to_string() });
+                *pr_labels = None;
+            }
+        }
+    }
+    messages
+}
+
+fn to_date(time: git_repository::actor::Time) -> anyhow::Result<time::Date> {
+    Ok(time::OffsetDateTime::from_unix_timestamp(time.time as i64)?
+        .to_offset(time::UtcOffset::from_whole_seconds(time.offset)?)
+        .date())
+}
+
 fn assure_working_tree_is_unchanged(options: Options) -> anyhow::Result<()> {
-    if options.allow_dirty {
+    if options.allow_dirty || options.check {
         Ok(())
     } else {
         crate::git::assure_clean_working_tree().or_else(|err|
@@ -35,3 +964,154 @@ fn assure_working_tree_is_unchanged(options: Options) -> anyhow::Result<()> {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{changelog_directory, generate, link_remote_url, per_version_files};
+    use crate::{
+        changelog::{released_versions, write, ChangeLog, Entry, Section, Version},
+        command::changelog::Options,
+        fixture::Workspace,
+        utils::package_by_name,
+    };
+
+    #[test]
+    fn links_point_to_the_configured_remote_or_origin() {
+        let ws = Workspace::tri_depth("link-remote-url");
+        let ctx = ws.context(&["a"]);
+        let url = |link_remote: Option<&str>| {
+            link_remote_url(&ctx, link_remote).map(|url| url.map(|url| url.host.unwrap_or_default()))
+        };
+
+        assert_eq!(url(None).unwrap(), None, "without origin there is nothing to link to");
+        assert_eq!(
+            url(Some("upstream")).unwrap_err().to_string(),
+            "The remote 'upstream' configured in 'link-remote' doesn't exist, the remotes are: none"
+        );
+
+        ws.git(&["remote", "add", "origin", "https://github.com/fork/repo.git"]);
+        ws.git(&["remote", "add", "upstream", "https://gitlab.com/owner/repo.git"]);
+        assert_eq!(url(None).unwrap().as_deref(), Some("github.com"));
+        assert_eq!(url(Some("upstream")).unwrap().as_deref(), Some("gitlab.com"));
+        assert_eq!(
+            url(Some("other")).unwrap_err().to_string(),
+            "The remote 'other' configured in 'link-remote' doesn't exist, the remotes are: origin, upstream"
+        );
+    }
+
+    #[test]
+    fn releases_are_rendered_into_one_file_each() {
+        let section = |version| Section {
+            version,
+            date: None,
+            entries: vec![Entry {
+                kind: Some("fix"),
+                title: "hi".into(),
+                ..Default::default()
+            }],
+        };
+        let log = ChangeLog {
+            sections: vec![
+                section(Version::Unreleased),
+                section(Version::Semantic("1.0.0".parse().unwrap())),
+                section(Version::Range {
+                    from: "v0.9.0".into(),
+                    to: "main".into(),
+                }),
+            ],
+        };
+        let options = write::Options::default();
+        let files = per_version_files(&log, None, "a", &options).unwrap();
+        assert_eq!(
+            files.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(),
+            ["unreleased.md", "1.0.0.md"],
+            "ranges of commits have no file"
+        );
+        for (_, content) in &files {
+            assert!(content.starts_with("### "), "each file has the section heading");
+            assert!(
+                content.ends_with("- hi\n"),
+                "trailing whitespace is trimmed to a single newline"
+            );
+        }
+
+        let files = per_version_files(
+            &log,
+            Some("{crate_name}: {{ for section in sections }}{section.heading}{{ endfor }}\n\n"),
+            "a",
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            files,
+            [
+                ("unreleased.md".to_string(), "a: Unreleased\n".to_string()),
+                ("1.0.0.md".to_string(), "a: v1.0.0\n".to_string())
+            ],
+            "templates render one section at a time"
+        );
+    }
+
+    #[test]
+    fn changelog_directories_are_relative_to_the_crate() {
+        let ws = Workspace::tri_depth("changelog-directory");
+        for (name, directory) in [("a", "changes"), ("b", "../changes")] {
+            ws.configure(
+                name,
+                &format!(
+                    "[package.metadata.smart-release.changelog]\ndirectory = \"{}\"",
+                    directory
+                ),
+            );
+        }
+        let ctx = ws.context(&["a"]);
+        let directory = |name| changelog_directory(package_by_name(&ctx.meta, name).unwrap());
+
+        assert_eq!(
+            directory("a").unwrap(),
+            Some(
+                package_by_name(&ctx.meta, "a")
+                    .unwrap()
+                    .manifest_path
+                    .with_file_name("changes")
+            )
+        );
+        assert_eq!(
+            directory("b").unwrap_err().to_string(),
+            "The changelog directory '../changes' of 'b' must be a relative path within the crate's directory"
+        );
+        assert_eq!(directory("c").unwrap(), None, "it's optional");
+    }
+
+    #[test]
+    fn changelogs_are_back_filled_with_all_releases_and_stay_the_same_when_regenerated() {
+        let ws = Workspace::tri_depth("back-fill");
+        std::fs::remove_file(ws.root.join("a/CHANGELOG.md")).unwrap();
+        ws.commit("chore: a never had a changelog");
+        for (index, version) in ["0.1.0", "0.2.0", "0.10.0"].iter().enumerate() {
+            ws.write("a/src/lib.rs", &format!("// {}\n", index));
+            ws.commit(&format!("fix: change {}", index));
+            ws.git(&["tag", &format!("a-v{}", version)]);
+        }
+        let ctx = ws.context(&["a"]);
+        let write = || {
+            let options = Options {
+                allow_dirty: true,
+                ..Default::default()
+            };
+            generate(&ctx, options, None, None, &[], None, &[]).unwrap();
+            std::fs::read_to_string(ws.root.join("a/CHANGELOG.md")).unwrap()
+        };
+
+        let back_filled = write();
+        assert_eq!(
+            released_versions(&back_filled),
+            ["0.10.0", "0.2.0", "0.1.0"]
+                .iter()
+                .map(|version| version.parse().unwrap())
+                .collect::<Vec<semver::Version>>(),
+            "all releases are listed, the most recent one first"
+        );
+        assert_eq!(write(), back_filled, "regenerating the changelog doesn't change it");
+    }
+}
@@ -0,0 +1,230 @@
+//! Netting out changes which were reverted, and possibly reapplied, before they were released.
+use std::collections::{BTreeMap, BTreeSet};
+
+use git_repository::hash::ObjectId;
+
+use crate::command::changelog_impl::commit::history::{Item, Segment};
+
+/// Net out reverted changes in the history of each of `segments` as [`net_out()`] does.
+pub fn net_out_segments(ctx: &crate::Context, segments: &mut [Segment<'_>]) -> anyhow::Result<()> {
+    for segment in segments {
+        net_out(ctx, &mut segment.history)?;
+    }
+    Ok(())
+}
+
+/// Remove all changes from `history`, ordered from the newest to the oldest change, whose effect is undone by a later
+/// change in it.
+///
+/// A change is reverted by a commit whose message says `This reverts commit <id>.` like the ones created by `git revert`,
+/// and both are removed. If it's reapplied later on, by reverting the revert or with a commit of the same patch, the
+/// change is kept once and all reverts and reapplications are removed instead.
+pub fn net_out(ctx: &crate::Context, history: &mut Vec<&Item>) -> anyhow::Result<()> {
+    let reverted: Vec<_> = history
+        .iter()
+        .map(|item| item.message.body.as_deref().and_then(reverted_commit))
+        .collect();
+    if !reverted
+        .iter()
+        .flatten()
+        .any(|id| history.iter().any(|item| item.id == *id))
+    {
+        return Ok(());
+    }
+    let patch_ids = crate::git::patch_ids(
+        ctx,
+        &history
+            .iter()
+            .zip(&reverted)
+            .filter(|(_, reverted)| reverted.is_none())
+            .map(|(item, _)| item.id)
+            .collect::<Vec<_>>(),
+    )?;
+    let changes: Vec<_> = history
+        .iter()
+        .zip(&reverted)
+        .rev()
+        .map(|(item, reverted)| (item.id, *reverted, patch_ids.get(&item.id).map(String::as_str)))
+        .collect();
+    let netted_out = netted_out(&changes);
+    history.retain(|item| !netted_out.contains(&item.id));
+    Ok(())
+}
+
+/// Return the id of the commit reverted by a commit with the given message `body`, as stated in its
+/// `This reverts commit <id>.` line.
+fn reverted_commit(body: &str) -> Option<ObjectId> {
+    body.lines().find_map(|line| {
+        let id = line.trim().strip_prefix("This reverts commit ")?;
+        let id = id.split_whitespace().next()?.trim_end_matches(&['.', ','][..]);
+        ObjectId::from_hex(id.as_bytes()).ok()
+    })
+}
+
+/// The state of a change which was reverted at least once.
+struct Change {
+    original: ObjectId,
+    /// The reverts and reapplications of the change.
+    followups: Vec<ObjectId>,
+    applied: bool,
+}
+
+/// Return the ids of all `changes` to remove as their effect is undone, given as id, the id of the change it reverts if
+/// it's a revert, and its patch id if it's known, ordered from the oldest to the newest change.
+fn netted_out(changes: &[(ObjectId, Option<ObjectId>, Option<&str>)]) -> BTreeSet<ObjectId> {
+    let mut change_by_commit = BTreeMap::<ObjectId, usize>::new();
+    let mut change_by_patch = BTreeMap::<&str, usize>::new();
+    let mut netted = Vec::<Change>::new();
+    let mut originals = BTreeMap::<ObjectId, Option<&str>>::new();
+    for (id, reverted, patch_id) in changes {
+        match reverted {
+            Some(reverted) => {
+                let index = match change_by_commit.get(reverted) {
+                    Some(index) => *index,
+                    None => match originals.get(reverted) {
+                        Some(patch_id) => {
+                            netted.push(Change {
+                                original: *reverted,
+                                followups: Vec::new(),
+                                applied: true,
+                            });
+                            let index = netted.len() - 1;
+                            change_by_commit.insert(*reverted, index);
+                            if let Some(patch_id) = patch_id {
+                                change_by_patch.insert(patch_id, index);
+                            }
+                            index
+                        }
+                        None => continue,
+                    },
+                };
+                let change = &mut netted[index];
+                change.followups.push(*id);
+                change.applied = !change.applied;
+                change_by_commit.insert(*id, index);
+            }
+            None => match patch_id.and_then(|patch_id| change_by_patch.get(patch_id)) {
+                Some(index) if !netted[*index].applied => {
+                    let change = &mut netted[*index];
+                    change.followups.push(*id);
+                    change.applied = true;
+                    change_by_commit.insert(*id, *index);
+                }
+                _ => {
+                    originals.insert(*id, *patch_id);
+                }
+            },
+        }
+    }
+    netted
+        .into_iter()
+        .flat_map(|change| {
+            let original = Some(change.original).filter(|_| !change.applied);
+            change.followups.into_iter().chain(original)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use git_repository::hash::ObjectId;
+
+    use super::{net_out, netted_out, reverted_commit};
+    use crate::{command::changelog_impl::git::commit_history, fixture::Workspace};
+
+    fn id(n: u8) -> ObjectId {
+        ObjectId::from_hex(format!("{:040x}", n).as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn reverted_commit_from_body() {
+        let reverted = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(
+            reverted_commit(&format!("This reverts commit {}.", reverted)),
+            Some(ObjectId::from_hex(reverted.as_bytes()).unwrap())
+        );
+        assert_eq!(
+            reverted_commit(&format!(
+                "why\n\nThis reverts commit {}, reversing\nchanges made to x.",
+                reverted
+            )),
+            Some(ObjectId::from_hex(reverted.as_bytes()).unwrap())
+        );
+        assert_eq!(reverted_commit("This reverts commit abc1234."), None);
+        assert_eq!(reverted_commit("Reverts the last change"), None);
+    }
+
+    #[test]
+    fn reverted_changes_are_netted_out() {
+        let netted = |changes: &[(u8, Option<u8>, Option<&str>)]| {
+            let mut netted: Vec<_> = netted_out(
+                &changes
+                    .iter()
+                    .map(|(n, reverted, patch_id)| (id(*n), reverted.map(id), *patch_id))
+                    .collect::<Vec<_>>(),
+            )
+            .into_iter()
+            .collect();
+            netted.sort();
+            netted
+        };
+        assert_eq!(
+            netted(&[(1, None, Some("a")), (2, None, Some("b")), (3, Some(1), None)]),
+            vec![id(1), id(3)],
+            "a change and its revert cancel each other out"
+        );
+        assert_eq!(
+            netted(&[(1, None, Some("a")), (2, Some(1), None), (3, Some(2), None)]),
+            vec![id(2), id(3)],
+            "a change reapplied by reverting its revert is kept once"
+        );
+        assert_eq!(
+            netted(&[(1, None, Some("a")), (2, Some(1), None), (3, None, Some("a"))]),
+            vec![id(2), id(3)],
+            "a change reapplied with the same patch is kept once"
+        );
+        assert_eq!(
+            netted(&[
+                (1, None, Some("a")),
+                (2, Some(1), None),
+                (3, None, Some("a")),
+                (4, Some(3), None)
+            ]),
+            vec![id(1), id(2), id(3), id(4)],
+            "reverting the reapplied change removes it entirely"
+        );
+        assert_eq!(
+            netted(&[(1, None, Some("a")), (2, None, Some("a"))]),
+            Vec::<ObjectId>::new(),
+            "the same patch without a revert in between is kept"
+        );
+        assert_eq!(
+            netted(&[(2, Some(1), None), (3, None, Some("a"))]),
+            Vec::<ObjectId>::new(),
+            "reverts of changes outside of the history are kept"
+        );
+    }
+
+    #[test]
+    fn changes_reverted_and_reapplied_in_a_repository_are_kept_once() {
+        let ws = Workspace::tri_depth("net-out-reapplied");
+        let initial = ws.git(&["rev-parse", "HEAD"]);
+        ws.write("a/src/lib.rs", "// changed\n");
+        ws.commit("fix: change a");
+        let change = ws.git(&["rev-parse", "HEAD"]);
+        ws.git(&["revert", "--no-edit", "HEAD"]);
+        ws.write("a/src/lib.rs", "// changed\n");
+        ws.commit("fix: change a again");
+        let ctx = ws.context(&["a"]);
+        let history = commit_history(&ctx, Default::default(), &[]).unwrap().expect("born");
+        let mut items: Vec<_> = history.items.iter().collect();
+        assert_eq!(items.len(), 4);
+
+        net_out(&ctx, &mut items).unwrap();
+        assert_eq!(
+            items.iter().map(|item| item.id.to_string()).collect::<Vec<_>>(),
+            [change, initial],
+            "the revert and the reapplication with the same patch are removed, computing patch ids in the repository"
+        );
+    }
+}
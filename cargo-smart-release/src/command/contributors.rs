@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    command::changelog_impl::{commit::history::log_warnings, git},
+    utils::{package_by_name, will},
+};
+
+/// The name of the file listing the contributors of a crate, next to its manifest.
+const FILE_NAME: &str = "CONTRIBUTORS.md";
+
+/// Write a file listing the authors of all changes to each of `crates` in its entire history next to its manifest, or
+/// the one of the crate in the current directory if none are given. With `dry_run`, the files are printed instead.
+///
+/// Authors are deduplicated by email address, and listed with their handle from `author-handles` if there is one.
+pub fn contributors(dry_run: bool, crates: Vec<String>) -> anyhow::Result<()> {
+    let ctx = crate::Context::new(crates)?;
+    let history = match git::commit_history(&ctx, Default::default(), &[])? {
+        None => return Ok(()),
+        Some(history) => history,
+    };
+    let mut warnings = history.warnings.clone();
+    for crate_name in &ctx.crate_names {
+        let package = package_by_name(&ctx.meta, crate_name)?;
+        let (segments, segment_warnings) = git::ref_segments(crate_name, &ctx, &history)?;
+        warnings.extend(segment_warnings);
+        let names = names(
+            segments
+                .iter()
+                .flat_map(|segment| segment.history.iter())
+                .map(|item| (item.author.as_str(), item.author_email.as_str())),
+            &ctx.config.changelog.author_handles,
+        );
+        let mut content = format!(
+            "# Contributors\n\nEveryone who contributed to `{}`, generated from its commit history.\n\n",
+            crate_name
+        );
+        for name in &names {
+            content.push_str(&format!("- {}\n", name));
+        }
+
+        let path = package.manifest_path.with_file_name(FILE_NAME);
+        log::info!("{} write {} contributor(s) to '{}'", will(dry_run), names.len(), path);
+        if dry_run {
+            print!("{}", content);
+        } else {
+            std::fs::write(&path, content)?;
+        }
+    }
+    log_warnings(&warnings);
+    Ok(())
+}
+
+/// Return the names of all `authors`, given as name and email address from the most recent to the oldest change,
+/// deduplicated by case-insensitive email address and then by name, and sorted case-insensitively.
+///
+/// Authors with a handle in `handles` are listed with it, and all others with the name of their most recent change.
+fn names<'a>(authors: impl IntoIterator<Item = (&'a str, &'a str)>, handles: &BTreeMap<String, String>) -> Vec<String> {
+    let handles: BTreeMap<_, _> = handles
+        .iter()
+        .map(|(email, handle)| (email.to_lowercase(), handle.as_str()))
+        .collect();
+    let mut names_by_email = BTreeMap::new();
+    for (name, email) in authors {
+        let email = email.to_lowercase();
+        let name = handles.get(&email).copied().unwrap_or(name).to_owned();
+        names_by_email.entry(email).or_insert(name);
+    }
+    let mut names: Vec<_> = names_by_email.into_values().collect();
+    names.sort_by_cached_key(|name| (name.to_lowercase(), name.clone()));
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::names;
+
+    #[test]
+    fn authors_are_deduplicated_by_email_and_use_their_handles() {
+        let handles = std::iter::once(("Sebastian.Thiel@icloud.com".to_string(), "@Byron".to_string())).collect();
+        assert_eq!(
+            names(
+                vec![
+                    ("Sebastian Thiel", "sebastian.thiel@icloud.com"),
+                    ("zed", "zed@example.com"),
+                    ("Zed Old", "ZED@example.com"),
+                    ("alice", "alice@example.com"),
+                    ("Alice", "alice@work.example.com"),
+                    ("alice", "alice@home.example.com"),
+                ],
+                &handles
+            ),
+            vec!["@Byron", "Alice", "alice", "zed"],
+            "the most recent name is used, handles are found case-insensitively, and the same name with another email address is listed once"
+        );
+    }
+
+    #[test]
+    fn names_differing_in_case_are_listed_once_each() {
+        assert_eq!(
+            names(
+                vec![
+                    ("alice", "a@example.com"),
+                    ("Alice", "b@example.com"),
+                    ("alice", "c@example.com"),
+                ],
+                &Default::default()
+            ),
+            vec!["Alice", "alice"],
+            "the same name is listed once even if a name differing in case sorts between its email addresses"
+        );
+    }
+}
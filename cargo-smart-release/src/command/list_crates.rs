@@ -0,0 +1,45 @@
+use crate::utils::{is_publishable, package_by_id, tag_prefix};
+
+/// Print all workspace members along with their version, whether they are publishable and the prefix of their tags.
+pub fn list_crates() -> anyhow::Result<()> {
+    let ctx = crate::Context::for_introspection()?;
+    let mut rows: Vec<_> = ctx
+        .meta
+        .workspace_members
+        .iter()
+        .map(|id| {
+            let package = package_by_id(&ctx.meta, id);
+            [
+                package.name.clone(),
+                package.version.to_string(),
+                if is_publishable(package) { "yes" } else { "no" }.to_owned(),
+                match tag_prefix(package, &ctx.repo) {
+                    Some(prefix) => format!("{}-v", prefix),
+                    None => "v".into(),
+                },
+            ]
+        })
+        .collect();
+    rows.sort();
+    rows.insert(
+        0,
+        ["NAME", "VERSION", "PUBLISHABLE", "TAG PREFIX"].map(ToOwned::to_owned),
+    );
+
+    let mut widths = [0; 4];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in rows {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
@@ -1,37 +1,228 @@
 pub mod release {
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Default, Clone, Copy)]
     pub struct Options {
         pub dry_run: bool,
+        /// Don't ask for confirmation before releasing, which is required if stdin isn't a terminal.
+        pub yes: bool,
         pub allow_dirty: bool,
         pub ignore_instability: bool,
         pub skip_publish: bool,
         pub dry_run_cargo_publish: bool,
+        /// The maximum amount of `cargo publish --dry-run` invocations to run at once with `dry_run_cargo_publish`, or
+        /// the amount of available CPUs if unset.
+        pub dry_run_cargo_publish_jobs: Option<usize>,
         pub conservative_pre_release_version_handling: bool,
         /// Pass --no-verify unconditionally to cargo publish. Really just for fixing things
         pub no_verify: bool,
         pub skip_tag: bool,
+        /// Sign the release commit with the key configured in git.
+        pub commit_sign: bool,
+        /// Move tags of the new versions to the release commit if they exist already instead of refusing to release.
+        pub force_tag: bool,
         pub allow_auto_publish_of_stable_crates: bool,
         pub update_crates_index: bool,
+        /// Never update the crates.io index but read it as present locally, even when waiting for new versions to appear in it.
+        pub skip_crates_index_update: bool,
         pub bump_when_needed: bool,
+        /// Run `cargo semver-checks` on each crate to release which was published before, and raise its bump to a
+        /// breaking one if it detects breaking changes to the API.
+        pub semver_checks: bool,
         pub verbose: bool,
+        /// In dry-run mode, print the changes to each manifest as unified diff to stdout.
+        pub color_diff: bool,
         pub skip_push: bool,
+        /// Don't push `HEAD` with the release commit, but push tags unless `skip_push_tags` is set.
+        pub skip_push_commits: bool,
+        /// Don't push tags, but push `HEAD` unless `skip_push_commits` is set.
+        pub skip_push_tags: bool,
+        /// Push `HEAD` and tags in a single `git push --atomic` invocation so that either all or none of them are updated.
+        pub atomic_push: bool,
         pub skip_dependencies: bool,
+        /// Release all publishable workspace members instead of the given crates, except for the excluded ones.
+        pub workspace: bool,
+        /// Release exactly the given crates without automatically including changed dependencies or bumping dependents
+        /// for safety, which implies `skip_dependencies` and disables `isolate_dependencies_from_breaking_changes`.
+        ///
+        /// Fails if one of the crates depends on a workspace crate that isn't published in a compatible version.
+        pub only: bool,
+        /// Release the given crates only if one of their workspace dependencies is released, ignoring their own changes,
+        /// and bump them like dependencies.
+        pub bump_dependencies_only: bool,
+        /// Put the manifest changes of all crates into a single release commit. Otherwise, each crate gets its own
+        /// release commit and tag, in publishing order, except for crates published together due to dependency cycles.
         pub multi_crate_release: bool,
         pub isolate_dependencies_from_breaking_changes: bool,
+        /// Ignore the state of a previous release that failed midway instead of resuming it.
+        pub restart: bool,
+        /// The parts of the release to perform.
+        pub phase: Phase,
+        /// Write a `release-<version>.toml` file with machine-readable information about each released crate into
+        /// `<target-dir>/smart-release/<crate>/`.
+        pub release_metadata: bool,
+        /// The time to wait between successive invocations of `cargo publish`, to respect rate limits of the registry.
+        pub publish_delay: std::time::Duration,
+        /// If set, wait up to the given time after publishing each crate until its new version is visible in the
+        /// crates.io index.
+        pub wait_for_index: Option<std::time::Duration>,
+        /// The day to consider today when dating a release, instead of the current day or the one in `SOURCE_DATE_EPOCH`.
+        pub date_today: Option<time::Date>,
+        /// Print the names of the tags for the computed versions of all crates to release to stdout, one per line,
+        /// instead of releasing them.
+        pub tag_name_only: bool,
+        /// Print why each crate is released with its new version, along with the changes since its last release, to
+        /// stdout instead of releasing them.
+        pub explain: bool,
+        /// Print the dependency graph of the crates to release and their dependents with updated manifests to stdout in the
+        /// given format instead of releasing them.
+        pub dependencies_graph: Option<GraphFormat>,
+        /// Fail if a release tag of any workspace crate points to a commit that isn't reachable from `HEAD` anymore, as
+        /// happens if history was rewritten after a release.
+        pub tags_are_immutable: bool,
+        /// If set, fail if more than the given amount of commits would have to be traversed when the commit history is
+        /// needed.
+        pub max_commits: Option<usize>,
+        /// If set, stop the traversal after `max_commits` instead of failing.
+        pub truncate_history: bool,
+        /// Only publish the single crate to release at the version in its manifest, and push its existing tag, for
+        /// releases that were committed and tagged but failed to publish.
+        pub publish_only: bool,
+        /// If set, the requirements of dependents are updated for crates whose version is kept, like with `--bump keep`,
+        /// as they are for bumped crates.
+        pub update_dependents_of_kept_versions: bool,
+        /// If set, crates whose version is kept are tagged with it, as bumped crates are tagged with their new version.
+        pub tag_kept_versions: bool,
+    }
+
+    /// A crate to release along with the version computed for it, as passed to [`VersionTransform`]s.
+    #[derive(Debug, Clone)]
+    pub struct ProposedVersion<'a> {
+        pub package: &'a cargo_metadata::Package,
+        pub new_version: semver::Version,
+    }
+
+    /// A step to rewrite the versions computed for the crates to release before they are written to manifests and used
+    /// for tags, like to follow a calendar-versioning scheme.
+    pub trait VersionTransform {
+        /// Return the versions to use instead of the `proposed` ones, keyed by crate name. Crates without a replacement
+        /// keep their proposed version, and replacements must be greater than the current version of their crate.
+        fn transform(
+            &self,
+            proposed: &[ProposedVersion<'_>],
+        ) -> anyhow::Result<std::collections::BTreeMap<String, semver::Version>>;
+    }
+
+    /// Use the given versions, keyed by crate name, like the ones read from a file with [`crates_from_file()`][crate::command::crates_from_file()].
+    ///
+    /// All of these crates must be going to be released, which isn't the case for those without changes.
+    #[derive(Debug, Default, Clone)]
+    pub struct ExplicitVersions(pub std::collections::BTreeMap<String, semver::Version>);
+
+    impl VersionTransform for ExplicitVersions {
+        fn transform(
+            &self,
+            proposed: &[ProposedVersion<'_>],
+        ) -> anyhow::Result<std::collections::BTreeMap<String, semver::Version>> {
+            if let Some((name, version)) = self
+                .0
+                .iter()
+                .find(|(name, _)| !proposed.iter().any(|p| p.package.name == **name))
+            {
+                anyhow::bail!(
+                    "Version {} of '{}' was given with --crates-from, but '{}' isn't going to be released, usually as it didn't change since its last release - remove its version from the file to skip it",
+                    version,
+                    name,
+                    name
+                );
+            }
+            Ok(self.0.clone())
+        }
+    }
+
+    /// The format to print the dependency graph of a release in.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum GraphFormat {
+        /// Each crate on its own line, followed by its dependencies in the graph.
+        Text,
+        /// A digraph in the DOT language of Graphviz.
+        Dot,
+    }
+
+    /// The inputs to a release beyond the crates to release and their bumps, which aren't plain flags like [`Options`].
+    #[derive(Default)]
+    pub struct Inputs {
+        /// The workspace members not to release with `Options::workspace`.
+        pub excluded: Vec<String>,
+        /// Full or abbreviated ids of commits whose changes don't make a crate count as changed since its last release.
+        pub excluded_commits: Vec<String>,
+        /// Arguments passed to `cargo publish` for each crate that doesn't configure its own.
+        pub publish_args: Vec<String>,
+        /// Globs relative to the directory of each bumped crate matching files to update its version in.
+        pub bump_files: Vec<String>,
+        /// Rewrite the computed versions in order, after the `version-hook` configured in the workspace, if there is one.
+        pub version_transforms: Vec<Box<dyn VersionTransform>>,
+    }
+
+    /// The parts of a release to perform, allowing the release commit to be reviewed before anything is published.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub enum Phase {
+        /// Edit manifests and commit the changes, then publish, tag and push.
+        #[default]
+        All,
+        /// Only edit manifests and commit the changes, without tagging, publishing or pushing.
+        Prepare,
+        /// Only publish, tag and push the crates whose versions were set in a previous `Prepare` phase.
+        ///
+        /// Versions are taken from the manifests as is and tags point to the current `HEAD`.
+        Publish,
     }
 }
 #[path = "release/mod.rs"]
 mod release_impl;
-pub use release_impl::release;
+pub use release_impl::{crates_from_file, release};
 
 pub mod changelog {
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Default, Clone, Copy)]
     pub struct Options {
         pub dry_run: bool,
         pub dependencies: bool,
         pub allow_dirty: bool,
+        /// If set, fail if more than the given amount of commits would have to be traversed.
+        pub max_commits: Option<usize>,
+        /// If set, stop the traversal after `max_commits` instead of failing.
+        pub truncate_history: bool,
+        /// If set, render a table of contents linking to each release at the top of the generated changelog.
+        pub table_of_contents: bool,
+        /// If set, don't write anything but fail if the generated content of any changelog isn't up to date.
+        pub check: bool,
+        /// If set, print the unreleased changes of all publishable workspace crates to stdout instead of writing changelogs.
+        pub preview_all: bool,
+        /// If set, the changes of pre-releases like `1.0.0-rc.1` are merged into the section of the release of their version.
+        pub merge_prereleases: bool,
+        /// If set, the sections of releases already in a changelog are kept as they are, and only sections of newer
+        /// releases and unreleased changes are generated.
+        pub keep_released_sections: bool,
+        /// If set, trailers like `Signed-off-by` are removed from the bodies of changes before rendering them, which only
+        /// matters for templates and the first body line used as title of changes with an empty subject.
+        pub strip_trailers: bool,
+        /// If set, changes squash-merged from GitHub pull requests are classified by the labels of these, as configured
+        /// in `pr-labels`, falling back to their commit message if GitHub can't be reached.
+        pub notes_from_pr_labels: bool,
+        /// The day to date the section of an explicit range of commits with, or `None` to use the `SOURCE_DATE_EPOCH`
+        /// environment variable or the current day.
+        pub date_today: Option<time::Date>,
     }
+
+    pub use super::changelog_impl::git::commit_history;
 }
 #[path = "changelog/mod.rs"]
 mod changelog_impl;
-pub use changelog_impl::changelog;
+pub use changelog_impl::{changelog, lint};
+
+mod list_crates;
+pub use list_crates::list_crates;
+
+mod audit_tags;
+pub use audit_tags::audit_tags;
+
+mod contributors;
+pub use contributors::contributors;
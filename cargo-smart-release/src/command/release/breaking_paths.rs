@@ -0,0 +1,90 @@
+use anyhow::bail;
+use cargo_metadata::Package;
+
+use super::{
+    semver_checks::{breaking_bump, escalate_to_breaking},
+    Context,
+};
+use crate::config::Bump;
+
+/// Return the bump spec needed for a breaking change if files of `package` matching its `breaking-paths` changed since
+/// its latest release and `bump_spec` is a smaller bump, or `bump_spec` otherwise.
+///
+/// The bump needed for a breaking change can be configured with `breaking-paths-bump`, and crates which were never
+/// released are skipped as there is nothing to compare them to.
+pub(in crate::command::release_impl) fn escalate_bump_spec<'a>(
+    package: &Package,
+    bump_spec: &'a str,
+    ctx: &Context,
+) -> anyhow::Result<&'a str> {
+    let (tag_name, changed_paths) = match crate::git::changed_breaking_paths(package, &ctx.base)? {
+        Some((tag_name, changed_paths)) if !changed_paths.is_empty() => (tag_name, changed_paths),
+        _ => return Ok(bump_spec),
+    };
+    let breaking = match crate::config::package(package)?.breaking_paths_bump {
+        Some(Bump::Patch) => bail!(
+            "The 'breaking-paths-bump' of '{}' must be 'minor' or 'major' to signal breaking changes",
+            package.name
+        ),
+        Some(bump) => bump,
+        None => breaking_bump(package)?,
+    };
+    Ok(escalate_to_breaking(
+        package,
+        bump_spec,
+        breaking,
+        &format!(
+            "its breaking paths changed since {}: {}",
+            tag_name,
+            changed_paths.join(", ")
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escalate_bump_spec;
+    use crate::{command::release_impl::Context, fixture::Workspace, utils::package_by_name};
+
+    #[test]
+    fn smaller_bumps_are_raised_if_breaking_paths_changed() {
+        let ws = Workspace::tri_depth("escalate-breaking-paths");
+        let configure = |config: &str| {
+            ws.configure(
+                "a",
+                &format!(
+                    "[package.metadata.smart-release]\nbreaking-paths = [\"src/**\"]\n{}",
+                    config
+                ),
+            );
+            ws.commit("configure breaking paths");
+        };
+        configure("");
+        ws.git(&["tag", "a-v0.8.0"]);
+        ws.write("a/src/lib.rs", "// changed\n");
+        ws.commit("change a");
+        let escalate = |bump_spec| {
+            let ctx = Context::new(ws.context(&["a"]), "auto".into(), "auto".into(), Vec::new(), Vec::new()).unwrap();
+            let a = package_by_name(&ctx.base.meta, "a").unwrap();
+            escalate_bump_spec(a, bump_spec, &ctx).map(ToOwned::to_owned)
+        };
+
+        assert_eq!(
+            escalate("patch").unwrap(),
+            "minor",
+            "minor bumps are breaking before 1.0"
+        );
+        assert_eq!(escalate("major").unwrap(), "major", "larger bumps are kept");
+        assert_eq!(escalate("keep").unwrap(), "keep", "kept versions stay as they are");
+
+        configure("breaking-paths-bump = \"major\"");
+        assert_eq!(escalate("patch").unwrap(), "major");
+        assert_eq!(escalate("minor").unwrap(), "major");
+
+        configure("breaking-paths-bump = \"patch\"");
+        assert_eq!(
+            escalate("patch").unwrap_err().to_string(),
+            "The 'breaking-paths-bump' of 'a' must be 'minor' or 'major' to signal breaking changes"
+        );
+    }
+}
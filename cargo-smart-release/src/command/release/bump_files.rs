@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context as _};
+use cargo_metadata::{camino::Utf8PathBuf, Package};
+
+use super::Context;
+use crate::{config::version_regex, utils::will};
+
+/// Replace the current version of `package` with `new_version` in the files configured in its `bump-files` and in those
+/// matching the globs given with `--bump-files`, all relative to its directory.
+///
+/// The new content of each changed file is put into `new_content_by_path`, which is also used as source for the files
+/// changed for other crates already.
+pub(in crate::command::release_impl) fn update(
+    package: &Package,
+    new_version: &str,
+    ctx: &Context,
+    new_content_by_path: &mut BTreeMap<Utf8PathBuf, String>,
+    verbose: bool,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let current_version = package.version.to_string();
+    if current_version == new_version {
+        return Ok(());
+    }
+    let crate_dir = package
+        .manifest_path
+        .parent()
+        .expect("parent of a file is always present");
+    let configured = crate::config::package(package)?.bump_files;
+    let files = configured
+        .iter()
+        .map(|file| (file.path.as_str(), file.pattern.as_deref(), true))
+        .chain(ctx.bump_files.iter().map(|glob| (glob.as_str(), None, false)));
+
+    for (glob, pattern, must_match) in files {
+        let pattern = pattern.unwrap_or("{version}");
+        let regex = version_regex(pattern, &current_version)?;
+        let paths = glob::glob(crate_dir.join(glob).as_str())
+            .with_context(|| format!("Invalid glob pattern '{}' of files to bump", glob))?
+            .map(|path| {
+                Utf8PathBuf::from_path_buf(path?).map_err(|path| anyhow::anyhow!("Invalid UTF-8 in {}", path.display()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if paths.is_empty() && must_match {
+            bail!(
+                "'{}' in 'bump-files' of '{}' doesn't match any file",
+                glob,
+                package.name
+            );
+        }
+        for path in paths.into_iter().filter(|path| path.is_file()) {
+            let content = match new_content_by_path.get(&path) {
+                Some(content) => content.clone(),
+                None => std::fs::read_to_string(&path)?,
+            };
+            let (new_content, num_replacements) = replace_version(&content, &regex, new_version);
+            if num_replacements == 0 {
+                if must_match {
+                    bail!(
+                        "'{}' doesn't contain version {} of '{}' matching '{}'",
+                        path,
+                        current_version,
+                        package.name,
+                        pattern
+                    );
+                }
+                continue;
+            }
+            if verbose {
+                log::info!(
+                    "{} replace {} occurrence(s) of version {} of '{}' with {} in '{}'",
+                    will(dry_run),
+                    num_replacements,
+                    current_version,
+                    package.name,
+                    new_version,
+                    path.strip_prefix(&ctx.base.root).unwrap_or(&path)
+                );
+            }
+            new_content_by_path.insert(path, new_content);
+        }
+    }
+    Ok(())
+}
+
+/// Replace the `version` capture group of all matches of `regex` in `content` with `new_version`, except for those
+/// which are part of a longer version, like `1.2.3` in `1.2.30` or `1.2.3-alpha`.
+///
+/// Return the new content along with the amount of replacements.
+fn replace_version(content: &str, regex: &regex::Regex, new_version: &str) -> (String, usize) {
+    let mut out = String::with_capacity(content.len());
+    let mut last_end = 0;
+    let mut num_replacements = 0;
+    for version in regex.captures_iter(content).filter_map(|c| c.name("version")) {
+        let (before, after) = (
+            &content.as_bytes()[..version.start()],
+            &content.as_bytes()[version.end()..],
+        );
+        let preceded_by_version = matches!(before, [.., b'0'..=b'9'] | [.., b'0'..=b'9', b'.']);
+        let followed_by_version = matches!(after, [b'0'..=b'9' | b'-' | b'+', ..] | [b'.', b'0'..=b'9', ..]);
+        if preceded_by_version || followed_by_version {
+            continue;
+        }
+        out.push_str(&content[last_end..version.start()]);
+        out.push_str(new_version);
+        last_end = version.end();
+        num_replacements += 1;
+    }
+    out.push_str(&content[last_end..]);
+    (out, num_replacements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_replaced_unless_part_of_a_longer_one() {
+        let regex = version_regex("{version}", "1.2.3").unwrap();
+        assert_eq!(
+            replace_version("v1.2.3, 1.2.3\n1.2.30 11.2.3 1.2.3-alpha 1.2.3.", &regex, "1.3.0"),
+            ("v1.3.0, 1.3.0\n1.2.30 11.2.3 1.2.3-alpha 1.3.0.".into(), 3)
+        );
+    }
+
+    #[test]
+    fn only_the_version_within_a_pattern_is_replaced() {
+        let regex = version_regex(r#"my-crate = "\^?{version}""#, "0.1.0").unwrap();
+        assert_eq!(
+            replace_version(
+                "other = \"0.1.0\"\nmy-crate = \"0.1.0\"\nmy-crate = \"^0.1.0\"",
+                &regex,
+                "0.2.0"
+            ),
+            (
+                "other = \"0.1.0\"\nmy-crate = \"0.2.0\"\nmy-crate = \"^0.2.0\"".into(),
+                2
+            )
+        );
+    }
+}
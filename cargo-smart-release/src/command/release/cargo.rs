@@ -1,48 +1,79 @@
-use std::process::Command;
+use std::{
+    process::Command,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use anyhow::bail;
 use cargo_metadata::Package;
 
-use super::Options;
+use super::{Context, Options};
 use crate::utils::will;
 
+/// Flags of `cargo publish` which are passed as needed by us and thus can't be set by the user.
+const MANAGED_FLAGS: &[&str] = &[
+    "--no-verify",
+    "--dry-run",
+    "--allow-dirty",
+    "--manifest-path",
+    "--package",
+    "-p",
+];
+
+/// Fail if any of the user-provided `args` for `cargo publish` as configured in `origin` is one of the flags we manage.
+pub(in crate::command::release_impl) fn assure_publish_args_are_not_managed(
+    args: &[String],
+    origin: &str,
+) -> anyhow::Result<()> {
+    for arg in args {
+        let flag = arg.split('=').next().expect("split yields at least one item");
+        if MANAGED_FLAGS.contains(&flag) {
+            bail!(
+                "'{}' in {} can't be passed to 'cargo publish' as it is controlled by cargo smart-release",
+                arg,
+                origin
+            );
+        }
+    }
+    Ok(())
+}
+
 pub(in crate::command::release_impl) fn publish_crate(
     publishee: &Package,
+    new_version: &str,
     other_publishee_names: &[String],
-    Options {
+    ctx: &Context,
+    options: Options,
+) -> anyhow::Result<()> {
+    let Options {
         skip_publish,
         dry_run,
         dry_run_cargo_publish,
-        allow_dirty,
-        no_verify,
         verbose,
+        publish_delay,
+        wait_for_index,
+        skip_crates_index_update,
         ..
-    }: Options,
-) -> anyhow::Result<()> {
+    } = options;
     if skip_publish {
         return Ok(());
     }
+    if ctx.published_any.replace(true) && !publish_delay.is_zero() {
+        log::info!(
+            "{} wait {:.1}s before publishing {} as requested by --publish-delay",
+            will(dry_run),
+            publish_delay.as_secs_f32(),
+            publishee.name
+        );
+        if !dry_run {
+            std::thread::sleep(publish_delay);
+        }
+    }
     let max_attempts = 3;
-    let must_not_verify = publishee
-        .dependencies
-        .iter()
-        .any(|dep| other_publishee_names.contains(&dep.name));
     let uses_cargo_dry_run = dry_run && dry_run_cargo_publish;
     let cargo_must_run = !dry_run || uses_cargo_dry_run;
     for attempt in 1..=max_attempts {
-        let mut c = Command::new("cargo");
-        c.arg("publish");
-
-        if allow_dirty {
-            c.arg("--allow-dirty");
-        }
-        if no_verify || must_not_verify {
-            c.arg("--no-verify");
-        }
-        if uses_cargo_dry_run {
-            c.arg("--dry-run");
-        }
-        c.arg("--manifest-path").arg(&publishee.manifest_path);
+        let mut c = publish_command(publishee, other_publishee_names, ctx, options)?;
         if verbose {
             log::info!("{} run {:?}", will(!cargo_must_run), c);
         }
@@ -58,9 +89,173 @@ pub(in crate::command::release_impl) fn publish_crate(
             );
         }
     }
+    if let Some(timeout) = wait_for_index {
+        wait_for_version_in_index(publishee, new_version, timeout, ctx, dry_run, !skip_crates_index_update)?;
+    }
+    Ok(())
+}
+
+/// Return the `cargo publish` invocation for `publishee`, which is run with `--dry-run` if `options` ask for it.
+///
+/// It isn't verified if it depends on one of `other_publishee_names` which aren't published yet.
+fn publish_command(
+    publishee: &Package,
+    other_publishee_names: &[String],
+    ctx: &Context,
+    Options {
+        dry_run,
+        dry_run_cargo_publish,
+        allow_dirty,
+        no_verify,
+        ..
+    }: Options,
+) -> anyhow::Result<Command> {
+    let publish_args = match crate::config::package(publishee)?.publish_args {
+        Some(args) => {
+            assure_publish_args_are_not_managed(
+                &args,
+                &format!("[package.metadata.smart-release] of '{}'", publishee.name),
+            )?;
+            args
+        }
+        None => ctx.publish_args.clone(),
+    };
+    let must_not_verify = publishee
+        .dependencies
+        .iter()
+        .any(|dep| other_publishee_names.contains(&dep.name));
+    let mut c = Command::new("cargo");
+    c.arg("publish");
+
+    if allow_dirty {
+        c.arg("--allow-dirty");
+    }
+    if no_verify || must_not_verify {
+        c.arg("--no-verify");
+    }
+    if dry_run && dry_run_cargo_publish {
+        c.arg("--dry-run");
+    }
+    c.arg("--manifest-path").arg(&publishee.manifest_path);
+    c.args(&publish_args);
+    Ok(c)
+}
+
+/// Run `cargo publish --dry-run` for all `publishees` along with the names of the crates published after each of them
+/// in the same batch, up to `options.dry_run_cargo_publish_jobs` at a time, and fail with the names of all crates for
+/// which it failed.
+pub(in crate::command::release_impl) fn dry_run_publish_crates(
+    publishees: Vec<(&Package, Vec<String>)>,
+    ctx: &Context,
+    options: Options,
+) -> anyhow::Result<()> {
+    let mut queue = Vec::new();
+    for (publishee, other_publishee_names) in publishees {
+        let c = publish_command(publishee, &other_publishee_names, ctx, options)?;
+        if options.verbose {
+            log::info!("{} run {:?}", will(false), c);
+        }
+        queue.push((publishee.name.to_owned(), c));
+    }
+    let jobs = options
+        .dry_run_cargo_publish_jobs
+        .or_else(|| std::thread::available_parallelism().ok().map(usize::from))
+        .unwrap_or(1)
+        .min(queue.len());
+    // Workers take invocations from the end of the queue, which is why it's in reverse publishing order.
+    queue.reverse();
+    let queue = Arc::new(Mutex::new(queue));
+    let workers: Vec<_> = (0..jobs)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            std::thread::spawn(move || {
+                let mut outputs = Vec::new();
+                loop {
+                    let next = queue.lock().expect("workers don't panic").pop();
+                    match next {
+                        Some((name, mut c)) => outputs.push((name, c.output())),
+                        None => break outputs,
+                    }
+                }
+            })
+        })
+        .collect();
+    let mut failed = Vec::new();
+    for worker in workers {
+        for (name, output) in worker.join().expect("workers don't panic") {
+            let output = output?;
+            if !output.status.success() {
+                log::error!(
+                    "'cargo publish --dry-run' failed for '{}':\n{}",
+                    name,
+                    String::from_utf8_lossy(&output.stderr).trim_end()
+                );
+                failed.push(name);
+            }
+        }
+    }
+    if !failed.is_empty() {
+        bail!(
+            "Could not successfully execute 'cargo publish --dry-run' for {}.",
+            failed.join(", ")
+        )
+    }
     Ok(())
 }
 
+/// Update the crates.io index until it contains `new_version` of `publishee`, failing if that takes longer than `timeout`.
+///
+/// If `update_index` is false, the local index is only read repeatedly, assuming it's updated by other means.
+fn wait_for_version_in_index(
+    publishee: &Package,
+    new_version: &str,
+    timeout: Duration,
+    ctx: &Context,
+    dry_run: bool,
+    update_index: bool,
+) -> anyhow::Result<()> {
+    log::info!(
+        "{} wait up to {:.0}s for '{}' v{} to appear in the crates.io index",
+        will(dry_run),
+        timeout.as_secs_f32(),
+        publishee.name,
+        new_version
+    );
+    if dry_run {
+        return Ok(());
+    }
+    let poll_interval = Duration::from_secs(5);
+    let start = Instant::now();
+    loop {
+        if update_index {
+            ctx.crates_index.update()?;
+        }
+        let is_visible = ctx
+            .crates_index
+            .crate_(&publishee.name)
+            .map(|krate| krate.versions().iter().any(|v| v.version() == new_version))
+            .unwrap_or_default();
+        if is_visible {
+            log::info!(
+                "'{}' v{} appeared in the crates.io index after {:.0}s",
+                publishee.name,
+                new_version,
+                start.elapsed().as_secs_f32()
+            );
+            return Ok(());
+        }
+        if start.elapsed() + poll_interval > timeout {
+            bail!(
+                "'{}' v{} didn't appear in the crates.io index within {:.0}s - run the release again to resume it once it does",
+                publishee.name,
+                new_version,
+                timeout.as_secs_f32()
+            );
+        }
+        std::thread::sleep(poll_interval);
+    }
+}
+
 pub fn refresh_lock_file() -> anyhow::Result<()> {
     cargo_metadata::MetadataCommand::new().exec()?;
     Ok(())
@@ -0,0 +1,84 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{anyhow, bail, Context as _};
+
+use crate::command::release::ExplicitVersions;
+
+/// Read the crates to release from the file at `path`, along with the versions to release them at if given.
+///
+/// Each line names a crate, optionally followed by its version like `my-crate 1.2.0`. Empty lines and everything after
+/// a `#` are ignored.
+pub fn crates_from_file(path: &Path) -> anyhow::Result<(Vec<String>, ExplicitVersions)> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Could not read crates to release from {:?}", path))?;
+    parse(&content).with_context(|| format!("Invalid list of crates to release in {:?}", path))
+}
+
+fn parse(content: &str) -> anyhow::Result<(Vec<String>, ExplicitVersions)> {
+    let mut crates = Vec::new();
+    let mut versions = BTreeMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let name = match tokens.next() {
+            Some(name) => name,
+            None => continue,
+        };
+        if crates.iter().any(|existing| existing == name) {
+            bail!("Line {}: crate '{}' is listed more than once", line_number, name);
+        }
+        crates.push(name.to_owned());
+        if let Some(version) = tokens.next() {
+            let version = version.parse().map_err(|err| {
+                anyhow!(
+                    "Line {}: invalid version '{}' of '{}': {}",
+                    line_number,
+                    version,
+                    name,
+                    err
+                )
+            })?;
+            versions.insert(name.to_owned(), version);
+        }
+        if let Some(unexpected) = tokens.next() {
+            bail!(
+                "Line {}: unexpected '{}' after the name and version of '{}'",
+                line_number,
+                unexpected,
+                name
+            );
+        }
+    }
+    Ok((crates, ExplicitVersions(versions)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn names_with_optional_versions() {
+        let (crates, versions) = parse("# release train\nfoo 1.2.0\n\n  bar  # no version\nbaz 0.1.0-rc.1\n").unwrap();
+        assert_eq!(crates, vec!["foo", "bar", "baz"]);
+        assert_eq!(
+            versions
+                .0
+                .iter()
+                .map(|(name, version)| format!("{} {}", name, version))
+                .collect::<Vec<_>>(),
+            vec!["baz 0.1.0-rc.1", "foo 1.2.0"]
+        );
+    }
+
+    #[test]
+    fn invalid_lines_are_reported_with_their_number() {
+        let err = |content: &str| parse(content).unwrap_err().to_string();
+        assert_eq!(err("foo\nfoo 1.0.0"), "Line 2: crate 'foo' is listed more than once");
+        assert!(err("foo 1.0").starts_with("Line 1: invalid version '1.0' of 'foo'"));
+        assert_eq!(
+            err("\nfoo 1.0.0 bar"),
+            "Line 2: unexpected 'bar' after the name and version of 'foo'"
+        );
+    }
+}
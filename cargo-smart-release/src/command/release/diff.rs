@@ -0,0 +1,91 @@
+/// The amount of unchanged lines to show around each change.
+const CONTEXT_LINES: usize = 3;
+
+/// Print the changes from `old` to `new` content of the file at `path` as unified diff to stdout, colored if stdout
+/// is a terminal and `NO_COLOR` isn't set.
+pub(in crate::command::release_impl) fn print(path: &str, old: &str, new: &str) {
+    let color = atty::is(atty::Stream::Stdout) && std::env::var_os("NO_COLOR").is_none();
+    print!("{}", unified(path, old, new, color));
+}
+
+/// Return the changes from `old` to `new` content of the file at `path` as unified diff, with ANSI colors if `color`
+/// is set, or an empty string if there are none.
+fn unified(path: &str, old: &str, new: &str, color: bool) -> String {
+    let paint = |code: &str, line: String| {
+        if color {
+            format!("\x1b[{}m{}\x1b[0m\n", code, line)
+        } else {
+            format!("{}\n", line)
+        }
+    };
+    let lines = diff::lines(without_trailing_newline(old), without_trailing_newline(new));
+    let changed: Vec<_> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, diff::Result::Both(..)))
+        .map(|(index, _)| index)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    let mut out = paint("1", format!("--- a/{}", path));
+    out.push_str(&paint("1", format!("+++ b/{}", path)));
+    let mut hunk_start = 0;
+    while hunk_start < changed.len() {
+        let mut hunk_end = hunk_start;
+        while hunk_end + 1 < changed.len() && changed[hunk_end + 1] - changed[hunk_end] <= 2 * CONTEXT_LINES + 1 {
+            hunk_end += 1;
+        }
+        let first = changed[hunk_start].saturating_sub(CONTEXT_LINES);
+        let last = (changed[hunk_end] + CONTEXT_LINES).min(lines.len() - 1);
+        let (mut old_line, mut new_line) = (1, 1);
+        for line in &lines[..first] {
+            match line {
+                diff::Result::Left(_) => old_line += 1,
+                diff::Result::Right(_) => new_line += 1,
+                diff::Result::Both(..) => {
+                    old_line += 1;
+                    new_line += 1;
+                }
+            }
+        }
+        let hunk = &lines[first..=last];
+        let old_len = hunk.iter().filter(|l| !matches!(l, diff::Result::Right(_))).count();
+        let new_len = hunk.iter().filter(|l| !matches!(l, diff::Result::Left(_))).count();
+        out.push_str(&paint(
+            "36",
+            format!("@@ -{},{} +{},{} @@", old_line, old_len, new_line, new_len),
+        ));
+        for line in hunk {
+            out.push_str(&match line {
+                diff::Result::Left(l) => paint("31", format!("-{}", l)),
+                diff::Result::Right(l) => paint("32", format!("+{}", l)),
+                diff::Result::Both(l, _) => format!(" {}\n", l),
+            });
+        }
+        hunk_start = hunk_end + 1;
+    }
+    out
+}
+
+/// Strip the newline at the end of `content`, which would otherwise be diffed as an empty last line.
+fn without_trailing_newline(content: &str) -> &str {
+    content.strip_suffix('\n').unwrap_or(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unified;
+
+    #[test]
+    fn changes_are_shown_with_context() {
+        let old = "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2018\"\n\n[dependencies]\nb = { version = \"0.1.0\", path = \"../b\" }\nc = \"1\"\n";
+        let new = old.replace("0.1.0", "0.2.0");
+        assert_eq!(
+            unified("a/Cargo.toml", old, &new, false),
+            "--- a/a/Cargo.toml\n+++ b/a/Cargo.toml\n@@ -1,8 +1,8 @@\n [package]\n name = \"a\"\n-version = \"0.1.0\"\n+version = \"0.2.0\"\n edition = \"2018\"\n \n [dependencies]\n-b = { version = \"0.1.0\", path = \"../b\" }\n+b = { version = \"0.2.0\", path = \"../b\" }\n c = \"1\"\n"
+        );
+        assert_eq!(unified("a/Cargo.toml", old, old, true), "");
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt::Write;
+
+use cargo_metadata::Package;
+use git_repository::prelude::ReferenceAccessExt;
+use semver::Version;
+
+use super::{manifest, version, Context, Options};
+use crate::{
+    command::changelog_impl::{commit::history::Item, git::ref_segments},
+    utils::{package_eq_dependency, tag_name},
+};
+
+/// Print why each crate in `plan` is released with its new version, and which workspace members would receive a
+/// safety bump because of it, to stdout.
+pub(in crate::command::release_impl) fn print(
+    plan: &[(&Package, String)],
+    ctx: &Context,
+    options: Options,
+) -> anyhow::Result<()> {
+    let history = ctx
+        .history
+        .as_ref()
+        .expect("history is traversed if an explanation is requested");
+    let mut out = String::new();
+    for (publishee, new_version) in plan {
+        writeln!(out, "{} v{} -> v{}", publishee.name, publishee.version, new_version)?;
+        if ctx.base.crate_names.contains(&publishee.name) {
+            writeln!(out, "  released as it was requested")?;
+        } else {
+            writeln!(
+                out,
+                "  released as it's a dependency of the requested crates which changed since its last release"
+            )?;
+        }
+
+        let (given_bump, flag) = if ctx.base.crate_names.contains(&publishee.name) {
+            (ctx.bump.as_str(), "--bump")
+        } else {
+            (ctx.bump_dependencies.as_str(), "--bump-dependencies")
+        };
+        write!(out, "  bumped with '{}' as given by {}", given_bump, flag)?;
+        let bump = match version::raised_bump_spec(publishee, given_bump)? {
+            Some(raised) => {
+                write!(out, ", raised to '{}' by its min-bump configuration", raised)?;
+                raised
+            }
+            None => given_bump,
+        };
+        writeln!(out)?;
+        let bumped = version::apply_bump_spec(&publishee.version, bump)?;
+        if bumped.to_string() != *new_version {
+            writeln!(
+                out,
+                "  using v{} instead of v{} as per the manifest version and the versions published on crates.io",
+                new_version, bumped
+            )?;
+        }
+
+        let tag_name = tag_name(publishee, &publishee.version.to_string(), &ctx.base.repo);
+        let (segments, warnings) = ref_segments(&publishee.name, &ctx.base, history)?;
+        ctx.warnings.borrow_mut().extend(warnings);
+        match segments.first().map(|segment| segment.history.as_slice()) {
+            Some(changes) if !changes.is_empty() => {
+                if ctx.base.repo.try_find_reference(&tag_name)?.is_some() {
+                    writeln!(out, "  changes since {}:", tag_name)?;
+                } else {
+                    writeln!(out, "  changes since its last release, as {} doesn't exist:", tag_name)?;
+                }
+                for item in changes {
+                    writeln!(out, "    {}", describe(item))?;
+                }
+            }
+            _ => writeln!(out, "  no changes since its last release")?,
+        }
+    }
+
+    let safety_bumps: Vec<_> = manifest::collect_directly_dependent_packages(&ctx.base.meta, plan, ctx, options)?
+        .into_iter()
+        .filter_map(|(p, v)| v.map(|v| (p, v)))
+        .collect();
+    for (dependent, new_version) in &safety_bumps {
+        writeln!(
+            out,
+            "{} v{} -> v{} (safety bump)",
+            dependent.name, dependent.version, new_version
+        )?;
+        let breaking_dependencies = breaking_dependencies(dependent, plan, &safety_bumps);
+        if !breaking_dependencies.is_empty() {
+            writeln!(
+                out,
+                "  bumped as its dependencies have breaking changes: {}",
+                breaking_dependencies.join(", ")
+            )?;
+        }
+    }
+    print!("{}", out);
+    Ok(())
+}
+
+/// Return the dependencies of `dependent` which receive a breaking version bump in `plan` or as part of `safety_bumps`,
+/// each at most once even if it's also a dev- or build-dependency.
+fn breaking_dependencies(
+    dependent: &Package,
+    plan: &[(&Package, String)],
+    safety_bumps: &[(&Package, String)],
+) -> Vec<String> {
+    let mut breaking = Vec::new();
+    for (package, new_version) in plan.iter().chain(safety_bumps) {
+        let is_dependency = dependent
+            .dependencies
+            .iter()
+            .any(|dep| package_eq_dependency(package, dep));
+        let is_breaking = match new_version.parse::<Version>() {
+            Ok(new_version) => version::rhs_is_breaking_bump_for_lhs(&package.version, &new_version),
+            Err(_) => false,
+        };
+        if is_dependency && is_breaking {
+            breaking.push(format!("{} v{}", package.name, new_version));
+        }
+    }
+    breaking
+}
+
+/// Describe `item` with its abbreviated id, conventional commit type and breaking marker, and title.
+fn describe(item: &Item) -> String {
+    let id = item.id.to_string();
+    let message = &item.message;
+    format!(
+        "{} {}{}: {}",
+        &id[..7],
+        message.kind.unwrap_or("unconventional"),
+        if message.breaking { "!" } else { "" },
+        if message.title.is_empty() {
+            message
+                .body
+                .as_deref()
+                .unwrap_or_default()
+                .lines()
+                .next()
+                .unwrap_or_default()
+        } else {
+            &message.title
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::breaking_dependencies;
+    use crate::{fixture::Workspace, utils::package_by_name};
+
+    #[test]
+    fn only_dependencies_with_breaking_bumps_are_listed_once() {
+        let ws = Workspace::tri_depth("explain-breaking-dependencies");
+        ws.configure("c", "[dev-dependencies]\nb = { path = \"../b\" }");
+        ws.commit("b is also a dev-dependency");
+        let ctx = ws.context(&["a"]);
+        let (a, b, c) = (
+            package_by_name(&ctx.meta, "a").unwrap(),
+            package_by_name(&ctx.meta, "b").unwrap(),
+            package_by_name(&ctx.meta, "c").unwrap(),
+        );
+
+        let plan = vec![(a, "0.9.0".to_string())];
+        let safety_bumps = vec![(b, "0.9.0".to_string()), (c, "8.1.0".to_string())];
+        assert_eq!(breaking_dependencies(b, &plan, &safety_bumps), vec!["a v0.9.0"]);
+        assert_eq!(
+            breaking_dependencies(c, &plan, &safety_bumps),
+            vec!["b v0.9.0"],
+            "a dependency listed in multiple sections is mentioned only once"
+        );
+        assert!(
+            breaking_dependencies(a, &plan, &safety_bumps).is_empty(),
+            "crates without bumped dependencies have no breaking dependencies"
+        );
+
+        let plan = vec![(a, "0.8.1".to_string())];
+        assert!(
+            breaking_dependencies(b, &plan, &[]).is_empty(),
+            "patch bumps aren't breaking"
+        );
+    }
+}
@@ -1,17 +1,18 @@
-use std::{convert::TryInto, process::Command};
+use std::{collections::BTreeSet, convert::TryInto, process::Command};
 
 use anyhow::bail;
 use cargo_metadata::Package;
 use git_repository::{bstr::ByteSlice, prelude::ReferenceAccessExt, refs, refs::transaction::PreviousValue};
 
 use super::{tag_name, Oid, Options};
-use crate::utils::will;
+use crate::utils::{names_and_versions, will};
 
 pub(in crate::command::release_impl) fn commit_changes(
     message: impl AsRef<str>,
     verbose: bool,
     dry_run: bool,
     empty_commit_possible: bool,
+    sign: bool,
     ctx: &crate::Context,
 ) -> anyhow::Result<Option<Oid<'_>>> {
     // TODO: replace with gitoxide one day
@@ -20,6 +21,12 @@ pub(in crate::command::release_impl) fn commit_changes(
     if empty_commit_possible {
         cmd.arg("--allow-empty");
     }
+    if sign {
+        match crate::git::signing_key()? {
+            Some(key) => cmd.arg(format!("--gpg-sign={}", key)),
+            None => cmd.arg("--gpg-sign"),
+        };
+    }
     if verbose {
         log::info!("{} run {:?}", will(dry_run), cmd);
     }
@@ -28,9 +35,19 @@ pub(in crate::command::release_impl) fn commit_changes(
     }
 
     if !cmd.status()?.success() {
+        if sign {
+            bail!("Failed to create a signed commit of changed manifests. Check the signing key configured in 'user.signingkey' or its default, the committer email.");
+        }
         bail!("Failed to commit changed manifests");
     }
-    Ok(Some(ctx.repo.find_reference("HEAD")?.peel_to_id_in_place()?))
+    head_id(ctx).map(Some)
+}
+
+pub(in crate::command::release_impl) fn head_id(ctx: &crate::Context) -> anyhow::Result<Oid<'_>> {
+    match crate::git::head(ctx)?.into_fully_peeled_id() {
+        Some(id) => Ok(id?),
+        None => bail!("HEAD doesn't point to a commit"),
+    }
 }
 
 pub(in crate::command::release_impl) fn create_version_tag<'repo>(
@@ -42,6 +59,7 @@ pub(in crate::command::release_impl) fn create_version_tag<'repo>(
         verbose,
         dry_run,
         skip_tag,
+        force_tag,
         ..
     }: Options,
 ) -> anyhow::Result<Option<refs::FullName>> {
@@ -55,25 +73,179 @@ pub(in crate::command::release_impl) fn create_version_tag<'repo>(
         }
         Ok(Some(format!("refs/tags/{}", tag_name).try_into()?))
     } else {
-        let tag = ctx
-            .repo
-            .tag(tag_name, commit_id.expect("set in --execute mode"), PreviousValue::Any)?;
+        let tag = ctx.repo.tag(
+            tag_name,
+            commit_id.expect("set in --execute mode"),
+            if force_tag {
+                PreviousValue::Any
+            } else {
+                PreviousValue::MustNotExist
+            },
+        )?;
         log::info!("Created tag {}", tag.name().as_bstr());
         Ok(Some(tag.inner.name))
     }
 }
 
+/// Fail if any of the `publishees` is released with the version in its manifest while that version is tagged already,
+/// which indicates it was bumped by hand after it was released, or released before.
+///
+/// With `--force-tag` this is left to [`assure_tags_do_not_exist()`], and tags in `created_previously` are expected to exist.
+pub(in crate::command::release_impl) fn assure_manifest_versions_are_not_tagged(
+    publishees: &[(&Package, String)],
+    created_previously: impl Fn(&str) -> bool,
+    ctx: &crate::Context,
+    Options { force_tag, .. }: Options,
+) -> anyhow::Result<()> {
+    if force_tag {
+        return Ok(());
+    }
+    let existing_tag_names = existing_tag_names(ctx)?;
+    let tagged: Vec<_> = publishees
+        .iter()
+        .filter(|(p, v)| {
+            let tag_name = tag_name(p, v, &ctx.repo);
+            p.version.to_string() == *v && existing_tag_names.contains(&tag_name) && !created_previously(&tag_name)
+        })
+        .map(|(p, v)| (*p, v.to_owned()))
+        .collect();
+    if tagged.is_empty() {
+        return Ok(());
+    }
+    bail!(
+        "The manifest versions of {} are tagged as {} already, so they might have been released before. Bump them with --bump or --bump-dependencies, or use --force-tag to release them anyway.",
+        names_and_versions(&tagged),
+        tagged
+            .iter()
+            .map(|(p, v)| tag_name(p, v, &ctx.repo))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn existing_tag_names(ctx: &crate::Context) -> anyhow::Result<BTreeSet<String>> {
+    Ok(ctx
+        .repo
+        .references()?
+        .prefixed("refs/tags")?
+        .filter_map(|r| r.ok().map(|r| r.detach()))
+        .filter_map(|r| {
+            r.name
+                .as_bstr()
+                .strip_prefix(b"refs/tags/")
+                .and_then(|n| n.to_str().ok())
+                .map(ToOwned::to_owned)
+        })
+        .collect())
+}
+
+/// Fail if the tags for the new versions of `publishees` exist already, unless they are to be moved with `--force-tag`.
+///
+/// Tags in `created_previously` were created by a previous run of the same release and are expected to exist, and kept
+/// versions aren't checked unless they are tagged.
+pub(in crate::command::release_impl) fn assure_tags_do_not_exist(
+    publishees: &[(&Package, String)],
+    created_previously: impl Fn(&str) -> bool,
+    ctx: &crate::Context,
+    Options {
+        dry_run,
+        force_tag,
+        tag_kept_versions,
+        ..
+    }: Options,
+) -> anyhow::Result<()> {
+    let existing_tag_names = existing_tag_names(ctx)?;
+    let existing: Vec<_> = publishees
+        .iter()
+        .filter(|(p, v)| tag_kept_versions || p.version.to_string() != *v)
+        .filter(|(p, v)| {
+            let tag_name = tag_name(p, v, &ctx.repo);
+            existing_tag_names.contains(&tag_name) && !created_previously(&tag_name)
+        })
+        .map(|(p, v)| (*p, v.to_owned()))
+        .collect();
+    if existing.is_empty() {
+        return Ok(());
+    }
+    let tag_names = existing
+        .iter()
+        .map(|(p, v)| tag_name(p, v, &ctx.repo))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if force_tag {
+        log::warn!(
+            "{} move existing tag(s) {} to the release commit of {}",
+            will(dry_run),
+            tag_names,
+            names_and_versions(&existing)
+        );
+        Ok(())
+    } else {
+        bail!(
+            "Tag(s) {} of the new versions of {} exist already. Use --force-tag to move them to the new release commit or delete them.",
+            tag_names,
+            names_and_versions(&existing)
+        )
+    }
+}
+
 // TODO: Make this gitoxide
-pub fn push_tags_and_head(tag_names: impl IntoIterator<Item = refs::FullName>, options: Options) -> anyhow::Result<()> {
+/// Push `HEAD` to the upstream of the current branch unless `skip_push_commits` is set, and then `tag_names` to the
+/// same remote unless `skip_push_tags` is set, as separate invocations so the release commit can be pushed even if the
+/// tags are rejected.
+///
+/// With `atomic_push`, all of them are pushed in a single atomic invocation instead, so that either all or none of them
+/// are updated on the remote.
+pub fn push_tags_and_head(
+    tag_names: impl IntoIterator<Item = refs::FullName>,
+    ctx: &crate::Context,
+    options: Options,
+) -> anyhow::Result<()> {
     if options.skip_push {
         return Ok(());
     }
 
+    let target = crate::git::push_target(ctx)?;
+    let head = if options.skip_push_commits {
+        None
+    } else {
+        match &target.branch {
+            Some(branch) => Some(format!("HEAD:{}", branch)),
+            None => bail!("Cannot determine the branch to push to as HEAD isn't on a branch - use --no-push-commits to only push tags"),
+        }
+    };
+    let tag_names = if options.skip_push_tags {
+        Vec::new()
+    } else {
+        tag_names
+            .into_iter()
+            .map(|tag_name| tag_name.as_bstr().to_str().map(ToOwned::to_owned))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    if options.atomic_push {
+        let refspecs: Vec<_> = head.into_iter().chain(tag_names).collect();
+        if !refspecs.is_empty() {
+            push(&target.remote, refspecs, options)?;
+        }
+        return Ok(());
+    }
+    if let Some(head) = head {
+        push(&target.remote, Some(head), options)?;
+    }
+    if !tag_names.is_empty() {
+        push(&target.remote, tag_names, options)?;
+    }
+    Ok(())
+}
+
+fn push(remote: &str, refspecs: impl IntoIterator<Item = String>, options: Options) -> anyhow::Result<()> {
     let mut cmd = Command::new("git");
-    cmd.arg("push").arg("origin").arg("HEAD");
-    for tag_name in tag_names {
-        cmd.arg(tag_name.as_bstr().to_str()?);
+    cmd.arg("push");
+    if options.atomic_push {
+        cmd.arg("--atomic");
     }
+    cmd.arg(remote).args(refspecs);
 
     if options.verbose {
         log::info!("{} run {:?}", will(options.dry_run), cmd);
@@ -84,3 +256,100 @@ pub fn push_tags_and_head(tag_names: impl IntoIterator<Item = refs::FullName>, o
         bail!("'git push' invocation failed. Try to push manually and repeat the smart-release invocation to resume, possibly with --skip-push.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{assure_manifest_versions_are_not_tagged, assure_tags_do_not_exist, push_tags_and_head};
+    use crate::{command::release::Options, fixture::Workspace, utils::package_by_name};
+
+    #[test]
+    fn existing_tags_of_kept_versions_only_matter_if_kept_versions_are_tagged() {
+        let ws = Workspace::tri_depth("kept-versions-tagged");
+        ws.git(&["tag", "a-v0.8.0"]);
+        ws.git(&["tag", "b-v0.9.0"]);
+        let ctx = ws.context(&["a"]);
+        let a = package_by_name(&ctx.meta, "a").unwrap();
+        let b = package_by_name(&ctx.meta, "b").unwrap();
+        let tag_kept_versions = Options {
+            tag_kept_versions: true,
+            ..Default::default()
+        };
+
+        let kept = [(a, "0.8.0".to_string())];
+        assert!(
+            assure_tags_do_not_exist(&kept, |_| false, &ctx, Options::default()).is_ok(),
+            "kept versions aren't tagged"
+        );
+        assert_eq!(
+            assure_tags_do_not_exist(&kept, |_| false, &ctx, tag_kept_versions)
+                .unwrap_err()
+                .to_string(),
+            "Tag(s) a-v0.8.0 of the new versions of a v0.8.0 exist already. Use --force-tag to move them to the new release commit or delete them."
+        );
+
+        let bumped = [(b, "0.9.0".to_string())];
+        assert!(
+            assure_tags_do_not_exist(&bumped, |_| false, &ctx, Options::default()).is_err(),
+            "new versions are always tagged"
+        );
+    }
+
+    #[test]
+    fn tagged_manifest_versions_are_refused_unless_tags_are_forced() {
+        let ws = Workspace::tri_depth("manifest-versions-tagged");
+        ws.git(&["tag", "a-v0.8.0"]);
+        let ctx = ws.context(&["a"]);
+        let a = package_by_name(&ctx.meta, "a").unwrap();
+        let b = package_by_name(&ctx.meta, "b").unwrap();
+        let assure = |plan: &[(&cargo_metadata::Package, String)], created_previously: &str, options| {
+            assure_manifest_versions_are_not_tagged(plan, |tag_name| tag_name == created_previously, &ctx, options)
+        };
+
+        let plan = [(a, "0.8.0".to_string()), (b, "0.8.0".to_string())];
+        assert_eq!(
+            assure(&plan, "", Options::default()).unwrap_err().to_string(),
+            "The manifest versions of a v0.8.0 are tagged as a-v0.8.0 already, so they might have been released before. Bump them with --bump or --bump-dependencies, or use --force-tag to release them anyway."
+        );
+        assert!(
+            assure(&plan, "a-v0.8.0", Options::default()).is_ok(),
+            "tags created by a previous run of the same release are fine"
+        );
+        assert!(assure(
+            &plan,
+            "",
+            Options {
+                force_tag: true,
+                ..Default::default()
+            }
+        )
+        .is_ok());
+        assert!(
+            assure(&[(a, "0.9.0".into())], "", Options::default()).is_ok(),
+            "only manifest versions that are kept are checked"
+        );
+    }
+
+    #[test]
+    fn only_tags_can_be_pushed_from_a_detached_head() {
+        let ws = Workspace::tri_depth("push-detached-head");
+        ws.git(&["checkout", "-q", "--detach"]);
+        let ctx = ws.context(&["a"]);
+        let push = |skip_push_commits| {
+            push_tags_and_head(
+                None,
+                &ctx,
+                Options {
+                    dry_run: true,
+                    skip_push_commits,
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert!(push(true).is_ok());
+        assert!(push(false)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Cannot determine the branch to push to as HEAD isn't on a branch"));
+    }
+}
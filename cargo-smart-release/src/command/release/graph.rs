@@ -0,0 +1,145 @@
+use std::fmt::Write;
+
+use cargo_metadata::{DependencyKind, Package};
+use semver::Version;
+
+use super::{manifest, version, Context, Options};
+use crate::{command::release::GraphFormat, utils::package_eq_dependency};
+
+/// How a crate became part of the release.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Role {
+    /// It was requested to be released.
+    Requested,
+    /// It's released as it's a changed dependency of a requested crate.
+    Dependency,
+    /// Its version is bumped as one of its dependencies has a breaking change.
+    SafetyBump,
+    /// Only the requirements of its dependencies are updated, without changing its version.
+    Dependent,
+}
+
+struct Node<'a> {
+    package: &'a Package,
+    new_version: Option<&'a str>,
+    role: Role,
+}
+
+impl Node<'_> {
+    /// Return true if the new version of this crate is a breaking change for its dependents.
+    fn is_breaking(&self) -> bool {
+        match self.new_version.and_then(|v| v.parse::<Version>().ok()) {
+            Some(new_version) => version::rhs_is_breaking_bump_for_lhs(&self.package.version, &new_version),
+            None => false,
+        }
+    }
+
+    fn versions(&self) -> String {
+        match self.new_version {
+            Some(new_version) => format!("v{} -> v{}", self.package.version, new_version),
+            None => format!("v{}", self.package.version),
+        }
+    }
+
+    fn role(&self) -> &'static str {
+        match self.role {
+            Role::Requested => "requested",
+            Role::Dependency => "changed dependency",
+            Role::SafetyBump => "safety bump",
+            Role::Dependent => "updated dependency requirements",
+        }
+    }
+}
+
+/// Print the crates in `plan` along with the workspace members whose manifests change because of them to stdout in the
+/// given `format`, with an edge from each of them to each of its dependencies among them.
+///
+/// Edges to dependencies with a breaking bump are marked, as these cause safety bumps of their dependents.
+pub(in crate::command::release_impl) fn print(
+    plan: &[(&Package, String)],
+    ctx: &Context,
+    options: Options,
+    format: GraphFormat,
+) -> anyhow::Result<()> {
+    let dependents = manifest::collect_directly_dependent_packages(&ctx.base.meta, plan, ctx, options)?;
+    let nodes: Vec<_> = plan
+        .iter()
+        .map(|(package, new_version)| Node {
+            package,
+            new_version: Some(new_version.as_str()),
+            role: if ctx.base.crate_names.contains(&package.name) {
+                Role::Requested
+            } else {
+                Role::Dependency
+            },
+        })
+        .chain(dependents.iter().map(|(package, new_version)| Node {
+            package,
+            new_version: new_version.as_deref(),
+            role: if new_version.is_some() {
+                Role::SafetyBump
+            } else {
+                Role::Dependent
+            },
+        }))
+        .collect();
+
+    let mut out = String::new();
+    if format == GraphFormat::Dot {
+        writeln!(out, "digraph release {{")?;
+    }
+    for node in &nodes {
+        match format {
+            GraphFormat::Text => writeln!(out, "{} {} ({})", node.package.name, node.versions(), node.role())?,
+            GraphFormat::Dot => writeln!(
+                out,
+                "  {:?} [label=\"{}\\n{}\", style={}];",
+                node.package.name,
+                node.package.name,
+                node.versions(),
+                match node.role {
+                    Role::Requested => "bold",
+                    Role::Dependency => "solid",
+                    Role::SafetyBump => "dashed",
+                    Role::Dependent => "dotted",
+                }
+            )?,
+        }
+        for dep in &node.package.dependencies {
+            let dependency = match nodes.iter().find(|n| package_eq_dependency(n.package, dep)) {
+                Some(dependency) => dependency,
+                None => continue,
+            };
+            let is_dev = dep.kind == DependencyKind::Development;
+            match format {
+                GraphFormat::Text => writeln!(
+                    out,
+                    "  {} -> {}{}{}",
+                    node.package.name,
+                    dependency.package.name,
+                    if is_dev { " (dev)" } else { "" },
+                    if dependency.is_breaking() { " (breaking)" } else { "" }
+                )?,
+                GraphFormat::Dot => {
+                    let mut attributes = Vec::new();
+                    if is_dev {
+                        attributes.push("style=dotted");
+                    }
+                    if dependency.is_breaking() {
+                        attributes.push("color=red");
+                    }
+                    write!(out, "  {:?} -> {:?}", node.package.name, dependency.package.name)?;
+                    if !attributes.is_empty() {
+                        write!(out, " [{}]", attributes.join(", "))?;
+                    }
+                    writeln!(out, ";")?;
+                }
+            }
+        }
+    }
+    if format == GraphFormat::Dot {
+        writeln!(out, "}}")?;
+    }
+    print!("{}", out);
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::{
+    io::{self, Write},
+    process::{Command, Output, Stdio},
+};
+
+use anyhow::Context;
+
+/// Launch `cmd` as the hook called `name`, write `input` as JSON to its stdin and wait for it to exit.
+///
+/// Hooks may exit without reading their input, which isn't an error as their exit status tells whether they succeeded.
+/// The output only contains what was printed to stdout if it was configured to be piped.
+pub(in crate::command::release_impl) fn run(
+    name: &str,
+    cmd: &mut Command,
+    input: &serde_json::Value,
+) -> anyhow::Result<Output> {
+    let input = serde_json::to_vec(input)?;
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Could not launch {} '{}'", name, cmd.get_program().to_string_lossy()))?;
+    let mut stdin = child.stdin.take().expect("configured");
+    // Write from another thread as hooks may print to a piped stdout while still reading, which would block both of us
+    // once the pipe buffers are full.
+    let writer = std::thread::spawn(move || match stdin.write_all(&input) {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => Err(err),
+        _ => Ok(()),
+    });
+    let output = child.wait_with_output()?;
+    writer
+        .join()
+        .expect("writing to stdin doesn't panic")
+        .with_context(|| format!("Could not pass input to {}", name))?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::{Command, Stdio};
+
+    use super::run;
+
+    fn sh(script: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", script]);
+        cmd
+    }
+
+    fn large_input() -> serde_json::Value {
+        serde_json::json!({ "crates": vec!["a"; 100_000] })
+    }
+
+    #[test]
+    fn hooks_may_exit_without_reading_their_input() {
+        let output = run("hook", &mut sh("exit 0"), &large_input()).unwrap();
+        assert!(output.status.success(), "a closed stdin isn't an error");
+
+        let output = run("hook", &mut sh("exit 3"), &large_input()).unwrap();
+        assert_eq!(output.status.code(), Some(3), "the exit status decides");
+    }
+
+    #[test]
+    fn input_is_passed_as_json_and_stdout_can_be_captured() {
+        let input = serde_json::json!({ "dry_run": true });
+        let output = run("hook", sh("cat").stdout(Stdio::piped()), &input).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, br#"{"dry_run":true}"#);
+    }
+
+    #[test]
+    fn hooks_may_print_while_reading_large_inputs() {
+        let input = large_input();
+        let output = run("hook", sh("cat").stdout(Stdio::piped()), &input).unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            output.stdout,
+            serde_json::to_vec(&input).unwrap(),
+            "no deadlock on full pipes"
+        );
+    }
+
+    #[test]
+    fn missing_programs_fail_to_launch() {
+        let err = run(
+            "policy hook",
+            &mut Command::new("does-not-exist-anywhere"),
+            &large_input(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Could not launch policy hook 'does-not-exist-anywhere'"
+        );
+    }
+}
@@ -1,10 +1,18 @@
-use std::{borrow::Cow, collections::BTreeMap, str::FromStr};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    str::FromStr,
+};
 
-use anyhow::bail;
-use cargo_metadata::{camino::Utf8PathBuf, Metadata, Package};
+use anyhow::{bail, Context as _};
+use cargo_metadata::{
+    camino::{Utf8Path, Utf8PathBuf},
+    Metadata, Package,
+};
 use semver::{Op, Version, VersionReq};
 
-use super::{cargo, git, version, Context, Oid, Options};
+use super::{bump_files, cargo, diff, git, version, Context, Oid, Options};
 use crate::utils::{names_and_versions, package_by_id, package_eq_dependency, will};
 
 pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates<'repo>(
@@ -18,6 +26,7 @@ pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates<
         verbose,
         dry_run,
         skip_publish,
+        commit_sign,
         ..
     } = opts;
     for (publishee, _) in publishees {
@@ -30,8 +39,15 @@ pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates<
         assert!(previous.is_none(), "publishees are unique so insertion always happens");
     }
 
-    let mut dependent_packages =
-        collect_directly_dependent_packages(meta, publishees, &mut locks_by_manifest_path, ctx, opts)?;
+    let mut dependent_packages = collect_directly_dependent_packages(meta, publishees, ctx, opts)?;
+    for (dependent, _) in &dependent_packages {
+        let lock = git_repository::lock::File::acquire_to_update_resource(
+            &dependent.manifest_path,
+            git_repository::lock::acquire::Fail::Immediately,
+            None,
+        )?;
+        locks_by_manifest_path.insert(&dependent.manifest_path, lock);
+    }
     let publishees_and_bumped_dependent_packages = publishees
         .iter()
         .map(|(p, v)| (*p, v.to_owned()))
@@ -42,6 +58,18 @@ pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates<
                 .filter_map(|(p, v)| v.map(|v| (p, v))),
         )
         .collect::<Vec<_>>();
+    let workspace_manifest_path = meta.workspace_root.join("Cargo.toml");
+    let workspace_version = inherited_workspace_version(meta, &publishees_and_bumped_dependent_packages)?;
+    let workspace_version_for = |package: &Package| {
+        workspace_version
+            .as_deref()
+            .filter(|_| package.manifest_path == workspace_manifest_path)
+    };
+    let dependency_updates: Vec<_> = publishees_and_bumped_dependent_packages
+        .iter()
+        .filter(|(p, v)| opts.update_dependents_of_kept_versions || p.version.to_string() != *v)
+        .cloned()
+        .collect();
     let mut made_change = false;
     for (publishee, new_version) in publishees {
         let mut lock = locks_by_manifest_path
@@ -50,8 +78,10 @@ pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates<
         made_change |= set_version_and_update_package_dependency(
             publishee,
             Some(&new_version.to_string()),
-            &publishees_and_bumped_dependent_packages,
+            workspace_version_for(publishee),
+            &dependency_updates,
             &mut lock,
+            &meta.workspace_root,
             opts,
         )?;
     }
@@ -63,54 +93,115 @@ pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates<
         made_change |= set_version_and_update_package_dependency(
             dependant_on_publishee,
             possibly_new_version.as_deref(),
-            &publishees_and_bumped_dependent_packages,
+            workspace_version_for(dependant_on_publishee),
+            &dependency_updates,
             &mut lock,
+            &meta.workspace_root,
             opts,
         )?;
     }
 
+    let mut workspace_manifest_lock = None;
+    if let Some(new_version) = workspace_version
+        .as_deref()
+        .filter(|_| !locks_by_manifest_path.contains_key(&workspace_manifest_path))
+    {
+        let mut lock = git_repository::lock::File::acquire_to_update_resource(
+            &workspace_manifest_path,
+            git_repository::lock::acquire::Fail::Immediately,
+            None,
+        )?;
+        let manifest = std::fs::read_to_string(&workspace_manifest_path)?;
+        let mut doc = parse_manifest(&manifest, &workspace_manifest_path)?;
+        set_workspace_package_version(&mut doc, new_version, &workspace_manifest_path, verbose)?;
+        let new_manifest = manifest_to_string(&doc, &manifest);
+        lock.write_all(new_manifest.as_bytes())?;
+        if dry_run && opts.color_diff {
+            diff::print("Cargo.toml", &manifest, &new_manifest);
+        }
+        made_change |= manifest != new_manifest;
+        workspace_manifest_lock = Some(lock);
+    }
+
+    let mut new_content_by_path = BTreeMap::new();
+    for (package, new_version) in &publishees_and_bumped_dependent_packages {
+        bump_files::update(package, new_version, ctx, &mut new_content_by_path, verbose, dry_run)?;
+    }
+    made_change |= !new_content_by_path.is_empty();
+    let mut bump_file_locks = Vec::new();
+    if !dry_run {
+        for (path, new_content) in new_content_by_path {
+            let mut lock = git_repository::lock::File::acquire_to_update_resource(
+                &path,
+                git_repository::lock::acquire::Fail::Immediately,
+                None,
+            )?;
+            lock.write_all(new_content.as_bytes())?;
+            bump_file_locks.push(lock);
+        }
+    }
+
     let message = format!(
-        "{} {}{}",
+        "{} {}{}\n\n{}: true",
         if skip_publish { "Bump" } else { "Release" },
         names_and_versions(publishees),
-        {
-            let safety_bumped_packages = dependent_packages
+        safety_bumps_message(
+            &dependent_packages
                 .into_iter()
                 .filter_map(|(p, v)| v.map(|v| (p, v)))
-                .collect::<Vec<_>>();
-            if safety_bumped_packages.is_empty() {
-                Cow::from("")
-            } else {
-                match safety_bumped_packages.len() {
-                    1 => format!(", safety bump {}", names_and_versions(&safety_bumped_packages)).into(),
-                    num_crates => format!(
-                        ", safety bump {} crates\n\nSAFETY BUMP: {}",
-                        num_crates,
-                        names_and_versions(&safety_bumped_packages)
-                    )
-                    .into(),
-                }
-            }
-        }
+                .collect::<Vec<_>>(),
+            ctx.base.config.max_listed_safety_bumps,
+        ),
+        crate::command::changelog_impl::commit::RELEASE_TRAILER_TOKEN
     );
     if verbose {
         log::info!("{} persist changes to manifests with: {:?}", will(dry_run), message);
     }
     if !dry_run {
-        for manifest_lock in locks_by_manifest_path.into_values() {
-            manifest_lock.commit()?;
+        for lock in locks_by_manifest_path
+            .into_values()
+            .chain(workspace_manifest_lock)
+            .chain(bump_file_locks)
+        {
+            lock.commit()?;
         }
         // This is dangerous as incompatibilities can happen here, leaving the working tree dirty.
         // For now we leave it that way without auto-restoring originals to facilitate debugging.
         cargo::refresh_lock_file()?;
     }
-    git::commit_changes(message, verbose, dry_run, !made_change, &ctx.base)
+    git::commit_changes(message, verbose, dry_run, !made_change, commit_sign, &ctx.base)
+}
+
+/// Return the part of a release commit message announcing the `safety_bumped` crates, listing no more than `max_listed`
+/// of them in its body if set.
+fn safety_bumps_message(safety_bumped: &[(&Package, String)], max_listed: Option<usize>) -> Cow<'static, str> {
+    match safety_bumped.len() {
+        0 => Cow::from(""),
+        1 => format!(", safety bump {}", names_and_versions(safety_bumped)).into(),
+        num_crates => {
+            let listed = max_listed.unwrap_or(num_crates).min(num_crates);
+            if listed == 0 {
+                return format!(", safety bump {} crates", num_crates).into();
+            }
+            let mut body = names_and_versions(&safety_bumped[..listed]);
+            if listed < num_crates {
+                let num_unlisted = num_crates - listed;
+                body.push_str(&format!(
+                    " ...and {} more dependency update{}",
+                    num_unlisted,
+                    if num_unlisted == 1 { "" } else { "s" }
+                ));
+            }
+            format!(", safety bump {} crates\n\nSAFETY BUMP: {}", num_crates, body).into()
+        }
+    }
 }
 
-fn collect_directly_dependent_packages<'a>(
+/// Return all workspace members depending on `publishees`, directly or through other dependents, whose manifests
+/// need an update, along with their new version if they need a safety bump.
+pub(in crate::command::release_impl) fn collect_directly_dependent_packages<'a>(
     meta: &'a Metadata,
     publishees: &[(&Package, String)],
-    locks_by_manifest_path: &mut BTreeMap<&'a Utf8PathBuf, git_repository::lock::File>,
     ctx: &Context,
     Options {
         isolate_dependencies_from_breaking_changes,
@@ -126,6 +217,7 @@ fn collect_directly_dependent_packages<'a>(
         .map(|(p, v)| (*p, Some(v.to_owned())))
         .collect::<Vec<_>>();
     let mut publishees_and_dependents = publishees_backing.as_slice();
+    let mut seen_manifest_paths: BTreeSet<_> = publishees.iter().map(|(p, _)| &p.manifest_path).collect();
 
     loop {
         for workspace_package in meta.workspace_members.iter().map(|id| package_by_id(meta, id)) {
@@ -135,17 +227,9 @@ fn collect_directly_dependent_packages<'a>(
                         .iter()
                         .any(|(publishee, _)| package_eq_dependency(publishee, dep))
                 });
-                if !has_publishee_in_dependencies
-                    || locks_by_manifest_path.contains_key(&workspace_package.manifest_path)
-                {
+                if !has_publishee_in_dependencies || !seen_manifest_paths.insert(&workspace_package.manifest_path) {
                     continue;
                 }
-                let lock = git_repository::lock::File::acquire_to_update_resource(
-                    &workspace_package.manifest_path,
-                    git_repository::lock::acquire::Fail::Immediately,
-                    None,
-                )?;
-                locks_by_manifest_path.insert(&workspace_package.manifest_path, lock);
                 dependent_packages_this_round.push((workspace_package, None));
             } else {
                 let mut desired_versions = Vec::<Version>::new();
@@ -164,7 +248,7 @@ fn collect_directly_dependent_packages<'a>(
                             ctx,
                             bump_when_needed,
                             verbose,
-                        ) {
+                        )? {
                             desired_versions.push(version)
                         }
                     }
@@ -172,13 +256,9 @@ fn collect_directly_dependent_packages<'a>(
                 if desired_versions.is_empty() {
                     continue;
                 }
-                desired_versions.sort();
+                let new_version = safety_bump_version(&workspace_package.version, &desired_versions);
 
-                let greatest_version = desired_versions.pop().expect("at least one version");
-                let new_version = version::rhs_is_breaking_bump_for_lhs(&workspace_package.version, &greatest_version)
-                    .then(|| greatest_version.to_string());
-
-                if locks_by_manifest_path.contains_key(&workspace_package.manifest_path) {
+                if seen_manifest_paths.contains(&workspace_package.manifest_path) {
                     if let Some(previous_version) = packages_to_fix
                         .iter()
                         .find_map(|(p, v)| (p.id == workspace_package.id && *v < new_version).then(|| v))
@@ -193,12 +273,7 @@ fn collect_directly_dependent_packages<'a>(
                     continue;
                 }
                 if new_version.is_some() || is_direct_dependency_of(publishees, workspace_package) {
-                    let lock = git_repository::lock::File::acquire_to_update_resource(
-                        &workspace_package.manifest_path,
-                        git_repository::lock::acquire::Fail::Immediately,
-                        None,
-                    )?;
-                    locks_by_manifest_path.insert(&workspace_package.manifest_path, lock);
+                    seen_manifest_paths.insert(&workspace_package.manifest_path);
                     dependent_packages_this_round.push((workspace_package, new_version));
                 }
             };
@@ -216,6 +291,17 @@ fn collect_directly_dependent_packages<'a>(
     Ok(packages_to_fix)
 }
 
+/// Return the greatest of the `desired_versions` of a dependent at `current_version` if it's greater than that.
+///
+/// This includes bumps which aren't breaking, like `0.3.1` to `0.3.2` with `"pre-1.0-breaking" = "patch"`.
+fn safety_bump_version(current_version: &Version, desired_versions: &[Version]) -> Option<String> {
+    desired_versions
+        .iter()
+        .max()
+        .filter(|greatest_version| *greatest_version > current_version)
+        .map(ToString::to_string)
+}
+
 fn is_direct_dependency_of(publishees: &[(&Package, String)], package_to_fix: &Package) -> bool {
     package_to_fix.dependencies.iter().any(|dep| {
         publishees
@@ -224,22 +310,157 @@ fn is_direct_dependency_of(publishees: &[(&Package, String)], package_to_fix: &P
     })
 }
 
+/// Return the version to set in `[workspace.package]` if one of the `packages` to change inherits its version from the
+/// workspace, or fail if they would need different versions.
+///
+/// All other workspace members inheriting the version will change their version as well.
+fn inherited_workspace_version(meta: &Metadata, packages: &[(&Package, String)]) -> anyhow::Result<Option<String>> {
+    let mut inheriting = Vec::new();
+    for (package, new_version) in packages {
+        if inherits_version(&parse_manifest(
+            &std::fs::read_to_string(&package.manifest_path)?,
+            &package.manifest_path,
+        )?) {
+            inheriting.push((*package, new_version.as_str()));
+        }
+    }
+    let new_version = match inheriting.first() {
+        Some((_, new_version)) => *new_version,
+        None => return Ok(None),
+    };
+    if inheriting.iter().any(|(_, v)| *v != new_version) {
+        bail!(
+            "Crates inheriting their version from the workspace must be released with the same version, but would be released as {}",
+            inheriting
+                .iter()
+                .map(|(p, v)| format!("{} v{}", p.name, v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    for package in meta.workspace_members.iter().map(|id| package_by_id(meta, id)) {
+        if inheriting.iter().any(|(p, _)| p.id == package.id) {
+            continue;
+        }
+        if inherits_version(&parse_manifest(
+            &std::fs::read_to_string(&package.manifest_path)?,
+            &package.manifest_path,
+        )?) {
+            log::warn!(
+                "'{}' inherits its version from the workspace and will change from {} to {} without being released",
+                package.name,
+                package.version,
+                new_version
+            );
+        }
+    }
+    Ok(Some(new_version.to_owned()))
+}
+
+/// Return true if the manifest in `doc` inherits its package version from the workspace.
+fn inherits_version(doc: &toml_edit::Document) -> bool {
+    doc["package"]["version"]["workspace"].as_bool() == Some(true)
+}
+
+/// Parse `manifest` for editing, with dotted keys like `version.workspace = true`, which can't be parsed otherwise, turned
+/// into inline tables like `version = { workspace = true }`.
+///
+/// Use [`manifest_to_string()`] to turn them back into dotted keys.
+fn parse_manifest(manifest: &str, path: &Utf8PathBuf) -> anyhow::Result<toml_edit::Document> {
+    let manifest: String = manifest
+        .split_inclusive('\n')
+        .map(
+            |line| match inline_workspace_key(line.trim_end_matches(&['\r', '\n'][..])) {
+                Some(inline) => format!("{}{}", inline, &line[line.trim_end_matches(&['\r', '\n'][..]).len()..]),
+                None => line.to_owned(),
+            },
+        )
+        .collect();
+    toml_edit::Document::from_str(&manifest).with_context(|| format!("Could not parse manifest at '{}'", path))
+}
+
+/// Render `doc` as parsed from `original` by [`parse_manifest()`], with the dotted keys of `original` restored unless
+/// they were changed.
+fn manifest_to_string(doc: &toml_edit::Document, original: &str) -> String {
+    let dotted_by_inline: BTreeMap<_, _> = original
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter_map(|line| inline_workspace_key(line).map(|inline| (inline, line)))
+        .collect();
+    doc.to_string_in_original_order()
+        .split_inclusive('\n')
+        .map(|line| {
+            let content = line.trim_end_matches(&['\r', '\n'][..]);
+            match dotted_by_inline.get(content) {
+                Some(dotted) => format!("{}{}", dotted, &line[content.len()..]),
+                None => line.to_owned(),
+            }
+        })
+        .collect()
+}
+
+/// Return `line` with a dotted key like `version.workspace = true` as inline table like `version = { workspace = true }`,
+/// or `None` if it doesn't have such a key.
+fn inline_workspace_key(line: &str) -> Option<String> {
+    let (key, value) = line.split_once('=')?;
+    let name = key.trim().strip_suffix(".workspace")?.trim_end();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return None;
+    }
+    let rest = value.trim_start().strip_prefix("true")?;
+    if !rest.trim().is_empty() && !rest.trim_start().starts_with('#') {
+        return None;
+    }
+    let indentation = &line[..line.len() - line.trim_start().len()];
+    Some(format!("{}{} = {{ workspace = true }}{}", indentation, name, rest))
+}
+
+fn set_workspace_package_version(
+    doc: &mut toml_edit::Document,
+    new_version: &str,
+    path: &Utf8PathBuf,
+    verbose: bool,
+) -> anyhow::Result<()> {
+    let version = &mut doc["workspace"]["package"]["version"];
+    match version.as_str() {
+        None => bail!(
+            "The workspace manifest at '{}' doesn't set the version to inherit in [workspace.package]",
+            path
+        ),
+        Some(version) if version == new_version => return Ok(()),
+        Some(_) => {}
+    }
+    *version = toml_edit::value(new_version);
+    if verbose {
+        log::info!("Pending workspace manifest version update: \"{}\"", new_version);
+    }
+    Ok(())
+}
+
 fn set_version_and_update_package_dependency(
     package_to_update: &Package,
     new_package_version: Option<&str>,
+    new_workspace_version: Option<&str>,
     publishees: &[(&Package, String)],
     mut out: impl std::io::Write,
+    workspace_root: &Utf8Path,
     Options {
         verbose,
+        dry_run,
         conservative_pre_release_version_handling,
+        color_diff,
         ..
     }: Options,
 ) -> anyhow::Result<bool> {
     let manifest = std::fs::read_to_string(&package_to_update.manifest_path)?;
-    let mut doc = toml_edit::Document::from_str(&manifest)?;
+    let mut doc = parse_manifest(&manifest, &package_to_update.manifest_path)?;
 
+    if let Some(new_version) = new_workspace_version {
+        set_workspace_package_version(&mut doc, new_version, &package_to_update.manifest_path, verbose)?;
+    }
     if let Some(new_version) = new_package_version {
-        if doc["package"]["version"].as_str() != Some(new_version) {
+        // Inherited versions are updated in the workspace manifest instead.
+        if !inherits_version(&doc) && doc["package"]["version"].as_str() != Some(new_version) {
             doc["package"]["version"] = toml_edit::value(new_version);
             if verbose {
                 log::info!(
@@ -279,15 +500,17 @@ fn set_version_and_update_package_dependency(
                             bail!("{} has it's {} dependency set to a version requirement with comparator {} - cannot currently handle that.", package_to_update.name, name_to_find, current_version_req);
                         }
                         let new_version = format!("^{}", new_version);
-                        if verbose && version_req.to_string() != new_version {
+                        // Always show these in dry-run mode as requirement changes in dependents are the most surprising.
+                        if (verbose || dry_run) && version_req.to_string() != new_version {
                             log::info!(
-                                "Pending '{}' {}manifest {} update: '{} = \"{}\"' (from {})",
+                                "Pending '{}' {}manifest {} update: '{} = \"{}\"' (from '{} = \"{}\"')",
                                 package_to_update.name,
                                 if force_update { "conservative " } else { "" },
                                 dep_type,
                                 name_to_find,
                                 new_version,
-                                current_version_req.to_string()
+                                name_to_find,
+                                current_version_req.as_str().expect("versions are strings")
                             );
                         }
                         *current_version_req = toml_edit::Value::from(new_version.as_str());
@@ -296,8 +519,16 @@ fn set_version_and_update_package_dependency(
             }
         }
     }
-    let new_manifest = doc.to_string_in_original_order();
+    let new_manifest = manifest_to_string(&doc, &manifest);
     out.write_all(new_manifest.as_bytes())?;
+    if dry_run && color_diff {
+        let path = &package_to_update.manifest_path;
+        diff::print(
+            path.strip_prefix(workspace_root).unwrap_or(path).as_str(),
+            &manifest,
+            &new_manifest,
+        );
+    }
 
     Ok(manifest != new_manifest)
 }
@@ -312,3 +543,105 @@ fn req_as_version(req: &VersionReq) -> Version {
         build: Default::default(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use super::{inherits_version, manifest_to_string, parse_manifest, safety_bump_version, safety_bumps_message};
+    use crate::{fixture::Workspace, utils::package_by_name};
+
+    #[test]
+    fn safety_bumps_use_the_greatest_desired_version_if_it_is_greater() {
+        let v = |v: &str| Version::parse(v).unwrap();
+        assert_eq!(
+            safety_bump_version(&v("0.3.1"), &[v("0.3.2")]),
+            Some("0.3.2".into()),
+            "a patch bump is a safety bump with pre-1.0-breaking = \"patch\""
+        );
+        assert_eq!(
+            safety_bump_version(&v("0.3.1"), &[v("0.4.0"), v("0.3.2")]),
+            Some("0.4.0".into())
+        );
+        assert_eq!(
+            safety_bump_version(&v("1.2.0"), &[v("1.2.0")]),
+            None,
+            "versions which aren't greater don't need a bump"
+        );
+        assert_eq!(safety_bump_version(&v("1.2.0"), &[v("1.1.0")]), None);
+    }
+
+    #[test]
+    fn inherited_versions_in_both_spellings() {
+        let path = "Cargo.toml".into();
+        for manifest in [
+            "[package]\nname = \"a\"\nversion = { workspace = true }\n",
+            "[package]\nname = \"a\"\nversion.workspace = true\n",
+            "[package]\nname = \"a\"\n  version.workspace = true # inherited\n",
+        ] {
+            let doc = parse_manifest(manifest, &path).unwrap();
+            assert!(inherits_version(&doc), "{:?}", manifest);
+            assert_eq!(
+                manifest_to_string(&doc, manifest),
+                manifest,
+                "unchanged manifests are written as they were"
+            );
+        }
+        assert!(!inherits_version(
+            &parse_manifest("[package]\nname = \"a\"\nversion = \"1.0.0\"\n", &path).unwrap()
+        ));
+    }
+
+    #[test]
+    fn dotted_keys_are_kept_when_editing() {
+        let manifest = "[package]\nname = \"a\"\nversion.workspace = true\n\n[dependencies]\nb = { version = \"0.8.0\", path = \"../b\" }\nc.workspace = true\n";
+        let mut doc = parse_manifest(manifest, &"Cargo.toml".into()).unwrap();
+        doc["dependencies"]["b"]["version"] = toml_edit::value("^0.9.0");
+        assert_eq!(
+            manifest_to_string(&doc, manifest),
+            manifest.replace("\"0.8.0\"", "\"^0.9.0\"")
+        );
+    }
+
+    #[test]
+    fn safety_bumps_are_listed_up_to_the_configured_amount() {
+        let ws = Workspace::tri_depth("safety-bumps-message");
+        let ctx = ws.context(&[]);
+        let bumped: Vec<_> = ["a", "b", "c"]
+            .iter()
+            .map(|name| (package_by_name(&ctx.meta, name).unwrap(), "9.0.0".to_string()))
+            .collect();
+        let message =
+            |num_crates: usize, max_listed: Option<usize>| safety_bumps_message(&bumped[..num_crates], max_listed);
+
+        assert_eq!(message(0, Some(1)), "", "nothing to mention without safety bumps");
+        assert_eq!(
+            message(1, Some(0)),
+            ", safety bump a v9.0.0",
+            "a single crate is always named"
+        );
+        assert_eq!(
+            message(2, Some(2)),
+            ", safety bump 2 crates\n\nSAFETY BUMP: a v9.0.0, b v9.0.0",
+            "exactly as many crates as may be listed"
+        );
+        assert_eq!(
+            message(2, None),
+            message(2, Some(2)),
+            "all crates are listed by default"
+        );
+        assert_eq!(
+            message(3, Some(2)),
+            ", safety bump 3 crates\n\nSAFETY BUMP: a v9.0.0, b v9.0.0 ...and 1 more dependency update"
+        );
+        assert_eq!(
+            message(3, Some(1)),
+            ", safety bump 3 crates\n\nSAFETY BUMP: a v9.0.0 ...and 2 more dependency updates"
+        );
+        assert_eq!(
+            message(3, Some(0)),
+            ", safety bump 3 crates",
+            "only the amount of crates is mentioned if none may be listed"
+        );
+    }
+}
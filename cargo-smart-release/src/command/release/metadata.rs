@@ -0,0 +1,113 @@
+use anyhow::Context as _;
+use cargo_metadata::Package;
+use serde::Serialize;
+
+use super::{Context, Oid, Options};
+use crate::{
+    command::changelog_impl::git::ref_segments,
+    utils::{today, will},
+};
+
+/// Machine-readable information about the release of a single crate, for use by tooling.
+#[derive(Debug, Serialize)]
+struct Release {
+    name: String,
+    version: String,
+    /// The tag pointing to the release commit, if one is created.
+    tag: Option<String>,
+    /// The release commit, or `None` in dry-run mode.
+    commit: Option<String>,
+    /// The day of the release in UTC, like `2021-09-15`.
+    date: String,
+    /// All commits affecting the crate since its previous release, the most recent one first.
+    commits: Vec<String>,
+}
+
+/// Write information about the release of `publishee` to `<target-dir>/smart-release/<crate>/release-<version>.toml`.
+pub(in crate::command::release_impl) fn write(
+    publishee: &Package,
+    new_version: &str,
+    tag_name: Option<String>,
+    commit_id: Option<&Oid<'_>>,
+    ctx: &Context,
+    Options {
+        dry_run, date_today, ..
+    }: Options,
+) -> anyhow::Result<()> {
+    let history = ctx
+        .history
+        .as_ref()
+        .expect("history is traversed if release metadata is requested");
+    let commit = commit_id.map(|id| id.as_ref().to_owned());
+    let (segments, warnings) = ref_segments(&publishee.name, &ctx.base, history)?;
+    ctx.warnings.borrow_mut().extend(warnings);
+    let mut commits: Vec<_> = segments
+        .first()
+        .map(|segment| segment.history.iter().map(|item| item.id).collect())
+        .unwrap_or_default();
+    if let Some(commit) = commit.filter(|id| !commits.contains(id)) {
+        commits.insert(0, commit);
+    }
+    let release = Release {
+        name: publishee.name.clone(),
+        version: new_version.to_owned(),
+        tag: tag_name,
+        commit: commit.map(|id| id.to_string()),
+        date: today(date_today)?.to_string(),
+        commits: commits.into_iter().map(|id| id.to_string()).collect(),
+    };
+
+    let path = ctx
+        .base
+        .meta
+        .target_directory
+        .join("smart-release")
+        .join(&publishee.name)
+        .join(format!("release-{}.toml", new_version));
+    log::info!(
+        "{} write release metadata to '{}'",
+        will(dry_run),
+        path.strip_prefix(&ctx.base.root).unwrap_or(&path)
+    );
+    if dry_run {
+        return Ok(());
+    }
+    std::fs::create_dir_all(path.parent().expect("file in directory"))?;
+    std::fs::write(&path, toml::to_string(&release)?)
+        .with_context(|| format!("Could not write release metadata to '{}'", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::Context, write, Options};
+    use crate::{
+        command::changelog_impl::{commit::history::Warning, git::commit_history},
+        fixture::Workspace,
+        utils::package_by_name,
+    };
+
+    #[test]
+    fn warnings_are_collected_to_be_logged_at_the_end() {
+        let ws = Workspace::tri_depth("release-metadata-warnings");
+        ws.git(&["tag", "a-v0.8.0"]);
+        ws.git(&["tag", "a-v0.8.1"]);
+        let base = ws.context(&["a"]);
+        let mut ctx = Context::new(base, "auto".into(), "auto".into(), Vec::new(), Vec::new()).unwrap();
+        ctx.history = commit_history(&ctx.base, Default::default(), &[]).unwrap();
+        let a = package_by_name(&ctx.base.meta, "a").unwrap();
+        let options = Options {
+            dry_run: true,
+            ..Default::default()
+        };
+
+        write(a, "0.9.0", None, None, &ctx, options).unwrap();
+        assert_eq!(
+            *ctx.warnings.borrow(),
+            vec![Warning::TagOnSameCommit {
+                crate_name: "a".into(),
+                ignored: "a-v0.8.0".into(),
+                used: "a-v0.8.1".into()
+            }]
+        );
+    }
+}
@@ -1,21 +1,41 @@
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, convert::TryInto};
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 use cargo_metadata::{Dependency, DependencyKind, Metadata, Package};
 use crates_index::Index;
+use git_repository::{bstr::ByteSlice, prelude::ObjectIdExt, refs};
 
 use crate::{
-    command::release::Options,
+    command::{
+        changelog, changelog_impl,
+        release::{Inputs, Options, Phase, ProposedVersion, VersionTransform},
+    },
+    traverse::{SkipReason, Skipped},
     utils::{
-        is_dependency_with_version_requirement, names_and_versions, package_by_id, package_by_name,
-        package_eq_dependency, package_for_dependency, tag_name, will, workspace_package_by_id,
+        is_dependency_with_version_requirement, is_workspace_member, names_and_versions, package_by_id,
+        package_by_name, package_eq_dependency, package_for_dependency, tag_name, will, workspace_package_by_id,
     },
 };
+use state::State;
+
+pub use crates_file::crates_from_file;
 
+mod breaking_paths;
+mod bump_files;
 mod cargo;
+mod crates_file;
+mod diff;
+mod explain;
 mod git;
+mod graph;
+mod hook;
 mod manifest;
+mod metadata;
+mod policy;
+mod semver_checks;
+mod state;
 mod version;
+mod version_hook;
 
 type Oid<'repo> = git_repository::easy::Oid<'repo, git_repository::Easy>;
 
@@ -24,74 +44,595 @@ pub(crate) struct Context {
     crates_index: Index,
     bump: String,
     bump_dependencies: String,
+    /// Additional arguments to pass to `cargo publish` for crates that don't configure their own.
+    publish_args: Vec<String>,
+    /// Globs relative to the directory of each crate to bump matching files whose version should be updated as well.
+    bump_files: Vec<String>,
+    /// The commit history, only traversed if release metadata is to be written.
+    history: Option<changelog_impl::commit::History>,
+    /// Set once a crate was published, so the next one can be published after the configured delay.
+    published_any: std::cell::Cell<bool>,
+    /// Warnings encountered while traversing the history, logged together at the end of the run.
+    warnings: std::cell::RefCell<Vec<changelog_impl::commit::history::Warning>>,
 }
 
 impl Context {
-    fn new(crate_names: Vec<String>, bump: String, bump_dependencies: String) -> anyhow::Result<Self> {
+    fn new(
+        base: crate::Context,
+        bump: String,
+        bump_dependencies: String,
+        publish_args: Vec<String>,
+        bump_files: Vec<String>,
+    ) -> anyhow::Result<Self> {
         let crates_index = Index::new_cargo_default();
         Ok(Context {
-            base: crate::Context::new(crate_names)?,
+            base,
             crates_index,
             bump,
             bump_dependencies,
+            publish_args,
+            bump_files,
+            history: None,
+            published_any: Default::default(),
+            warnings: Default::default(),
         })
     }
 }
 
 /// In order to try dealing with https://github.com/sunng87/cargo-release/issues/224 and also to make workspace
 /// releases more selective.
-pub fn release(options: Options, crates: Vec<String>, bump: String, bump_dependencies: String) -> anyhow::Result<()> {
+///
+/// With `options.workspace`, all publishable workspace members except for the `excluded` ones of `inputs` are released
+/// instead of `crates`. All other `inputs` are described in [`Inputs`].
+pub fn release(
+    options: Options,
+    crates: Vec<String>,
+    bump: String,
+    bump_dependencies: String,
+    inputs: Inputs,
+) -> anyhow::Result<()> {
+    let options = if options.only {
+        Options {
+            skip_dependencies: true,
+            allow_auto_publish_of_stable_crates: false,
+            isolate_dependencies_from_breaking_changes: false,
+            ..options
+        }
+    } else {
+        options
+    };
+    let plan_printers: Vec<_> = [
+        ("--explain", options.explain),
+        ("--tag-name-only", options.tag_name_only),
+        ("--dependencies-graph", options.dependencies_graph.is_some()),
+    ]
+    .iter()
+    .filter(|(_, is_set)| *is_set)
+    .map(|(flag, _)| *flag)
+    .collect();
+    if plan_printers.len() > 1 {
+        bail!(
+            "{} print the release plan without performing it and can't be combined",
+            plan_printers.join(" and ")
+        )
+    }
+    if options.tag_name_only && options.skip_tag {
+        bail!("--tag-name-only prints the tags to create and can't be used along with --skip-tag")
+    }
     if options.dry_run_cargo_publish && !options.dry_run {
         bail!("The --no-dry-run-cargo-publish flag is only effective without --execute")
     }
-    let ctx = Context::new(crates, bump, bump_dependencies)?;
+    if let Some(jobs) = options.dry_run_cargo_publish_jobs {
+        if !options.dry_run_cargo_publish {
+            bail!("--dry-run-cargo-publish-jobs is only effective with --dry-run-cargo-publish")
+        }
+        if jobs == 0 {
+            bail!("--dry-run-cargo-publish-jobs must be at least 1")
+        }
+    }
+    if options.phase == Phase::Publish && (bump != "keep" || bump_dependencies != "keep") {
+        bail!("The versions of a prepared release are taken from the manifests and can't be bumped with --bump or --bump-dependencies")
+    }
+    if options.publish_only {
+        if crates.len() != 1 || options.workspace {
+            bail!("--publish-only publishes a single crate and can't be used along with other crates or --workspace")
+        }
+        if bump != "keep" || bump_dependencies != "keep" || options.semver_checks || options.phase != Phase::All {
+            bail!("--publish-only uses the version in the manifest and can't be used along with --bump, --bump-dependencies, --semver-checks or the 'prepare' and 'publish' phases")
+        }
+    }
+    if options.phase == Phase::Publish && options.semver_checks {
+        bail!(
+            "The versions of a prepared release are taken from the manifests and can't be raised with --semver-checks"
+        )
+    }
+    let bump = if options.bump_dependencies_only {
+        if options.skip_dependencies {
+            bail!("--bump-dependencies-only releases crates because of their dependencies and can't be used along with --skip-dependencies or --only")
+        }
+        if bump != "keep" {
+            bail!("--bump-dependencies-only bumps the given crates like their dependencies - use --bump-dependencies instead of --bump")
+        }
+        bump_dependencies.clone()
+    } else {
+        bump
+    };
+    let Inputs {
+        excluded,
+        excluded_commits,
+        publish_args,
+        bump_files,
+        version_transforms,
+    } = inputs;
+    cargo::assure_publish_args_are_not_managed(&publish_args, "--publish-arg")?;
+    let base = if options.workspace {
+        if !crates.is_empty() {
+            bail!("Crates to release can't be specified along with --workspace - use --exclude to skip some of them")
+        }
+        crate::Context::for_workspace(&excluded)?
+    } else {
+        if !excluded.is_empty() {
+            bail!("--exclude can only be used along with --workspace")
+        }
+        crate::Context::new(crates)?
+    };
+    let mut ctx = Context::new(base, bump, bump_dependencies, publish_args, bump_files)?;
+    if options.release_metadata || options.explain || options.tags_are_immutable || !excluded_commits.is_empty() {
+        ctx.history = changelog_impl::git::commit_history(
+            &ctx.base,
+            changelog::Options {
+                max_commits: options.max_commits,
+                truncate_history: options.truncate_history,
+                ..Default::default()
+            },
+            &excluded_commits,
+        )?;
+        if let Some(history) = &ctx.history {
+            ctx.warnings.borrow_mut().extend(history.warnings.iter().cloned());
+            let excluded = changelog_impl::git::resolve_commits(&history.items, &excluded_commits)?;
+            if !excluded.is_empty() {
+                ctx.base.changed_by_excluded_commits_only =
+                    changelog_impl::git::crates_changed_by_excluded_commits_only(&ctx.base, &history.items, &excluded)?;
+            }
+        }
+    }
+    if options.tags_are_immutable {
+        assure_tags_are_reachable(&ctx)?;
+    }
+    if options.update_crates_index && options.skip_crates_index_update {
+        bail!("--update-crates-index and --skip-crates-index-update are mutually exclusive")
+    }
     if options.update_crates_index {
         log::info!("Updating crates-io index at '{}'", ctx.crates_index.path().display());
         ctx.crates_index.update()?;
-    } else if options.bump_when_needed {
+    } else if options.bump_when_needed && !options.skip_crates_index_update {
         log::warn!(
             "Consider running with --update-crates-index to assure bumping on demand uses the latest information"
         );
     }
+    if options.wait_for_index.is_some() && !options.skip_publish && !ctx.crates_index.exists() {
+        bail!("--after-publish-sleep-for-index needs the crates.io index - use --update-crates-index to download it")
+    }
     if !ctx.crates_index.exists() {
         log::warn!("Crates.io index doesn't exist. Consider using --update-crates-index to help determining if release versions are published already");
     }
 
-    release_depth_first(ctx, options)?;
-    Ok(())
+    let res = if options.publish_only {
+        publish_only(&ctx, options)
+    } else {
+        release_depth_first(&ctx, options, &version_transforms)
+    };
+    changelog_impl::commit::history::log_warnings(&ctx.warnings.borrow());
+    res
+}
+
+/// Publish the only crate of `ctx` at the version in its manifest and push its existing tag, for a release which was
+/// committed and tagged by a previous run but failed to publish.
+fn publish_only(ctx: &Context, options: Options) -> anyhow::Result<()> {
+    let package = package_by_name(&ctx.base.meta, &ctx.base.crate_names[0])?;
+    let version = package.version.to_string();
+    let tag_name = tag_name(package, &version, &ctx.base.repo);
+    let tag_ref = format!("refs/tags/{}", tag_name);
+    let tagged_commit = match crate::git::resolve_commit(&ctx.base, &tag_ref) {
+        Ok(id) => id,
+        Err(_) => bail!(
+            "'{}' v{} has no tag '{}' - --publish-only is meant for releases which were committed and tagged already",
+            package.name,
+            version,
+            tag_name
+        ),
+    };
+    if tagged_commit != crate::git::resolve_commit(&ctx.base, "HEAD")? {
+        bail!(
+            "Tag '{}' doesn't point to HEAD - check out the tagged commit to publish it",
+            tag_name
+        );
+    }
+    let is_published = ctx
+        .crates_index
+        .crate_(&package.name)
+        .map(|krate| krate.versions().iter().any(|v| v.version() == version))
+        .unwrap_or_default();
+    if is_published {
+        bail!(
+            "'{}' v{} is published already - there is nothing to do",
+            package.name,
+            version
+        );
+    }
+    if !options.dry_run {
+        confirm_plan(&[(package, version.clone())], ctx, options)?;
+    }
+    cargo::publish_crate(package, &version, &[], ctx, options)?;
+    git::push_tags_and_head(
+        Some(tag_ref.as_str().try_into()?),
+        &ctx.base,
+        Options {
+            skip_push_commits: true,
+            ..options
+        },
+    )
 }
 
-fn release_depth_first(ctx: Context, options: Options) -> anyhow::Result<()> {
+fn release_depth_first(
+    ctx: &Context,
+    options: Options,
+    version_transforms: &[Box<dyn VersionTransform>],
+) -> anyhow::Result<()> {
     let meta = &ctx.base.meta;
+    let mut skipped = ctx.base.skipped.clone();
     let changed_crate_names_to_publish = if options.skip_dependencies {
         ctx.base.crate_names.clone()
     } else {
-        crate::traverse::dependencies(&ctx.base, options.verbose, options.allow_auto_publish_of_stable_crates)?
+        let (crate_names, skipped_dependencies) =
+            crate::traverse::dependencies(&ctx.base, options.verbose, options.allow_auto_publish_of_stable_crates)?;
+        assure_auto_published_crates_allow_it(&ctx.base, &crate_names)?;
+        skipped.extend(skipped_dependencies);
+        crate_names
+    };
+    let changed_crate_names_to_publish = if options.bump_dependencies_only {
+        retain_crates_with_released_dependencies(&ctx.base, changed_crate_names_to_publish, &mut skipped)?
+    } else {
+        changed_crate_names_to_publish
     };
 
     let crates_to_publish_together = resolve_cycles_with_publish_group(meta, &changed_crate_names_to_publish, options)?;
 
+    let mut plan = changed_crate_names_to_publish
+        .iter()
+        .map(|name| {
+            let p = package_by_name(meta, name)?;
+            let bump_spec = version::select_publishee_bump_spec(&p.name, ctx);
+            let bump_spec = if options.semver_checks {
+                semver_checks::escalate_bump_spec(p, bump_spec, ctx, options)?
+            } else {
+                bump_spec
+            };
+            let bump_spec = breaking_paths::escalate_bump_spec(p, bump_spec, ctx)?;
+            version::bump(p, bump_spec, ctx, options.bump_when_needed).map(|v| (p, v.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if options.phase != Phase::Publish {
+        transform_versions(&mut plan, version_transforms, ctx, options)?;
+    }
+    let mut state = State::load(ctx, options)?;
+    state.resume(&mut plan);
+    if options.only {
+        assure_dependencies_are_published(&plan, ctx)?;
+    }
+    assure_changelogs_have_no_forbidden_markers(&plan, ctx)?;
+    policy::validate_plan(&plan, ctx, options)?;
+    if options.explain {
+        return explain::print(&plan, ctx, options);
+    }
+    if let Some(format) = options.dependencies_graph {
+        return graph::print(&plan, ctx, options, format);
+    }
+    if options.tag_name_only {
+        for (publishee, new_version) in &plan {
+            println!("{}", tag_name(publishee, new_version, &ctx.base.repo));
+        }
+        return Ok(());
+    }
+
     assure_working_tree_is_unchanged(options)?;
+    git::assure_manifest_versions_are_not_tagged(&plan, |tag_name| state.is_tagged(tag_name), &ctx.base, options)?;
+    if !options.skip_tag && options.phase != Phase::Prepare {
+        git::assure_tags_do_not_exist(&plan, |tag_name| state.is_tagged(tag_name), &ctx.base, options)?;
+    }
+    if !options.dry_run {
+        confirm_plan(&plan, ctx, options)?;
+    }
+    let options = if options.dry_run_cargo_publish && !options.skip_publish && options.phase != Phase::Prepare {
+        let publishees = plan
+            .iter()
+            .enumerate()
+            .filter(|(_, (publishee, new_version))| {
+                !state.is_published(&tag_name(publishee, new_version, &ctx.base.repo))
+            })
+            .map(|(index, (publishee, _))| {
+                let published_together =
+                    options.multi_crate_release || crates_to_publish_together.contains(&publishee.name);
+                let other_publishee_names = plan[index + 1..]
+                    .iter()
+                    .filter(|(p, _)| {
+                        published_together
+                            && (options.multi_crate_release || crates_to_publish_together.contains(&p.name))
+                    })
+                    .map(|(p, _)| p.name.to_owned())
+                    .collect();
+                (*publishee, other_publishee_names)
+            })
+            .collect();
+        cargo::dry_run_publish_crates(publishees, ctx, options)?;
+        Options {
+            dry_run_cargo_publish: false,
+            ..options
+        }
+    } else {
+        options
+    };
 
     if options.multi_crate_release && !changed_crate_names_to_publish.is_empty() {
-        perforrm_multi_version_release(&ctx, options, meta, changed_crate_names_to_publish)?;
+        perforrm_multi_version_release(ctx, options, meta, &plan, changed_crate_names_to_publish, &mut state)?;
     } else {
         for publishee_name in changed_crate_names_to_publish
             .iter()
             .filter(|n| !crates_to_publish_together.contains(n))
         {
             let publishee = package_by_name(meta, publishee_name)?;
+            let new_version = planned_version(&plan, publishee_name);
 
-            let (new_version, commit_id) = perform_single_release(meta, publishee, options, &ctx)?;
-            let tag_name = git::create_version_tag(publishee, &new_version, commit_id, &ctx.base, options)?;
-            git::push_tags_and_head(tag_name, options)?;
+            let tag_name = perform_single_release(meta, publishee, new_version, options, ctx, &mut state)?;
+            if options.phase != Phase::Prepare {
+                git::push_tags_and_head(tag_name, &ctx.base, options)?;
+            }
         }
     }
 
     if !crates_to_publish_together.is_empty() {
-        perforrm_multi_version_release(&ctx, options, meta, crates_to_publish_together)?;
+        perforrm_multi_version_release(ctx, options, meta, &plan, crates_to_publish_together, &mut state)?;
     }
 
+    let released: Vec<_> = plan.iter().map(|(p, _)| p.name.clone()).collect();
+    crate::traverse::log_skipped(&skipped, &released);
+    state.finish()
+}
+
+/// Fail if one of `crate_names` to publish wasn't requested but would be published automatically as dependency, even
+/// though it's configured with `auto-publish = false`.
+fn assure_auto_published_crates_allow_it(ctx: &crate::Context, crate_names: &[String]) -> anyhow::Result<()> {
+    let mut opted_out = Vec::new();
+    for name in crate_names.iter().filter(|name| !ctx.crate_names.contains(name)) {
+        let package = package_by_name(&ctx.meta, name)?;
+        if crate::config::package(package)?.auto_publish == Some(false) {
+            opted_out.push(format!("{} v{}", package.name, package.version));
+        }
+    }
+    if !opted_out.is_empty() {
+        bail!(
+            "{} changed since the last release and would have to be published along with the requested crates, but {} configured with 'auto-publish = false'. Release {} explicitly or beforehand.",
+            opted_out.join(", "),
+            if opted_out.len() == 1 { "is" } else { "are" },
+            if opted_out.len() == 1 { "it" } else { "them" }
+        );
+    }
+    Ok(())
+}
+
+/// Remove the given crates from `crate_names` unless one of their workspace dependencies is released as well, so they
+/// are never released only because of their own changes. Dependencies in `crate_names` are kept as they are, and the
+/// removed crates are added to `skipped`.
+fn retain_crates_with_released_dependencies(
+    ctx: &crate::Context,
+    crate_names: Vec<String>,
+    skipped: &mut Vec<Skipped>,
+) -> anyhow::Result<Vec<String>> {
+    let mut retained = Vec::with_capacity(crate_names.len());
+    for crate_name in crate_names {
+        if ctx.crate_names.contains(&crate_name)
+            && !crate::traverse::dependency_tree_has_link_to_existing_crate_names(&ctx.meta, &crate_name, &retained)?
+        {
+            let package = package_by_name(&ctx.meta, &crate_name)?;
+            log::info!(
+                "Skipping {} v{} as none of its workspace dependencies are released and --bump-dependencies-only ignores its own changes",
+                package.name,
+                package.version
+            );
+            skipped.push(Skipped::new(crate_name, SkipReason::NoReleasedDependencies));
+            continue;
+        }
+        retained.push(crate_name);
+    }
+    Ok(retained)
+}
+
+/// Fail if a crate in `plan` depends on a workspace crate that isn't part of the plan and isn't published in a version
+/// matching its requirement, as publishing it would be impossible without also releasing the dependency.
+fn assure_dependencies_are_published(plan: &[(&Package, String)], ctx: &Context) -> anyhow::Result<()> {
+    let meta = &ctx.base.meta;
+    let mut unpublished = Vec::new();
+    for (publishee, _) in plan {
+        for dep in publishee.dependencies.iter().filter(|dep| {
+            dep.kind != DependencyKind::Development
+                && is_workspace_member(meta, &dep.name)
+                && !plan.iter().any(|(p, _)| package_eq_dependency(p, dep))
+        }) {
+            if !is_dependency_with_version_requirement(dep) {
+                unpublished.push(format!("{} (path dependency of {})", dep.name, publishee.name));
+                continue;
+            }
+            if !ctx.crates_index.exists() {
+                continue;
+            }
+            let is_published = match ctx.crates_index.crate_(&dep.name) {
+                Some(published) => published.versions().iter().any(|v| {
+                    !v.is_yanked()
+                        && matches!(semver::Version::parse(v.version()), Ok(version) if dep.req.matches(&version))
+                }),
+                None => false,
+            };
+            if !is_published {
+                unpublished.push(format!("{} {} (dependency of {})", dep.name, dep.req, publishee.name));
+            }
+        }
+    }
+    if !unpublished.is_empty() {
+        bail!(
+            "Cannot release {} on its own as the following workspace dependencies aren't published: {}. Release them as well or drop --only.",
+            names_and_versions(plan),
+            unpublished.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Fail if a release tag of any workspace crate points to a commit outside of the history of `HEAD`, listing all of them.
+fn assure_tags_are_reachable(ctx: &Context) -> anyhow::Result<()> {
+    let reachable: BTreeSet<_> = ctx
+        .history
+        .iter()
+        .flat_map(|history| history.items.iter().map(|item| item.id))
+        .collect();
+    let meta = &ctx.base.meta;
+    let mut orphaned = Vec::new();
+    for package in meta
+        .packages
+        .iter()
+        .filter(|p| meta.workspace_members.iter().any(|m| m == &p.id))
+    {
+        for tag in changelog_impl::git::tags(package, &ctx.base)? {
+            if tag.peeled.filter(|id| reachable.contains(id)).is_none() {
+                orphaned.push(
+                    changelog_impl::git::strip_tag_path(tag.name.as_bstr())
+                        .to_str_lossy()
+                        .into_owned(),
+                );
+            }
+        }
+    }
+    if !orphaned.is_empty() {
+        bail!(
+            "The following release tags point to commits which aren't reachable from HEAD anymore, indicating rewritten history: {}. Restore the released commits or delete these tags if that is intended.",
+            orphaned.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Fail if the hand-written parts of the changelog of any crate in `plan` contain one of the configured forbidden markers.
+fn assure_changelogs_have_no_forbidden_markers(plan: &[(&Package, String)], ctx: &Context) -> anyhow::Result<()> {
+    let markers = &ctx.base.config.changelog.forbidden_markers;
+    if markers.is_empty() {
+        return Ok(());
+    }
+    let mut offending = Vec::new();
+    for (publishee, _) in plan {
+        let path = changelog_impl::changelog_path(publishee)?;
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let found = crate::changelog::write::hand_written_markers(&content, markers);
+        if !found.is_empty() {
+            offending.push(format!(
+                "'{}' contains {}",
+                path.strip_prefix(&ctx.base.root).unwrap_or(&path),
+                found.iter().map(|m| format!("'{}'", m)).collect::<Vec<_>>().join(", ")
+            ));
+        }
+    }
+    if !offending.is_empty() {
+        bail!(
+            "Changelogs must not contain forbidden markers: {}. Replace them before releasing.",
+            offending.join("; ")
+        );
+    }
+    Ok(())
+}
+
+/// Rewrite the versions in `plan` with the configured version hook, if there is one, and then with each of `transforms`,
+/// failing if a replacement isn't greater than the current version of its crate.
+fn transform_versions(
+    plan: &mut Vec<(&Package, String)>,
+    transforms: &[Box<dyn VersionTransform>],
+    ctx: &Context,
+    options: Options,
+) -> anyhow::Result<()> {
+    let hook = version_hook::VersionHook::from_config(ctx, options);
+    for transform in hook
+        .iter()
+        .map(|hook| hook as &dyn VersionTransform)
+        .chain(transforms.iter().map(|transform| transform.as_ref()))
+    {
+        let proposed = plan
+            .iter()
+            .map(|(package, new_version)| {
+                Ok(ProposedVersion {
+                    package,
+                    new_version: new_version.parse()?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for (name, version) in transform.transform(&proposed)? {
+            let (package, new_version) = plan
+                .iter_mut()
+                .find(|(package, _)| package.name == name)
+                .ok_or_else(|| anyhow!("A version to use instead of the proposed one was returned for '{}', which isn't going to be released", name))?;
+            if version <= package.version {
+                bail!(
+                    "The version {} proposed for '{}' must be greater than its current version {}",
+                    version,
+                    name,
+                    package.version
+                );
+            }
+            if options.verbose && version.to_string() != *new_version {
+                log::info!(
+                    "Using version {} of '{}' instead of {} as proposed",
+                    version,
+                    name,
+                    new_version
+                );
+            }
+            *new_version = version.to_string();
+        }
+    }
+    Ok(())
+}
+
+fn planned_version<'a>(plan: &'a [(&Package, String)], crate_name: &str) -> &'a str {
+    plan.iter()
+        .find_map(|(p, v)| (p.name == crate_name).then(|| v.as_str()))
+        .expect("all crates to publish are part of the plan")
+}
+
+/// Print `plan` and ask for confirmation on stdin before anything is changed, unless `options.yes` is set.
+///
+/// Without a terminal to ask on, `--yes` is required.
+fn confirm_plan(plan: &[(&Package, String)], ctx: &Context, options: Options) -> anyhow::Result<()> {
+    if options.yes {
+        return Ok(());
+    }
+    if !atty::is(atty::Stream::Stdin) {
+        bail!("Refusing to release without confirmation as stdin isn't a terminal - pass --yes to release anyway")
+    }
+    eprintln!("About to release:");
+    for (publishee, new_version) in plan {
+        eprint!("  {} {} -> {}", publishee.name, publishee.version, new_version);
+        if options.skip_tag {
+            eprintln!();
+        } else {
+            eprintln!(" (tag {})", tag_name(publishee, new_version, &ctx.base.repo));
+        }
+    }
+    eprint!("Continue? [y/N] ");
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+        bail!("Release aborted")
+    }
     Ok(())
 }
 
@@ -112,21 +653,17 @@ fn perforrm_multi_version_release(
     ctx: &Context,
     options: Options,
     meta: &Metadata,
+    plan: &[(&Package, String)],
     crates_to_publish_together: Vec<String>,
+    state: &mut State,
 ) -> anyhow::Result<()> {
     let mut crates_to_publish_together = crates_to_publish_together
         .into_iter()
         .map(|name| {
             let p = package_by_name(meta, &name)?;
-            version::bump(
-                p,
-                version::select_publishee_bump_spec(&p.name, ctx),
-                ctx,
-                options.bump_when_needed,
-            )
-            .map(|v| (p, v.to_string()))
+            Ok((p, planned_version(plan, &name).to_owned()))
         })
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<anyhow::Result<Vec<_>>>()?;
 
     log::info!(
         "{} prepare releases of {}",
@@ -134,7 +671,10 @@ fn perforrm_multi_version_release(
         names_and_versions(&crates_to_publish_together)
     );
 
-    let commit_id = manifest::edit_version_and_fixup_dependent_crates(meta, &crates_to_publish_together, options, ctx)?;
+    let commit_id = edit_manifests_unless_committed(meta, &crates_to_publish_together, options, ctx, state)?;
+    if options.phase == Phase::Prepare {
+        return Ok(());
+    }
 
     crates_to_publish_together.reverse();
     let mut tag_names = Vec::new();
@@ -144,35 +684,135 @@ fn perforrm_multi_version_release(
             .map(|(p, _)| p.name.to_owned())
             .collect();
 
-        cargo::publish_crate(publishee, &unpublished_crates, options)?;
-        if let Some(tag_name) = git::create_version_tag(publishee, &new_version, commit_id.clone(), &ctx.base, options)?
-        {
+        if let Some(tag_name) = publish_and_tag(
+            publishee,
+            &new_version,
+            &unpublished_crates,
+            commit_id.clone(),
+            ctx,
+            options,
+            state,
+        )? {
             tag_names.push(tag_name);
         };
     }
-    git::push_tags_and_head(tag_names, options)?;
+    git::push_tags_and_head(tag_names, &ctx.base, options)?;
     Ok(())
 }
 
-fn perform_single_release<'repo>(
+fn perform_single_release(
     meta: &Metadata,
     publishee: &Package,
+    new_version: &str,
     options: Options,
-    ctx: &'repo Context,
-) -> anyhow::Result<(String, Option<Oid<'repo>>)> {
-    let bump_spec = version::select_publishee_bump_spec(&publishee.name, ctx);
-    let new_version = version::bump(publishee, bump_spec, ctx, options.bump_when_needed)?;
+    ctx: &Context,
+    state: &mut State,
+) -> anyhow::Result<Option<refs::FullName>> {
     log::info!(
         "{} prepare release of {} v{}",
         will(options.dry_run),
         publishee.name,
         new_version
     );
-    let new_version = new_version.to_string();
-    let commit_id =
-        manifest::edit_version_and_fixup_dependent_crates(meta, &[(publishee, new_version.clone())], options, ctx)?;
-    cargo::publish_crate(publishee, &[], options)?;
-    Ok((new_version, commit_id))
+    let commit_id = edit_manifests_unless_committed(meta, &[(publishee, new_version.to_owned())], options, ctx, state)?;
+    if options.phase == Phase::Prepare {
+        return Ok(None);
+    }
+    publish_and_tag(publishee, new_version, &[], commit_id, ctx, options, state)
+}
+
+/// Edit the manifests of all `publishees` and commit the changes, unless this was done in a previous run already as
+/// per our `state` or in a previous `prepare` phase, in which case the current `HEAD` is used as release commit.
+fn edit_manifests_unless_committed<'repo>(
+    meta: &Metadata,
+    publishees: &[(&Package, String)],
+    options: Options,
+    ctx: &'repo Context,
+    state: &mut State,
+) -> anyhow::Result<Option<Oid<'repo>>> {
+    if options.phase == Phase::Publish {
+        let id = git::head_id(&ctx.base)?;
+        log::info!(
+            "{} use prepared release commit {} for {}",
+            will(options.dry_run),
+            id.as_ref(),
+            names_and_versions(publishees)
+        );
+        return Ok(Some(id));
+    }
+    let tag_names: Vec<_> = publishees.iter().map(|(p, v)| tag_name(p, v, &ctx.base.repo)).collect();
+    if let Some(id) = state.commit(&tag_names) {
+        log::info!(
+            "{} reuse release commit {} of a previous run for {}",
+            will(options.dry_run),
+            id,
+            names_and_versions(publishees)
+        );
+        return Ok(Some(id.attach(&ctx.base.repo)));
+    }
+    let commit_id = manifest::edit_version_and_fixup_dependent_crates(meta, publishees, options, ctx)?;
+    if let Some(id) = &commit_id {
+        state.set_committed(&tag_names, id.clone().detach())?;
+    }
+    Ok(commit_id)
+}
+
+/// Publish and tag `publishee` unless this was done in a previous run already as per our `state`.
+fn publish_and_tag<'repo>(
+    publishee: &Package,
+    new_version: &str,
+    unpublished_crates: &[String],
+    commit_id: Option<Oid<'repo>>,
+    ctx: &'repo Context,
+    options: Options,
+    state: &mut State,
+) -> anyhow::Result<Option<refs::FullName>> {
+    let tag_name = tag_name(publishee, new_version, &ctx.base.repo);
+    let skip_tag = options.skip_tag || (!options.tag_kept_versions && publishee.version.to_string() == new_version);
+    if state.is_published(&tag_name) {
+        log::info!(
+            "{} skip publishing {} v{} as it was published in a previous run",
+            will(options.dry_run),
+            publishee.name,
+            new_version
+        );
+    } else {
+        cargo::publish_crate(publishee, new_version, unpublished_crates, ctx, options)?;
+        state.set_published(&tag_name)?;
+    }
+    if options.release_metadata {
+        metadata::write(
+            publishee,
+            new_version,
+            (!skip_tag).then(|| tag_name.clone()),
+            commit_id.as_ref(),
+            ctx,
+            options,
+        )?;
+    }
+
+    if skip_tag && !options.skip_tag {
+        log::info!(
+            "{} skip creating tag {} as the version of {} was kept",
+            will(options.dry_run),
+            tag_name,
+            publishee.name
+        );
+        return Ok(None);
+    }
+    if state.is_tagged(&tag_name) {
+        log::info!(
+            "{} skip creating tag {} as it was created in a previous run",
+            will(options.dry_run),
+            tag_name
+        );
+        return Ok(Some(format!("refs/tags/{}", tag_name).try_into()?));
+    }
+    let tag = git::create_version_tag(publishee, new_version, commit_id, &ctx.base, options)?;
+    if tag.is_some() {
+        state.set_tagged(&tag_name)?;
+    }
+    Ok(tag)
 }
 
 fn resolve_cycles_with_publish_group(
@@ -296,3 +936,170 @@ fn hops_for_dependency_to_link_back_to_publishee<'a>(
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        assure_auto_published_crates_allow_it, publish_only, retain_crates_with_released_dependencies,
+        transform_versions, Context,
+    };
+    use crate::{
+        command::release::{ExplicitVersions, VersionTransform},
+        fixture::Workspace,
+        traverse::{SkipReason, Skipped},
+    };
+
+    #[test]
+    fn crates_are_retained_only_if_a_workspace_dependency_is_released() {
+        let ws = Workspace::tri_depth("retain-released-dependencies");
+        let retain = |requested: &[&str], crate_names: &[&str]| {
+            let mut skipped = Vec::new();
+            let retained = retain_crates_with_released_dependencies(
+                &ws.context(requested),
+                crate_names.iter().map(|name| name.to_string()).collect(),
+                &mut skipped,
+            )
+            .unwrap();
+            (retained, skipped)
+        };
+
+        assert_eq!(
+            retain(&["b", "c"], &["a", "b", "c"]),
+            (vec!["a".into(), "b".into(), "c".into()], Vec::new()),
+            "b depends on the released a, and c on b"
+        );
+        assert_eq!(
+            retain(&["c"], &["a", "c"]),
+            (vec!["a".into(), "c".into()], Vec::new()),
+            "indirect dependencies count as well"
+        );
+        assert_eq!(
+            retain(&["b", "c"], &["b", "c"]),
+            (
+                Vec::<String>::new(),
+                vec![
+                    Skipped::new("b", SkipReason::NoReleasedDependencies),
+                    Skipped::new("c", SkipReason::NoReleasedDependencies)
+                ]
+            ),
+            "their own changes don't count"
+        );
+    }
+
+    #[test]
+    fn crates_opting_out_of_auto_publishing_must_be_requested() {
+        let ws = Workspace::tri_depth("auto-publish-opt-out");
+        for name in ["a", "b"] {
+            ws.configure(name, "[package.metadata.smart-release]\nauto-publish = false");
+        }
+        ws.commit("a and b opt out of auto-publishing");
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        assert!(
+            assure_auto_published_crates_allow_it(&ws.context(&["a", "b", "c"]), &names(&["a", "b", "c"])).is_ok(),
+            "requested crates are published no matter their configuration"
+        );
+        assert!(
+            assure_auto_published_crates_allow_it(&ws.context(&["b"]), &names(&["b"])).is_ok(),
+            "unchanged dependencies aren't published"
+        );
+        assert_eq!(
+            assure_auto_published_crates_allow_it(&ws.context(&["b"]), &names(&["a", "b"]))
+                .unwrap_err()
+                .to_string(),
+            "a v0.8.0 changed since the last release and would have to be published along with the requested crates, but is configured with 'auto-publish = false'. Release it explicitly or beforehand."
+        );
+        assert_eq!(
+            assure_auto_published_crates_allow_it(&ws.context(&["c"]), &names(&["a", "b", "c"]))
+                .unwrap_err()
+                .to_string(),
+            "a v0.8.0, b v0.8.0 changed since the last release and would have to be published along with the requested crates, but are configured with 'auto-publish = false'. Release them explicitly or beforehand."
+        );
+    }
+
+    #[test]
+    fn only_tagged_releases_at_head_are_published() {
+        let ws = Workspace::tri_depth("publish-only");
+        let ctx = || Context::new(ws.context(&["a"]), "auto".into(), "auto".into(), Vec::new(), Vec::new()).unwrap();
+        let options = crate::command::release::Options {
+            dry_run: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            publish_only(&ctx(), options).unwrap_err().to_string(),
+            "'a' v0.8.0 has no tag 'a-v0.8.0' - --publish-only is meant for releases which were committed and tagged already"
+        );
+        ws.git(&["tag", "a-v0.8.0"]);
+        ws.commit("after the release");
+        assert_eq!(
+            publish_only(&ctx(), options).unwrap_err().to_string(),
+            "Tag 'a-v0.8.0' doesn't point to HEAD - check out the tagged commit to publish it",
+            "the tagged commit is what was released"
+        );
+    }
+
+    #[test]
+    fn versions_are_transformed_by_the_version_hook_and_then_in_order() {
+        let ws = Workspace::tri_depth("transform-versions");
+        let mut ctx = Context::new(ws.context(&["a"]), "auto".into(), "auto".into(), Vec::new(), Vec::new()).unwrap();
+        let meta = ctx.base.meta.clone();
+        let (a, b) = (
+            crate::utils::package_by_name(&meta, "a").unwrap(),
+            crate::utils::package_by_name(&meta, "b").unwrap(),
+        );
+        let explicit = |versions: &[(&str, &str)]| -> Box<dyn VersionTransform> {
+            Box::new(ExplicitVersions(
+                versions
+                    .iter()
+                    .map(|(name, version)| (name.to_string(), version.parse().unwrap()))
+                    .collect(),
+            ))
+        };
+        let transform = |ctx: &Context, transforms: &[Box<dyn VersionTransform>]| {
+            let mut plan = vec![(a, "0.9.0".to_string()), (b, "0.9.0".to_string())];
+            transform_versions(&mut plan, transforms, ctx, Default::default())
+                .map(|()| plan.into_iter().map(|(_, version)| version).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            transform(&ctx, &[]).unwrap(),
+            ["0.9.0", "0.9.0"],
+            "nothing to transform"
+        );
+        assert_eq!(
+            transform(&ctx, &[explicit(&[("a", "1.0.0")]), explicit(&[("a", "2.0.0")])]).unwrap(),
+            ["2.0.0", "0.9.0"],
+            "later transforms win"
+        );
+        assert_eq!(
+            transform(&ctx, &[explicit(&[("c", "9.0.0")])]).unwrap_err().to_string(),
+            "Version 9.0.0 of 'c' was given with --crates-from, but 'c' isn't going to be released, usually as it didn't change since its last release - remove its version from the file to skip it"
+        );
+        assert_eq!(
+            transform(&ctx, &[explicit(&[("b", "0.8.0")])]).unwrap_err().to_string(),
+            "The version 0.8.0 proposed for 'b' must be greater than its current version 0.8.0"
+        );
+
+        ctx.base.config.version_hook = Some(vec![
+            "sh".into(),
+            "-c".into(),
+            r#"grep -q '"new_version":"0.9.0"' && echo '{"a": "2024.3.0", "b": "2024.3.0"}'"#.into(),
+        ]);
+        assert_eq!(
+            transform(&ctx, &[explicit(&[("b", "1.0.0")])]).unwrap(),
+            ["2024.3.0", "1.0.0"],
+            "the version hook sees the proposed versions and runs first"
+        );
+        ctx.base.config.version_hook = Some(vec!["sh".into(), "-c".into(), "echo '{\"a\": \"x\"}'".into()]);
+        assert_eq!(
+            transform(&ctx, &[]).unwrap_err().to_string(),
+            "The version hook 'sh' returned the invalid version 'x' for 'a'"
+        );
+        ctx.base.config.version_hook = Some(vec!["sh".into(), "-c".into(), "exit 1".into()]);
+        assert_eq!(
+            transform(&ctx, &[]).unwrap_err().to_string(),
+            "The version hook 'sh' failed with exit status: 1"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use std::process::Command;
+
+use anyhow::bail;
+use cargo_metadata::Package;
+
+use super::{hook, tag_name, Context, Options};
+use crate::utils::names_and_versions;
+
+/// Run the policy hook configured in the workspace, if there is one, with the release `plan` as JSON on its stdin.
+///
+/// This happens in dry-run mode as well to allow validating the hook itself.
+pub(in crate::command::release_impl) fn validate_plan(
+    plan: &[(&Package, String)],
+    ctx: &Context,
+    Options { dry_run, verbose, .. }: Options,
+) -> anyhow::Result<()> {
+    let (program, args) = match ctx.base.config.policy_hook.as_ref().and_then(|cmd| cmd.split_first()) {
+        Some(program_and_args) => program_and_args,
+        None => return Ok(()),
+    };
+    let input = serde_json::json!({
+        "dry_run": dry_run,
+        "crates": plan
+            .iter()
+            .map(|(package, new_version)| serde_json::json!({
+                "name": package.name,
+                "current_version": package.version.to_string(),
+                "new_version": new_version,
+                "tag_name": tag_name(package, new_version, &ctx.base.repo),
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let mut cmd = Command::new(program);
+    cmd.args(args).current_dir(&ctx.base.root);
+    if verbose {
+        log::info!("Running policy hook {:?}", cmd);
+    }
+    let status = hook::run("policy hook", &mut cmd, &input)?.status;
+    if !status.success() {
+        bail!(
+            "The policy hook '{}' vetoed the release of {} with {}",
+            program,
+            names_and_versions(plan),
+            status
+        );
+    }
+    Ok(())
+}
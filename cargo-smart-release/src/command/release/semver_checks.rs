@@ -0,0 +1,201 @@
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context as _};
+use cargo_metadata::Package;
+
+use super::{Context, Options};
+use crate::config::{Bump, PreOneBreaking};
+
+/// Run `cargo semver-checks` on `package` against its latest published version and return the spec of the bump its API
+/// changes require if `bump_spec` is a smaller bump, or `bump_spec` otherwise.
+///
+/// Crates which were never published are skipped as there is nothing to compare them to.
+pub(in crate::command::release_impl) fn escalate_bump_spec<'a>(
+    package: &Package,
+    bump_spec: &'a str,
+    ctx: &Context,
+    Options { verbose, .. }: Options,
+) -> anyhow::Result<&'a str> {
+    if ctx.crates_index.crate_(&package.name).is_none() {
+        return Ok(bump_spec);
+    }
+    let mut cmd = Command::new("cargo");
+    cmd.args(["semver-checks", "check-release", "--package"])
+        .arg(&package.name)
+        .current_dir(&ctx.base.root)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    if verbose {
+        log::info!("Running {:?}", cmd);
+    }
+    let output = cmd
+        .output()
+        .context("Could not run 'cargo semver-checks' - install it with 'cargo install cargo-semver-checks'")?;
+    escalate_for_api_changes(
+        package,
+        bump_spec,
+        output.status.success(),
+        &String::from_utf8_lossy(&output.stderr),
+    )
+}
+
+/// Return the spec of the bump required by the API changes `cargo semver-checks` reported in `stderr` if `bump_spec` is a
+/// smaller bump of `package`, or `bump_spec` otherwise.
+fn escalate_for_api_changes<'a>(
+    package: &Package,
+    bump_spec: &'a str,
+    success: bool,
+    stderr: &str,
+) -> anyhow::Result<&'a str> {
+    Ok(match detected_api_break(&package.name, success, stderr)? {
+        None => bump_spec,
+        Some(Bump::Major) => escalate_to_breaking(
+            package,
+            bump_spec,
+            breaking_bump(package)?,
+            "'cargo semver-checks' detected breaking changes to its API",
+        ),
+        Some(required) => escalate_to_breaking(
+            package,
+            bump_spec,
+            required,
+            "'cargo semver-checks' detected API changes requiring a new minor version",
+        ),
+    })
+}
+
+/// Return the spec of the `breaking` bump if `bump_spec` is a smaller bump of `package`, which is breaking for the given
+/// `reason`, or `bump_spec` otherwise.
+pub(in crate::command::release_impl) fn escalate_to_breaking<'a>(
+    package: &Package,
+    bump_spec: &'a str,
+    breaking: Bump,
+    reason: &str,
+) -> &'a str {
+    match Bump::from_spec(bump_spec) {
+        Some(bump) if bump < breaking => {
+            log::info!(
+                "Raising {} bump of '{}' to {} as {}",
+                bump_spec,
+                package.name,
+                breaking.as_spec(),
+                reason
+            );
+            breaking.as_spec()
+        }
+        Some(_) => bump_spec,
+        None => {
+            log::warn!(
+                "The version of '{}' is kept as {} even though {}",
+                package.name,
+                package.version,
+                reason
+            );
+            bump_spec
+        }
+    }
+}
+
+/// Return the bump required by the API changes `cargo semver-checks` detected for the crate named `crate_name` according
+/// to its `stderr`, or `None` if it finished with `success`. Fail if it couldn't check the API at all, for instance because
+/// it isn't installed or the crate didn't compile.
+fn detected_api_break(crate_name: &str, success: bool, stderr: &str) -> anyhow::Result<Option<Bump>> {
+    if success {
+        return Ok(None);
+    }
+    if stderr.contains("semver requires new major version") {
+        return Ok(Some(Bump::Major));
+    }
+    if stderr.contains("semver requires new minor version") {
+        return Ok(Some(Bump::Minor));
+    }
+    if stderr.contains("no such command") || stderr.contains("no such subcommand") {
+        bail!("'cargo semver-checks' isn't installed - install it with 'cargo install cargo-semver-checks'");
+    }
+    bail!(
+        "'cargo semver-checks' failed to check the API of '{}':\n{}",
+        crate_name,
+        stderr.trim_end()
+    )
+}
+
+/// The smallest bump of the version of `package` which signals a breaking change.
+pub(in crate::command::release_impl) fn breaking_bump(package: &Package) -> anyhow::Result<Bump> {
+    let v = &package.version;
+    Ok(match (v.major, v.minor) {
+        (0, 0) => Bump::Patch,
+        (0, _) => match crate::config::package(package)?.pre_1_0_breaking {
+            PreOneBreaking::Minor => Bump::Minor,
+            PreOneBreaking::Patch => Bump::Patch,
+        },
+        _ => Bump::Major,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detected_api_break, escalate_for_api_changes};
+    use crate::{config::Bump, fixture::Workspace, utils::package_by_name};
+
+    #[test]
+    fn api_breaks_are_told_apart_from_failures() {
+        assert_eq!(detected_api_break("a", true, "").unwrap(), None, "no breaking changes");
+        assert_eq!(
+            detected_api_break(
+                "a",
+                false,
+                "     Summary semver requires new major version: 1 major and 0 minor checks failed\n"
+            )
+            .unwrap(),
+            Some(Bump::Major),
+            "a detected API break"
+        );
+        assert_eq!(
+            detected_api_break(
+                "a",
+                false,
+                "     Summary semver requires new minor version: 0 major and 1 minor checks failed\n"
+            )
+            .unwrap(),
+            Some(Bump::Minor),
+            "API additions only need a new minor version"
+        );
+        assert_eq!(
+            detected_api_break("a", false, "error: no such command: `semver-checks`\n")
+                .unwrap_err()
+                .to_string(),
+            "'cargo semver-checks' isn't installed - install it with 'cargo install cargo-semver-checks'"
+        );
+        assert_eq!(
+            detected_api_break("a", false, "error: could not compile `a`\n")
+                .unwrap_err()
+                .to_string(),
+            "'cargo semver-checks' failed to check the API of 'a':\nerror: could not compile `a`",
+            "failures to build or fetch the baseline aren't mistaken for breaking changes"
+        );
+    }
+
+    #[test]
+    fn minor_api_changes_of_stable_crates_only_raise_bumps_to_minor() {
+        let ws = Workspace::tri_depth("semver-checks-minor");
+        let meta = ws.context(&["a"]).meta;
+        let mut a = package_by_name(&meta, "a").unwrap().clone();
+        a.version = semver::Version::new(1, 2, 0);
+        let minor_report = "     Summary semver requires new minor version: 0 major and 1 minor checks failed\n";
+        let major_report = "     Summary semver requires new major version: 1 major and 0 minor checks failed\n";
+
+        assert_eq!(
+            escalate_for_api_changes(&a, "patch", false, minor_report).unwrap(),
+            "minor"
+        );
+        assert_eq!(
+            escalate_for_api_changes(&a, "major", false, minor_report).unwrap(),
+            "major"
+        );
+        assert_eq!(
+            escalate_for_api_changes(&a, "patch", false, major_report).unwrap(),
+            "major"
+        );
+        assert_eq!(escalate_for_api_changes(&a, "patch", true, "").unwrap(), "patch");
+    }
+}
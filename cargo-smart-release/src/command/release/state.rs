@@ -0,0 +1,286 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context as _};
+use cargo_metadata::Package;
+use git_repository::hash::ObjectId;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use super::{Context, Options};
+
+/// The progress of a release, persisted after each step to be able to resume a release that failed midway.
+///
+/// All crates are identified by the tag name of their new version, except for those in the plan.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(in crate::command::release_impl) struct State {
+    /// The crates that are part of the release by name along with their new version, used to determine if the state
+    /// belongs to the current release and to release the same versions when resuming it.
+    plan: BTreeMap<String, String>,
+    /// The release commit containing the manifest changes of each crate.
+    committed: BTreeMap<String, String>,
+    /// The crates that were published successfully.
+    published: BTreeSet<String>,
+    /// The crates whose tag was created successfully.
+    tagged: BTreeSet<String>,
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    dry_run: bool,
+}
+
+impl State {
+    /// Load the state of a previous release, or start with a fresh state if there is none or if `restart` is set.
+    ///
+    /// Use [`State::resume()`] to apply it to the plan of the current release.
+    pub fn load(ctx: &Context, Options { restart, dry_run, .. }: Options) -> anyhow::Result<Self> {
+        Self::load_from(
+            ctx.base.repo.repo.git_dir().join("smart-release-state.json"),
+            restart,
+            dry_run,
+        )
+    }
+
+    fn load_from(path: PathBuf, restart: bool, dry_run: bool) -> anyhow::Result<Self> {
+        if restart || !path.is_file() {
+            return Ok(State {
+                path,
+                dry_run,
+                ..Default::default()
+            });
+        }
+        let previous: State = serde_json::from_slice(&std::fs::read(&path)?).with_context(|| {
+            format!(
+                "Could not read release state at '{}' - use --restart to ignore it",
+                path.display()
+            )
+        })?;
+        for id in previous.committed.values() {
+            if ObjectId::from_hex(id.as_bytes()).is_err() {
+                bail!(
+                    "The release state at '{}' contains the invalid commit id '{}' - use --restart to ignore it",
+                    path.display(),
+                    id
+                );
+            }
+        }
+        Ok(State {
+            path,
+            dry_run,
+            ..previous
+        })
+    }
+
+    /// Resume the previous release if all crates of `plan` are part of it, and release them with the versions recorded
+    /// for them as their manifests may have been bumped already. Otherwise start over with a new release of `plan`.
+    pub fn resume(&mut self, plan: &mut [(&Package, String)]) {
+        self.resume_versions(
+            plan.iter_mut()
+                .map(|(package, new_version)| (package.name.as_str(), &package.version, new_version))
+                .collect(),
+        )
+    }
+
+    /// Like [`State::resume()`], with the name, current version and new version of each crate in `plan`.
+    fn resume_versions(&mut self, plan: Vec<(&str, &Version, &mut String)>) {
+        let is_part_of_previous_release =
+            |(name, current_version, new_version): &(&str, &Version, &mut String)| match self.plan.get(*name) {
+                Some(recorded) => recorded == *new_version || *recorded == current_version.to_string(),
+                None => false,
+            };
+        if !self.plan.is_empty() && plan.iter().all(is_part_of_previous_release) {
+            log::info!(
+                "Resuming previous release of {} - use --restart to start over",
+                self.plan
+                    .iter()
+                    .map(|(name, version)| format!("{} v{}", name, version))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            for (name, _, new_version) in plan {
+                *new_version = self.plan[name].clone();
+            }
+            return;
+        }
+
+        if !self.plan.is_empty() {
+            log::warn!(
+                "Ignoring state of a previous release at '{}' as it doesn't match the current one",
+                self.path.display()
+            );
+        }
+        *self = State {
+            plan: plan
+                .into_iter()
+                .map(|(name, _, new_version)| (name.to_owned(), new_version.clone()))
+                .collect(),
+            path: std::mem::take(&mut self.path),
+            dry_run: self.dry_run,
+            ..Default::default()
+        };
+    }
+
+    /// Return the release commit of all crates identified by `tag_names` if it was created in a previous run.
+    pub fn commit(&self, tag_names: &[String]) -> Option<ObjectId> {
+        let mut ids = tag_names.iter().map(|name| self.committed.get(name));
+        let first = ids.next()??;
+        ids.all(|id| id == Some(first))
+            .then(|| ObjectId::from_hex(first.as_bytes()).expect("validated when loading"))
+    }
+
+    pub fn set_committed(&mut self, tag_names: &[String], id: ObjectId) -> anyhow::Result<()> {
+        for name in tag_names {
+            self.committed.insert(name.to_owned(), id.to_string());
+        }
+        self.persist()
+    }
+
+    pub fn is_published(&self, tag_name: &str) -> bool {
+        self.published.contains(tag_name)
+    }
+
+    pub fn set_published(&mut self, tag_name: &str) -> anyhow::Result<()> {
+        self.published.insert(tag_name.to_owned());
+        self.persist()
+    }
+
+    pub fn is_tagged(&self, tag_name: &str) -> bool {
+        self.tagged.contains(tag_name)
+    }
+
+    pub fn set_tagged(&mut self, tag_name: &str) -> anyhow::Result<()> {
+        self.tagged.insert(tag_name.to_owned());
+        self.persist()
+    }
+
+    /// Remove the persisted state as the release is complete.
+    pub fn finish(self) -> anyhow::Result<()> {
+        if !self.dry_run && self.path.is_file() {
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    fn persist(&self) -> anyhow::Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Could not write release state to '{}'", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use git_repository::hash::ObjectId;
+    use semver::Version;
+
+    use super::State;
+
+    fn state_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("smart-release-state-{}-{}.json", std::process::id(), name));
+        std::fs::remove_file(&path).ok();
+        path
+    }
+
+    fn resumed(state: &mut State, plan: &[(&str, &str, &str)]) -> Vec<String> {
+        let versions: Vec<_> = plan
+            .iter()
+            .map(|(_, current, _)| Version::parse(current).unwrap())
+            .collect();
+        let mut new_versions: Vec<_> = plan.iter().map(|(_, _, new)| new.to_string()).collect();
+        state.resume_versions(
+            plan.iter()
+                .zip(&versions)
+                .zip(new_versions.iter_mut())
+                .map(|(((name, _, _), current), new)| (*name, current, new))
+                .collect(),
+        );
+        new_versions
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = state_path("round-trip");
+        let id = ObjectId::from_hex(b"0123456789abcdef0123456789abcdef01234567").unwrap();
+        let mut state = State::load_from(path.clone(), false, false).unwrap();
+        resumed(&mut state, &[("a", "0.8.0", "0.9.0"), ("b", "1.0.0", "1.0.1")]);
+        state
+            .set_committed(&["a-v0.9.0".into(), "b-v1.0.1".into()], id)
+            .unwrap();
+        state.set_published("a-v0.9.0").unwrap();
+        state.set_tagged("a-v0.9.0").unwrap();
+
+        let state = State::load_from(path.clone(), false, false).unwrap();
+        assert_eq!(state.commit(&["a-v0.9.0".into(), "b-v1.0.1".into()]), Some(id));
+        assert_eq!(state.commit(&["b-v1.0.1".into(), "c-v1.0.0".into()]), None);
+        assert!(state.is_published("a-v0.9.0") && !state.is_published("b-v1.0.1"));
+        assert!(state.is_tagged("a-v0.9.0") && !state.is_tagged("b-v1.0.1"));
+
+        let state = State::load_from(path.clone(), true, false).unwrap();
+        assert!(!state.is_published("a-v0.9.0"), "restarting ignores the previous state");
+        state.finish().unwrap();
+        assert!(!path.exists(), "the state is removed once the release is complete");
+    }
+
+    #[test]
+    fn dry_runs_are_not_persisted() {
+        let path = state_path("dry-run");
+        let mut state = State::load_from(path.clone(), false, true).unwrap();
+        state.set_published("a-v0.9.0").unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn invalid_commit_ids_are_an_error() {
+        let path = state_path("invalid-id");
+        std::fs::write(
+            &path,
+            r#"{"plan":{},"committed":{"a-v0.9.0":"bogus"},"published":[],"tagged":[]}"#,
+        )
+        .unwrap();
+        let err = State::load_from(path.clone(), false, false).unwrap_err();
+        assert!(err.to_string().contains("invalid commit id 'bogus'"), "{}", err);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn resuming_uses_the_recorded_versions() {
+        let mut state = State::default();
+        resumed(&mut state, &[("a", "0.8.0", "0.9.0"), ("b", "1.0.0", "1.0.1")]);
+        assert_eq!(
+            resumed(&mut state, &[("a", "0.9.0", "0.10.0"), ("b", "1.0.1", "1.0.2")]),
+            vec!["0.9.0", "1.0.1"],
+            "the versions are recomputed from manifests that were bumped already"
+        );
+        assert_eq!(
+            resumed(&mut state, &[("b", "1.0.0", "1.0.1")]),
+            vec!["1.0.1"],
+            "a part of the previous release can be resumed"
+        );
+    }
+
+    #[test]
+    fn unrelated_releases_start_over() {
+        let mut state = State::default();
+        resumed(&mut state, &[("a", "0.8.0", "0.9.0")]);
+        assert_eq!(
+            resumed(&mut state, &[("a", "0.8.0", "1.0.0")]),
+            vec!["1.0.0"],
+            "a different version of a crate is another release"
+        );
+        assert_eq!(
+            resumed(&mut state, &[("a", "0.8.0", "1.0.0"), ("b", "1.0.0", "1.0.1")]),
+            vec!["1.0.0", "1.0.1"]
+        );
+        assert_eq!(
+            resumed(&mut state, &[("b", "1.0.1", "1.0.2")]),
+            vec!["1.0.1"],
+            "the new state is used from here on"
+        );
+    }
+}
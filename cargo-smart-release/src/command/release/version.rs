@@ -3,6 +3,7 @@ use cargo_metadata::Package;
 use semver::{BuildMetadata, Prerelease, Version};
 
 use super::Context;
+use crate::config::{Bump, ManifestVersionAhead, PreOneBreaking};
 
 #[allow(clippy::ptr_arg)]
 pub(crate) fn select_publishee_bump_spec<'a>(name: &String, ctx: &'a Context) -> &'a str {
@@ -19,7 +20,116 @@ pub(crate) fn bump(
     ctx: &Context,
     bump_when_needed: bool,
 ) -> anyhow::Result<Version> {
-    let mut v = publishee.version.clone();
+    let bump_spec = apply_min_bump(publishee, bump_spec)?;
+    if bump_when_needed {
+        if let Some(version) = reconcile_manifest_version_ahead_of_computed(publishee, bump_spec, ctx)? {
+            return smallest_necessary_version_relative_to_crates_index(
+                publishee, version, ctx, false, true, true, false,
+            );
+        }
+    }
+    smallest_necessary_version_relative_to_crates_index(
+        publishee,
+        apply_bump_spec(&publishee.version, bump_spec)?,
+        ctx,
+        bump_when_needed,
+        true,
+        true,
+        false,
+    )
+}
+
+/// Raise `bump_spec` to the minimum bump configured for `publishee`, if it's a smaller bump.
+fn apply_min_bump<'a>(publishee: &Package, bump_spec: &'a str) -> anyhow::Result<&'a str> {
+    Ok(match raised_bump_spec(publishee, bump_spec)? {
+        Some(raised) => {
+            log::info!(
+                "Raising {} bump of '{}' to its configured minimum of {}",
+                bump_spec,
+                publishee.name,
+                raised
+            );
+            raised
+        }
+        None => bump_spec,
+    })
+}
+
+/// Return the minimum bump configured for `publishee` if `bump_spec` is a smaller bump.
+pub(crate) fn raised_bump_spec(publishee: &Package, bump_spec: &str) -> anyhow::Result<Option<&'static str>> {
+    Ok(
+        match (Bump::from_spec(bump_spec), crate::config::package(publishee)?.min_bump) {
+            (Some(bump), Some(min_bump)) if bump < min_bump => Some(min_bump.as_spec()),
+            _ => None,
+        },
+    )
+}
+
+/// If the manifest version of `publishee` is greater than its latest published version bumped with `bump_spec`, return
+/// the computed version to use instead if configured so.
+///
+/// Releasing the manifest version is what bumping on demand does anyway, so this only warns if the computed version is
+/// used instead, or if the manifest version isn't a single bump of the latest published version.
+fn reconcile_manifest_version_ahead_of_computed(
+    publishee: &Package,
+    bump_spec: &str,
+    ctx: &Context,
+) -> anyhow::Result<Option<Version>> {
+    if bump_spec == "keep" {
+        return Ok(None);
+    }
+    let latest_published_version = match ctx.crates_index.crate_(&publishee.name) {
+        Some(published_crate) => Version::parse(published_crate.latest_version().version())?,
+        None => return Ok(None),
+    };
+    let computed = match computed_version_behind_manifest(&publishee.version, &latest_published_version, bump_spec)? {
+        Some(computed) => computed,
+        None => return Ok(None),
+    };
+    let policy = ctx.base.config.manifest_version_ahead;
+    if policy == ManifestVersionAhead::Override || skips_versions(&publishee.version, &latest_published_version)? {
+        log::warn!(
+            "Manifest version {} of '{}' is ahead of version {} computed by a {} bump of the latest published version {} - {}",
+            publishee.version,
+            publishee.name,
+            computed,
+            bump_spec,
+            latest_published_version,
+            match policy {
+                ManifestVersionAhead::Respect => "using the manifest version",
+                ManifestVersionAhead::Override => "using the computed version",
+            }
+        );
+    }
+    Ok(match policy {
+        ManifestVersionAhead::Respect => None,
+        ManifestVersionAhead::Override => Some(computed),
+    })
+}
+
+/// Return the version computed by bumping `latest_published_version` with `bump_spec` if `manifest_version` is greater.
+fn computed_version_behind_manifest(
+    manifest_version: &Version,
+    latest_published_version: &Version,
+    bump_spec: &str,
+) -> anyhow::Result<Option<Version>> {
+    let computed = apply_bump_spec(latest_published_version, bump_spec)?;
+    Ok(Some(computed).filter(|computed| manifest_version > computed))
+}
+
+/// Return true if `version`, ignoring its pre-release, isn't a patch, minor or major bump of `latest_published_version`.
+fn skips_versions(version: &Version, latest_published_version: &Version) -> anyhow::Result<bool> {
+    let release = Version::new(version.major, version.minor, version.patch);
+    for bump_spec in ["patch", "minor", "major"] {
+        if apply_bump_spec(latest_published_version, bump_spec)? == release {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+pub(crate) fn apply_bump_spec(version: &Version, bump_spec: &str) -> anyhow::Result<Version> {
+    let mut v = version.clone();
     match bump_spec {
         "major" => {
             v.major += 1;
@@ -39,7 +149,7 @@ pub(crate) fn bump(
         "keep" => {}
         _ => bail!("Invalid version specification: '{}'", bump_spec),
     };
-    smallest_necessary_version_relative_to_crates_index(publishee, v, ctx, bump_when_needed, true, true, false)
+    Ok(v)
 }
 
 fn smallest_necessary_version_relative_to_crates_index(
@@ -124,13 +234,14 @@ pub(crate) fn conservative_dependent_version(
     ctx: &Context,
     bump_when_needed: bool,
     verbose: bool,
-) -> Option<Version> {
+) -> anyhow::Result<Option<Version>> {
     let new_publishee_version: Version = new_publishee_version.parse().expect("new versions are always valid");
     if !rhs_is_breaking_bump_for_lhs(&publishee.version, &new_publishee_version) {
-        return None;
+        return Ok(None);
     }
-    let new_dependent_version = breaking_version_bump(&dependent.version);
-    smallest_necessary_version_relative_to_crates_index(
+    let new_dependent_version =
+        breaking_version_bump(&dependent.version, crate::config::package(dependent)?.pre_1_0_breaking);
+    Ok(smallest_necessary_version_relative_to_crates_index(
         dependent,
         new_dependent_version,
         ctx,
@@ -139,13 +250,16 @@ pub(crate) fn conservative_dependent_version(
         false,
         true,
     )
-    .ok()
+    .ok())
 }
 
-fn breaking_version_bump(v: &Version) -> Version {
+fn breaking_version_bump(v: &Version, pre_1_0_breaking: PreOneBreaking) -> Version {
     let (major, minor, patch) = match (v.major, v.minor, v.patch) {
         (0, 0, patch) => (0, 0, patch + 1),
-        (0, minor, _) => (0, minor + 1, 0),
+        (0, minor, patch) => match pre_1_0_breaking {
+            PreOneBreaking::Minor => (0, minor + 1, 0),
+            PreOneBreaking::Patch => (0, minor, patch + 1),
+        },
         (major, minor, _) => (major, minor + 1, 0),
     };
     Version {
@@ -160,3 +274,58 @@ fn breaking_version_bump(v: &Version) -> Version {
 pub(crate) fn rhs_is_breaking_bump_for_lhs(lhs: &Version, rhs: &Version) -> bool {
     rhs.major > lhs.major || rhs.minor > lhs.minor
 }
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use super::{apply_min_bump, computed_version_behind_manifest, skips_versions};
+    use crate::{fixture::Workspace, utils::package_by_name};
+
+    fn v(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn manifest_versions_ahead_of_the_computed_one() {
+        let computed = |manifest, bump_spec| {
+            computed_version_behind_manifest(&v(manifest), &v("0.8.0"), bump_spec)
+                .unwrap()
+                .map(|v| v.to_string())
+        };
+        assert_eq!(computed("0.9.0", "patch"), Some("0.8.1".into()));
+        assert_eq!(computed("0.9.0", "minor"), None, "the manifest was bumped as computed");
+        assert_eq!(computed("0.8.1", "minor"), None);
+        assert_eq!(computed("1.0.0-alpha.1", "minor"), Some("0.9.0".into()));
+        assert!(computed_version_behind_manifest(&v("0.9.0"), &v("0.8.0"), "invalid").is_err());
+    }
+
+    #[test]
+    fn versions_are_skipped_unless_they_are_a_single_bump() {
+        let skips = |version| skips_versions(&v(version), &v("0.8.0")).unwrap();
+        assert!(!skips("0.8.1"));
+        assert!(!skips("0.9.0"));
+        assert!(!skips("1.0.0"));
+        assert!(
+            !skips("1.0.0-alpha.1"),
+            "pre-releases of a bump are an ordinary pre-bump"
+        );
+        assert!(skips("0.8.2"));
+        assert!(skips("0.10.0"));
+        assert!(skips("2.0.0"));
+    }
+
+    #[test]
+    fn bumps_are_raised_to_the_configured_minimum() {
+        let ws = Workspace::tri_depth("min-bump");
+        ws.configure("b", "[package.metadata.smart-release]\nmin-bump = \"minor\"");
+        let ctx = ws.context(&["a", "b"]);
+        let min_bump = |name, bump_spec| apply_min_bump(package_by_name(&ctx.meta, name).unwrap(), bump_spec).unwrap();
+
+        assert_eq!(min_bump("b", "patch"), "minor");
+        assert_eq!(min_bump("b", "minor"), "minor");
+        assert_eq!(min_bump("b", "major"), "major", "greater bumps are kept");
+        assert_eq!(min_bump("b", "keep"), "keep", "versions to keep aren't bumped");
+        assert_eq!(min_bump("a", "patch"), "patch", "without configuration bumps are kept");
+    }
+}
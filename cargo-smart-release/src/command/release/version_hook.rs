@@ -0,0 +1,78 @@
+use std::{
+    collections::BTreeMap,
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Context as _};
+
+use super::{hook, Context, Options};
+use crate::command::release::{ProposedVersion, VersionTransform};
+
+/// The version hook configured in the workspace, which receives the computed versions as JSON on its stdin and prints
+/// the ones to use instead as JSON object with crate names as keys to its stdout.
+pub(in crate::command::release_impl) struct VersionHook<'a> {
+    program: &'a str,
+    args: &'a [String],
+    root: &'a cargo_metadata::camino::Utf8Path,
+    options: Options,
+}
+
+impl<'a> VersionHook<'a> {
+    /// Return the version hook configured in the workspace of `ctx`, if there is one.
+    pub fn from_config(ctx: &'a Context, options: Options) -> Option<Self> {
+        let (program, args) = ctx.base.config.version_hook.as_ref()?.split_first()?;
+        Some(VersionHook {
+            program,
+            args,
+            root: &ctx.base.root,
+            options,
+        })
+    }
+}
+
+impl VersionTransform for VersionHook<'_> {
+    fn transform(&self, proposed: &[ProposedVersion<'_>]) -> anyhow::Result<BTreeMap<String, semver::Version>> {
+        let input = serde_json::json!({
+            "dry_run": self.options.dry_run,
+            "crates": proposed
+                .iter()
+                .map(|p| serde_json::json!({
+                    "name": p.package.name,
+                    "current_version": p.package.version.to_string(),
+                    "new_version": p.new_version.to_string(),
+                }))
+                .collect::<Vec<_>>(),
+        });
+
+        let mut cmd = Command::new(self.program);
+        cmd.args(self.args).current_dir(self.root);
+        if self.options.verbose {
+            log::info!("Running version hook {:?}", cmd);
+        }
+        let output = hook::run("version hook", cmd.stdout(Stdio::piped()), &input)?;
+        if !output.status.success() {
+            bail!("The version hook '{}' failed with {}", self.program, output.status);
+        }
+        if output.stdout.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Default::default());
+        }
+        let versions: BTreeMap<String, String> = serde_json::from_slice(&output.stdout).with_context(|| {
+            format!(
+                "The version hook '{}' must print a JSON object with the versions to use by crate name",
+                self.program
+            )
+        })?;
+        versions
+            .into_iter()
+            .map(|(name, version)| {
+                let parsed = version.parse().with_context(|| {
+                    format!(
+                        "The version hook '{}' returned the invalid version '{}' for '{}'",
+                        self.program, version, name
+                    )
+                })?;
+                Ok((name, parsed))
+            })
+            .collect()
+    }
+}
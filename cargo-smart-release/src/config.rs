@@ -0,0 +1,550 @@
+//! Configuration as read from the `smart-release` table in `[workspace.metadata]` and `[package.metadata]`.
+use std::{collections::BTreeMap, convert::TryFrom};
+
+use cargo_metadata::Metadata;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::changelog::forge::Forge;
+
+/// The name of the table in the manifest metadata section holding our configuration.
+const TABLE_NAME: &str = "smart-release";
+
+/// Configuration affecting the entire workspace, read from `[workspace.metadata.smart-release]`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Workspace {
+    /// A program along with its arguments to receive the release plan as JSON on stdin right after all versions
+    /// were computed, but before anything is changed.
+    ///
+    /// If it exits with a non-zero status, the release is aborted.
+    pub policy_hook: Option<Vec<String>>,
+    /// A program along with its arguments to receive the versions computed for all crates to release as JSON on stdin,
+    /// which may print a JSON object with the versions to use instead by crate name to stdout, like
+    /// `{"foo": "2024.3.0"}`, to implement schemes like calendar versioning.
+    ///
+    /// Versions it returns must be greater than the current version of their crate.
+    pub version_hook: Option<Vec<String>>,
+    /// What to do if the version in the manifest of a crate to release is greater than the one computed by bumping
+    /// its latest published version.
+    pub manifest_version_ahead: ManifestVersionAhead,
+    /// Globs relative to the directory of each crate, like `tests/**`, matching files only needed for its development.
+    ///
+    /// If set, changes since the last release confined to these files and to the dev-dependencies in the manifest don't
+    /// make a crate need a release.
+    pub dev_paths: Vec<String>,
+    /// If set, a crate whose version in the manifest wasn't tagged yet doesn't need a release if the only change since the
+    /// tag of its previous version is the version in its manifest, as left behind by a version bump that wasn't released.
+    pub ignore_version_bumps: bool,
+    /// How to treat commits whose only change to a crate is to move the commits of git submodules within it.
+    pub submodule_updates: SubmoduleUpdates,
+    /// The maximum amount of crates receiving a safety bump to list in the body of a release commit message, with the
+    /// remaining ones summarized like `...and 12 more dependency updates`. All of them are listed if unset, and the
+    /// list is left out if it's 0.
+    pub max_listed_safety_bumps: Option<usize>,
+    /// Configuration for changelog generation, read from `[workspace.metadata.smart-release.changelog]`.
+    pub changelog: WorkspaceChangelog,
+}
+
+/// How to treat commits which only update the commits of git submodules within a crate.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SubmoduleUpdates {
+    /// Treat them like any other change.
+    #[default]
+    Change,
+    /// List them in their own `Submodule Updates` group in changelogs, but otherwise treat them like any other change.
+    Group,
+    /// Leave them out of changelogs, and don't release crates because of them.
+    Ignore,
+}
+
+/// How to reconcile a manifest version that was bumped by hand beyond the version smart-release would compute.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ManifestVersionAhead {
+    /// Release the version in the manifest as is.
+    #[default]
+    Respect,
+    /// Release the computed version instead, lowering the version in the manifest.
+    Override,
+}
+
+/// Workspace-wide configuration for changelog generation.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct WorkspaceChangelog {
+    /// Alternative names of conventional commit types mapped to their canonical name, like `feature = "feat"`.
+    pub type_aliases: BTreeMap<String, String>,
+    /// Conventional commit scopes mapped to the names of the crates they affect, like `macros = ["foo-macros", "foo"]`.
+    ///
+    /// Changes with such a scope are attributed to all of these crates, in addition to the crates whose files they change.
+    pub scopes: BTreeMap<String, Vec<String>>,
+    /// If set, render changelog entries with their conventional commit prefix, like `feat: add X`, even though the
+    /// heading of their group conveys the type already.
+    pub keep_type_prefix: bool,
+    /// If set, changes whose commit message has a `Highlight: true` footer are additionally listed in a `Highlights`
+    /// section at the top of their release.
+    pub highlights: bool,
+    /// The maximum amount of entries to list per group of changes in a release, keyed by conventional commit type,
+    /// like `fix = 20`. Changes that don't follow the convention are grouped under `other`.
+    ///
+    /// Remaining entries are summarized with their count. Groups without a limit list all of their entries.
+    pub max_entries: BTreeMap<String, usize>,
+    /// Icons like emoji to put in front of the headings of groups of changes, keyed by conventional commit type like
+    /// `feat = "🚀"`, or by `highlights`, `breaking` and `authors` for the groups of highlighted and breaking changes and
+    /// the list of authors.
+    pub icons: BTreeMap<String, String>,
+    /// If set, end each release with the names of all authors of its changes, sorted and without duplicates.
+    pub authors: bool,
+    /// Handles to show instead of the names of authors, keyed by their email address, like
+    /// `"sebastian.thiel@icloud.com" = "@Byron"`.
+    pub author_handles: BTreeMap<String, String>,
+    /// The kind of forge hosting the repository, like `gitlab`, for self-hosted instances whose kind can't be derived
+    /// from the host name of the `origin` remote.
+    pub forge: Option<Forge>,
+    /// The name of the remote whose URL is used to link to issues and commits, like `upstream` if `origin` is a fork, or
+    /// `origin` if unset.
+    pub link_remote: Option<String>,
+    /// What to do with changes whose commit message has an empty subject line.
+    pub empty_subjects: EmptySubjects,
+    /// Whether to list changes that update dependencies, like `build(deps): bump X`, in their own `Dependencies` group.
+    pub dependency_updates: DependencyUpdates,
+    /// If set, changes in the `Dependencies` group that bump the same dependency, like `bump serde from 1.0.1 to 1.0.2`,
+    /// are listed as a single change from the oldest to the newest version, like `bumped serde from 1.0.1 to 1.0.5`.
+    pub coalesce_dependency_bumps: bool,
+    /// If set, changes reverted by a later change of the same release are left out along with the revert, as indicated by
+    /// a `This reverts commit <id>.` line like `git revert` writes it. Changes reapplied after their revert, by reverting
+    /// the revert or with a commit of the same patch, are listed once with their original commit.
+    pub net_reverts: bool,
+    /// If set, render the `Dependencies` group as `Updated N dependencies` if it has more entries than this.
+    pub collapse_dependency_updates: Option<usize>,
+    /// If not 0, list up to this amount of top-level files and directories each change touched after its title.
+    pub changed_paths: usize,
+    /// Crate directories and extra paths with more components than this, like `crates/foo` with 2, reuse their entry of
+    /// the previously traversed commit if their top-level directory didn't change instead of looking up the trees below
+    /// it for every commit. Defaults to 1, and a value larger than the depth of all paths looks them up in every commit.
+    pub path_lookup_threshold: Option<usize>,
+    /// If set, a `!` in the subject line like `feat!: X` only marks a breaking change if the commit message also has a
+    /// `BREAKING CHANGE` footer, to avoid breaking changes by accident. A `!` without the footer is warned about.
+    pub require_breaking_footer: bool,
+    /// Rules to classify changes whose commit message doesn't follow the conventional commit format, tried in order on
+    /// their subject line until one matches.
+    pub classify: Vec<ClassifyRule>,
+    /// If set, classify changes whose subject line starts with an emoji of the gitmoji convention, like `✨ add X`, or its
+    /// code, like `:sparkles: add X`, if none of the `classify` rules matches.
+    pub gitmoji: bool,
+    /// The conventional commit type and bump of changes starting with the gitmoji or gitmoji code used as key, in
+    /// addition to the defaults of the gitmoji convention and taking precedence over them.
+    pub gitmoji_types: BTreeMap<String, GitmojiType>,
+    /// How to classify changes whose commit message follows the conventional commit format but has an unknown type, like
+    /// `wip` in `wip(parser): X`. Their scope is used to attribute them to crates either way.
+    pub unknown_types: UnknownTypes,
+    /// How to derive the anchors of release headings that the table of contents links to.
+    pub anchors: Anchors,
+    /// The line endings to write changelogs with.
+    pub line_endings: LineEndings,
+    /// The tokens of trailers to keep in the bodies of changes when stripping them with `--changelog-strip-trailers`,
+    /// like `Co-authored-by`.
+    pub keep_trailers: Vec<String>,
+    /// The text to put into sections of releases without any changes to list, like ones of crates released only because
+    /// of their dependencies. If unset, such sections are left out.
+    pub no_changes_text: Option<String>,
+    /// Translations of the headings and texts rendered into changelogs, keyed by a locale like `de` which is selected with
+    /// `--locale`. Each maps a conventional commit type like `feat` to the heading of its group, or `unreleased`,
+    /// `highlights`, `breaking` and `authors` to the respective headings, or `no-changes` to the text of releases without
+    /// changes, like `[changelog.translations.de] feat = "Neue Funktionen"`. The changes themselves aren't translated.
+    pub translations: BTreeMap<String, BTreeMap<String, String>>,
+    /// Placeholders like `TODO` or `<!-- fill me -->` which must not appear in the hand-written parts of the changelogs
+    /// of crates to release. Releases fail if one of them is found.
+    pub forbidden_markers: Vec<String>,
+    /// The conventional commit type of changes squash-merged from GitHub pull requests with the label used as key and
+    /// whether they are breaking, like `enhancement = { section = "feat" }`, in addition to the defaults and taking
+    /// precedence over them.
+    /// Labels are compared case-insensitively, and they are only used with `--notes-from-pr-labels`.
+    pub pr_labels: BTreeMap<String, PrLabel>,
+    /// A line to render at the top of each release with changes, in which `{commits}` and `{breaking}` are replaced by
+    /// the amount of its changes and of its breaking ones, and a conventional commit type like `{feat}` by the amount of
+    /// changes in its group, like `"{commits} commits, {feat} features, {fix} fixes, {breaking} breaking"`.
+    pub summary: Option<String>,
+}
+
+/// The line endings to write changelogs with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LineEndings {
+    /// Use the line endings most lines of an existing changelog end with, or `\n` for new changelogs.
+    #[default]
+    Auto,
+    /// Always end lines with `\n`.
+    Lf,
+    /// Always end lines with `\r\n`.
+    Crlf,
+}
+
+/// How to derive the anchors of release headings, which depends on where the changelog is rendered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Anchors {
+    /// Link to the anchors GitHub and most other forges generate.
+    #[default]
+    Github,
+    /// Link to the anchors rustdoc generates, for changelogs included in the documentation of a crate on docs.rs.
+    Rustdoc,
+    /// Put an HTML anchor before each heading to link to, for renderers generating none or unknown anchors.
+    Explicit,
+}
+
+/// A rule to classify changes by the subject line of their commit message, like `[FIX] crash on start`, read from
+/// `[[workspace.metadata.smart-release.changelog.classify]]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RawClassifyRule")]
+pub struct ClassifyRule {
+    /// The regular expression to find in the subject line. If it matches at the start, the match is removed from the
+    /// title and becomes its prefix.
+    pub pattern: regex::Regex,
+    /// The conventional commit type to treat matching changes as, like `fix`, which determines their group.
+    pub section: String,
+    /// The bump matching changes need, with `major` marking them as breaking.
+    pub bump: Option<Bump>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawClassifyRule {
+    pattern: String,
+    section: String,
+    bump: Option<Bump>,
+}
+
+impl TryFrom<RawClassifyRule> for ClassifyRule {
+    type Error = String;
+
+    fn try_from(RawClassifyRule { pattern, section, bump }: RawClassifyRule) -> Result<Self, Self::Error> {
+        Ok(ClassifyRule {
+            pattern: regex::Regex::new(&pattern).map_err(|err| format!("invalid pattern '{}': {}", pattern, err))?,
+            section,
+            bump,
+        })
+    }
+}
+
+/// The conventional commit type and bump of changes whose subject line starts with a gitmoji, read from
+/// `[workspace.metadata.smart-release.changelog.gitmoji-types]`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GitmojiType {
+    /// The conventional commit type to treat matching changes as, like `fix`, which determines their group.
+    pub section: String,
+    /// The bump matching changes need, with `major` marking them as breaking.
+    pub bump: Option<Bump>,
+}
+
+/// The conventional commit type of changes squash-merged from pull requests with a label and whether they are breaking,
+/// read from `[workspace.metadata.smart-release.changelog.pr-labels]`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrLabel {
+    /// The conventional commit type to treat matching changes as, like `fix`, which determines their group. If unset,
+    /// the type is taken from the commit message.
+    pub section: Option<String>,
+    /// If set, matching changes are breaking. Labels never make changes non-breaking.
+    pub breaking: bool,
+}
+
+/// The default labels of GitHub repositories, along with `breaking`, and the conventional commit type they correspond to
+/// and whether they are breaking.
+const PR_LABELS: &[(&str, Option<&str>, bool)] = &[
+    ("breaking", None, true),
+    ("breaking change", None, true),
+    ("bug", Some("fix"), false),
+    ("documentation", Some("docs"), false),
+    ("enhancement", Some("feat"), false),
+];
+
+/// The gitmojis and their codes of the gitmoji convention with the conventional commit type and bump they correspond to.
+const GITMOJI_TYPES: &[(&str, &str, &str, Option<Bump>)] = &[
+    ("✨", ":sparkles:", "feat", Some(Bump::Minor)),
+    ("🎉", ":tada:", "feat", Some(Bump::Minor)),
+    ("💥", ":boom:", "feat", Some(Bump::Major)),
+    ("🐛", ":bug:", "fix", Some(Bump::Patch)),
+    ("🚑", ":ambulance:", "fix", Some(Bump::Patch)),
+    ("🩹", ":adhesive_bandage:", "fix", Some(Bump::Patch)),
+    ("🔒", ":lock:", "fix", Some(Bump::Patch)),
+    ("⏪", ":rewind:", "revert", Some(Bump::Patch)),
+    ("⚡", ":zap:", "perf", Some(Bump::Patch)),
+    ("📝", ":memo:", "docs", None),
+    ("💡", ":bulb:", "docs", None),
+    ("🎨", ":art:", "style", None),
+    ("🚨", ":rotating_light:", "style", None),
+    ("♻", ":recycle:", "refactor", None),
+    ("🔥", ":fire:", "refactor", None),
+    ("🚚", ":truck:", "refactor", None),
+    ("✅", ":white_check_mark:", "test", None),
+    ("🧪", ":test_tube:", "test", None),
+    ("🔧", ":wrench:", "chore", None),
+    ("🔨", ":hammer:", "chore", None),
+    ("👷", ":construction_worker:", "chore", None),
+    ("💚", ":green_heart:", "chore", None),
+    ("⬆", ":arrow_up:", "chore", None),
+    ("⬇", ":arrow_down:", "chore", None),
+    ("📌", ":pushpin:", "chore", None),
+    ("➕", ":heavy_plus_sign:", "chore", None),
+    ("➖", ":heavy_minus_sign:", "chore", None),
+    ("🔖", ":bookmark:", "chore", None),
+];
+
+impl WorkspaceChangelog {
+    /// Return rules to classify changes by the gitmoji or gitmoji code their subject line starts with, using the
+    /// `gitmoji_types` along with the defaults of the gitmoji convention, or no rules if `gitmoji` isn't set.
+    pub fn gitmoji_rules(&self) -> Vec<ClassifyRule> {
+        if !self.gitmoji {
+            return Vec::new();
+        }
+        // Many gitmojis are written with or without the emoji variation selector, which is thus optional in any case.
+        let without_variation_selector = |gitmoji: &str| gitmoji.replace('\u{fe0f}', "");
+        let mut types: BTreeMap<_, _> = GITMOJI_TYPES
+            .iter()
+            .flat_map(|(emoji, code, section, bump)| {
+                let ty = GitmojiType {
+                    section: (*section).to_owned(),
+                    bump: *bump,
+                };
+                vec![(emoji.to_string(), ty.clone()), (code.to_string(), ty)]
+            })
+            .collect();
+        types.extend(
+            self.gitmoji_types
+                .iter()
+                .map(|(gitmoji, ty)| (without_variation_selector(gitmoji), ty.clone())),
+        );
+        types
+            .into_iter()
+            .map(|(gitmoji, GitmojiType { section, bump })| ClassifyRule {
+                pattern: regex::Regex::new(&format!(r"^{}\x{{fe0f}}?(\s+|$)", regex::escape(&gitmoji)))
+                    .expect("escaped patterns are valid"),
+                section,
+                bump,
+            })
+            .collect()
+    }
+
+    /// Return the `pr_labels` along with the defaults for labels not configured, keyed by their lowercase name.
+    pub fn pr_label_rules(&self) -> BTreeMap<String, PrLabel> {
+        let mut labels: BTreeMap<_, _> = PR_LABELS
+            .iter()
+            .map(|(label, section, breaking)| {
+                (
+                    (*label).to_owned(),
+                    PrLabel {
+                        section: section.map(ToOwned::to_owned),
+                        breaking: *breaking,
+                    },
+                )
+            })
+            .collect();
+        labels.extend(
+            self.pr_labels
+                .iter()
+                .map(|(label, rule)| (label.to_lowercase(), rule.clone())),
+        );
+        labels
+    }
+}
+
+/// Where to list changes that update dependencies, as indicated by the `deps` type or scope of their commit message.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DependencyUpdates {
+    /// List them in their own `Dependencies` group.
+    #[default]
+    Group,
+    /// List them along with other changes of their type, like `chore`.
+    Keep,
+}
+
+/// How to classify changes whose conventional commit type isn't known, even after resolving `type-aliases`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnknownTypes {
+    /// List them in the `Other` group.
+    #[default]
+    Other,
+    /// Classify them like changes whose commit message doesn't follow the conventional commit format, using their entire
+    /// subject line as title and the `classify` rules to find their type.
+    Unconventional,
+}
+
+/// How to render changes whose commit message has an empty subject line, and thus no title.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmptySubjects {
+    /// Leave them out of the changelog.
+    #[default]
+    Skip,
+    /// Use the first non-empty line of their body as title, or leave them out if there is no body.
+    FirstBodyLine,
+}
+
+/// Configuration affecting a single crate, read from `[package.metadata.smart-release]`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Package {
+    /// Additional arguments to pass to `cargo publish` for this crate, like `["--no-default-features"]`, instead of
+    /// the ones given with `--publish-arg`.
+    pub publish_args: Option<Vec<String>>,
+    /// How to bump the version of this crate before 1.0 if one of its dependencies has a breaking change, read from
+    /// `"pre-1.0-breaking"` which has to be quoted in TOML.
+    #[serde(rename = "pre-1.0-breaking")]
+    pub pre_1_0_breaking: PreOneBreaking,
+    /// The smallest bump to apply when releasing this crate, raising `patch` bumps to `minor` with `min-bump = "minor"`.
+    ///
+    /// Keeping the manifest version with `keep` isn't affected.
+    pub min_bump: Option<Bump>,
+    /// Glob patterns relative to the crate's directory, like `proto/**` or `include/*.h`, of files whose changes are
+    /// breaking regardless of the commit messages. If any of them changed since the latest release, smaller bumps are
+    /// raised to a breaking one.
+    pub breaking_paths: Vec<String>,
+    /// The bump to raise smaller bumps to if `breaking-paths` changed, either `minor` or `major`, instead of the one which
+    /// is breaking for the current version.
+    pub breaking_paths_bump: Option<Bump>,
+    /// If `false`, never publish this crate automatically because a crate depending on it is released, and fail such
+    /// releases instead. Defaults to `true`.
+    pub auto_publish: Option<bool>,
+    /// Files besides the manifest which contain the version of this crate, like `README.md`, to update along with it.
+    pub bump_files: Vec<BumpFile>,
+    /// Configuration for changelog generation, read from `[package.metadata.smart-release.changelog]`.
+    pub changelog: Changelog,
+}
+
+/// Files containing the version of a crate, read from `[[package.metadata.smart-release.bump-files]]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RawBumpFile")]
+pub struct BumpFile {
+    /// A glob pattern relative to the crate's directory, like `README.md` or `docs/*.md`, which has to match at least
+    /// one file.
+    pub path: String,
+    /// A regular expression in which `{version}` stands for the current version of the crate, like
+    /// `my-crate = "{version}"`, to only replace the version where it matches, or `None` to replace it everywhere.
+    pub pattern: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBumpFile {
+    path: String,
+    pattern: Option<String>,
+}
+
+impl TryFrom<RawBumpFile> for BumpFile {
+    type Error = String;
+
+    fn try_from(RawBumpFile { path, pattern }: RawBumpFile) -> Result<Self, Self::Error> {
+        if let Some(pattern) = &pattern {
+            if pattern.matches(VERSION_PLACEHOLDER).count() != 1 {
+                return Err(format!(
+                    "pattern '{}' has to contain the {} placeholder exactly once",
+                    pattern, VERSION_PLACEHOLDER
+                ));
+            }
+            version_regex(pattern, "0.0.0").map_err(|err| format!("invalid pattern '{}': {}", pattern, err))?;
+        }
+        Ok(BumpFile { path, pattern })
+    }
+}
+
+/// The placeholder for the current version of a crate in the `pattern` of a [`BumpFile`].
+const VERSION_PLACEHOLDER: &str = "{version}";
+
+/// Return a regular expression for `pattern` which has `version` in place of its `{version}` placeholder, in the
+/// capture group named `version`.
+pub fn version_regex(pattern: &str, version: &str) -> Result<regex::Regex, regex::Error> {
+    regex::Regex::new(&pattern.replace(VERSION_PLACEHOLDER, &format!("(?P<version>{})", regex::escape(version))))
+}
+
+/// The component of a version before 1.0 to bump to signal a breaking change, like `0.3.1` to `0.4.0` with `minor`.
+///
+/// With `patch`, the change becomes `0.3.1` to `0.3.2` which `cargo` considers compatible, so dependents would pick it up
+/// automatically. Unless `--no-conservative-pre-release-version-handling` is set, the version requirements of dependents
+/// within the workspace are updated to the new patch version nonetheless.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreOneBreaking {
+    #[default]
+    Minor,
+    Patch,
+}
+
+/// A version component to bump, ordered from the smallest to the largest bump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    /// Parse a bump specification as given on the command-line, like `minor`, returning `None` if it's not a bump level.
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        Some(match spec {
+            "patch" => Bump::Patch,
+            "minor" => Bump::Minor,
+            "major" => Bump::Major,
+            _ => return None,
+        })
+    }
+
+    /// The bump specification as given on the command-line.
+    pub fn as_spec(&self) -> &'static str {
+        match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+}
+
+/// Per-crate configuration for changelog generation.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Changelog {
+    /// Paths relative to the crate's directory, like `../proto`, whose changes are attributed to the crate
+    /// as if they were made in the crate's directory.
+    pub extra_paths: Vec<String>,
+    /// The name of the changelog file in the crate's directory, like `RELEASES.md`, or `CHANGELOG.md` if unset.
+    pub file_name: Option<String>,
+    /// A directory relative to the crate's directory, like `changelogs`, to write one file per release into instead of a
+    /// single changelog file. Files are named after their version, like `1.2.0.md`, and unreleased changes go into
+    /// `unreleased.md` until they are released.
+    pub directory: Option<String>,
+    /// The text to put into sections of releases without any changes to list, overriding the one of the workspace.
+    pub no_changes_text: Option<String>,
+}
+
+/// Read the workspace configuration from `meta`, or return the default configuration if there is none.
+///
+/// The rules for classifying changes by their gitmoji are appended to the `classify` rules of the changelog configuration.
+pub fn workspace(meta: &Metadata) -> anyhow::Result<Workspace> {
+    let mut config: Workspace = from_metadata(&meta.workspace_metadata)
+        .map_err(|err| err.context("Invalid [workspace.metadata.smart-release]"))?;
+    let gitmoji_rules = config.changelog.gitmoji_rules();
+    config.changelog.classify.extend(gitmoji_rules);
+    Ok(config)
+}
+
+/// Read the configuration of `package`, or return the default configuration if there is none.
+pub fn package(package: &cargo_metadata::Package) -> anyhow::Result<Package> {
+    from_metadata(&package.metadata).map_err(|err| {
+        err.context(format!(
+            "Invalid [package.metadata.smart-release] in manifest of '{}'",
+            package.name
+        ))
+    })
+}
+
+fn from_metadata<T: DeserializeOwned + Default>(metadata: &serde_json::Value) -> anyhow::Result<T> {
+    Ok(match metadata.get(TABLE_NAME) {
+        Some(table) => serde_json::from_value(table.clone())?,
+        None => T::default(),
+    })
+}
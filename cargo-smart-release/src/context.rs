@@ -1,27 +1,103 @@
+use std::{collections::BTreeSet, convert::TryFrom};
+
+use anyhow::bail;
 use cargo_metadata::{
-    camino::{Utf8Path, Utf8PathBuf},
+    camino::{Utf8Component, Utf8Path, Utf8PathBuf},
     Metadata, Package,
 };
 use git_repository as git;
 use git_repository::prelude::CacheAccessExt;
 
+use crate::{
+    traverse::{SkipReason, Skipped},
+    utils::{is_publishable, is_workspace_member, package_by_id, workspace_package_by_manifest_path},
+};
+
 pub struct Context {
     pub root: Utf8PathBuf,
     pub meta: Metadata,
     pub repo: git::Easy,
+    /// The store of references private to the linked worktree the workspace is in, like `HEAD`, or `None` if it's in the
+    /// main worktree.
+    pub worktree_refs: Option<git::refs::file::Store>,
     pub crate_names: Vec<String>,
+    /// The workspace members which were left out of `crate_names` when selecting all of them.
+    pub skipped: Vec<Skipped>,
+    pub config: crate::config::Workspace,
+    /// The names of crates which are considered unchanged since their last release as all their changes were made by
+    /// commits given with `--exclude-commit`.
+    pub changed_by_excluded_commits_only: BTreeSet<String>,
 }
 
 impl Context {
     pub fn new(crate_names: Vec<String>) -> anyhow::Result<Self> {
-        let meta = cargo_metadata::MetadataCommand::new().exec()?;
+        Self::with_crate_names(|meta| Ok((fill_in_root_crate_if_needed(meta, crate_names)?, Vec::new())))
+    }
+
+    /// Create a context for all publishable workspace members, that is those without `publish = false`, except for
+    /// the ones named in `excluded`.
+    pub fn for_workspace(excluded: &[String]) -> anyhow::Result<Self> {
+        Self::with_crate_names(|meta| {
+            if let Some(unknown) = excluded.iter().find(|name| !is_workspace_member(meta, name)) {
+                bail!("Excluded crate '{}' is not a member of the workspace", unknown);
+            }
+            let mut crate_names = Vec::new();
+            let mut skipped = Vec::new();
+            for package in meta.workspace_members.iter().map(|id| package_by_id(meta, id)) {
+                if !is_publishable(package) {
+                    skipped.push(Skipped::new(&package.name, SkipReason::Unpublishable));
+                } else if excluded.contains(&package.name) {
+                    skipped.push(Skipped::new(&package.name, SkipReason::Excluded));
+                } else {
+                    crate_names.push(package.name.clone());
+                }
+            }
+            if crate_names.is_empty() {
+                bail!("There are no publishable workspace members to release");
+            }
+            Ok((crate_names, skipped))
+        })
+    }
+
+    /// Create a context without any crates to operate on, for commands that look at the workspace as a whole.
+    pub fn for_introspection() -> anyhow::Result<Self> {
+        Self::with_crate_names(|_| Ok((Vec::new(), Vec::new())))
+    }
+
+    /// Like [`Context::new()`], but read the metadata of the workspace with `cmd`.
+    #[cfg(test)]
+    pub(crate) fn with_metadata_command(
+        cmd: cargo_metadata::MetadataCommand,
+        crate_names: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        Self::with_crate_names_and_metadata_command(cmd, |meta| {
+            Ok((fill_in_root_crate_if_needed(meta, crate_names)?, Vec::new()))
+        })
+    }
+
+    fn with_crate_names(
+        crate_names: impl FnOnce(&Metadata) -> anyhow::Result<(Vec<String>, Vec<Skipped>)>,
+    ) -> anyhow::Result<Self> {
+        Self::with_crate_names_and_metadata_command(cargo_metadata::MetadataCommand::new(), crate_names)
+    }
+
+    fn with_crate_names_and_metadata_command(
+        cmd: cargo_metadata::MetadataCommand,
+        crate_names: impl FnOnce(&Metadata) -> anyhow::Result<(Vec<String>, Vec<Skipped>)>,
+    ) -> anyhow::Result<Self> {
+        let meta = cmd.exec()?;
         let root = meta.workspace_root.clone();
-        let repo = git::discover(&root)?;
+        let (repo, worktree_refs) = open_repository(&root)?;
+        let (crate_names, skipped) = crate_names(&meta)?;
         Ok(Context {
             root,
             repo: repo.into_easy().apply_environment()?,
+            worktree_refs,
+            config: crate::config::workspace(&meta)?,
+            crate_names,
+            skipped,
             meta,
-            crate_names: fill_in_root_crate_if_needed(crate_names)?,
+            changed_by_excluded_commits_only: Default::default(),
         })
     }
 
@@ -39,22 +115,93 @@ impl Context {
             dir.into()
         }
     }
+
+    /// Return the `extra-paths` configured for `p` relative to the repository root, after resolving them relative to the
+    /// crate's directory.
+    pub(crate) fn extra_repo_relative_paths(&self, p: &Package) -> anyhow::Result<Vec<Utf8PathBuf>> {
+        let crate_dir = p.manifest_path.parent().expect("parent of a file is always present");
+        let mut paths = Vec::new();
+        for extra_path in crate::config::package(p)?.changelog.extra_paths {
+            let mut normalized = Utf8PathBuf::new();
+            for component in crate_dir.join(&extra_path).components() {
+                match component {
+                    Utf8Component::CurDir => {}
+                    Utf8Component::ParentDir => {
+                        normalized.pop();
+                    }
+                    other => normalized.push(other.as_str()),
+                }
+            }
+            match normalized.strip_prefix(&self.root) {
+                Ok(path) if !path.as_os_str().is_empty() => paths.push(path.to_owned()),
+                _ => bail!(
+                    "Extra path '{}' of crate '{}' must point to a directory or file within the workspace",
+                    extra_path,
+                    p.name
+                ),
+            }
+        }
+        Ok(paths)
+    }
 }
 
-fn fill_in_root_crate_if_needed(crate_names: Vec<String>) -> anyhow::Result<Vec<String>> {
+/// Open the repository containing `root`, which may also be a linked worktree as created by `git worktree add`, whose
+/// `.git` file points to a git directory private to the worktree.
+///
+/// In that case, the repository is opened at the common git directory with `root` as work tree, and the store of references
+/// private to the worktree, like `HEAD`, is returned as well.
+fn open_repository(root: &Utf8Path) -> anyhow::Result<(git::Repository, Option<git::refs::file::Store>)> {
+    for dir in root.ancestors() {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            break;
+        }
+        if !dot_git.is_file() {
+            continue;
+        }
+        let git_dir = match std::fs::read_to_string(&dot_git)?.trim().strip_prefix("gitdir:") {
+            Some(path) => dir.join(path.trim()),
+            None => bail!("The .git file at '{}' doesn't point to a git directory", dot_git),
+        };
+        let common_dir = match std::fs::read_to_string(git_dir.join("commondir")) {
+            Ok(path) => git_dir.join(path.trim()),
+            // submodules have a .git file as well, but their git directory isn't shared with other worktrees.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => git_dir.clone(),
+            Err(err) => return Err(err.into()),
+        };
+        let mut repo = git::Repository::try_from(git::Path::Repository(common_dir.clone().into_std_path_buf()))?;
+        repo.work_tree = Some(dir.into());
+        let worktree_refs = (git_dir != common_dir)
+            .then(|| git::refs::file::Store::at(git_dir.into_std_path_buf(), git::refs::file::WriteReflog::Normal));
+        return Ok((repo, worktree_refs));
+    }
+    Ok((git::discover(root)?, None))
+}
+
+/// If no `crate_names` are given, use the crate of the nearest manifest in the current directory or its parents,
+/// similar to how `cargo` finds the package to operate on.
+fn fill_in_root_crate_if_needed(meta: &Metadata, crate_names: Vec<String>) -> anyhow::Result<Vec<String>> {
     Ok(if crate_names.is_empty() {
         let current_dir = std::env::current_dir()?;
-        let manifest = current_dir.join("Cargo.toml");
-        let dir_name = current_dir
-            .file_name()
-            .expect("a valid directory with a name")
-            .to_str()
-            .expect("directory is UTF8 representable");
-        let crate_name = if manifest.is_file() {
-            let manifest = cargo_toml::Manifest::from_path(manifest)?;
-            manifest.package.map_or(dir_name.to_owned(), |p| p.name)
-        } else {
-            dir_name.to_owned()
+        let crate_name = match current_dir
+            .ancestors()
+            .find_map(|dir| workspace_package_by_manifest_path(meta, &dir.join("Cargo.toml")))
+        {
+            Some(package) => package.name.clone(),
+            None => {
+                let manifest = current_dir.join("Cargo.toml");
+                let dir_name = current_dir
+                    .file_name()
+                    .expect("a valid directory with a name")
+                    .to_str()
+                    .expect("directory is UTF8 representable");
+                if manifest.is_file() {
+                    let manifest = cargo_toml::Manifest::from_path(manifest)?;
+                    manifest.package.map_or(dir_name.to_owned(), |p| p.name)
+                } else {
+                    dir_name.to_owned()
+                }
+            }
         };
         log::warn!(
             "Using '{}' as crate name as no one was provided. Specify one if this isn't correct",
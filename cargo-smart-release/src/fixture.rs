@@ -0,0 +1,101 @@
+//! Workspaces and packages to run tests against.
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// A git repository with a copy of the `tri-depth-workspace` fixture in a temporary directory, whose crates `c`, `b`
+/// and `a` depend on each other in that order, with all files committed.
+///
+/// It's removed when dropped.
+pub struct Workspace {
+    pub root: PathBuf,
+}
+
+impl Workspace {
+    /// Create the workspace in a directory unique to the test named `name`.
+    pub fn tri_depth(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("smart-release-fixture-{}-{}", std::process::id(), name));
+        std::fs::remove_dir_all(&root).ok();
+        copy_dir(&tri_depth_fixture(), &root);
+        let ws = Workspace { root };
+        ws.write(".gitignore", "target\n");
+        ws.git(&["init", "-q"]);
+        ws.commit("initial");
+        ws
+    }
+
+    /// Return a context of the workspace operating on `crate_names`.
+    pub fn context(&self, crate_names: &[&str]) -> crate::Context {
+        let mut cmd = cargo_metadata::MetadataCommand::new();
+        cmd.manifest_path(self.root.join("Cargo.toml"));
+        crate::Context::with_metadata_command(cmd, crate_names.iter().map(|name| name.to_string()).collect())
+            .expect("valid workspace")
+    }
+
+    /// Write `content` to the file at `path` relative to the workspace root.
+    pub fn write(&self, path: &str, content: &str) {
+        std::fs::write(self.root.join(path), content).expect("writable");
+    }
+
+    /// Replace the manifest of the crate named `crate_name` with the one of the fixture followed by `toml`, like a
+    /// `[package.metadata.smart-release]` table, without committing it.
+    pub fn configure(&self, crate_name: &str, toml: &str) {
+        let path = format!("{}/Cargo.toml", crate_name);
+        let manifest = std::fs::read_to_string(tri_depth_fixture().join(&path)).expect("readable");
+        self.write(&path, &format!("{}\n{}\n", manifest, toml));
+    }
+
+    /// Commit all changes with the given `message`, allowing empty commits.
+    pub fn commit(&self, message: &str) {
+        self.git(&["add", "-A"]);
+        self.git(&["commit", "-q", "--allow-empty", "-m", message]);
+    }
+
+    /// Run git with `args` in the workspace, failing if it doesn't succeed, and return its trimmed output.
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args([
+                "-c",
+                "user.name=a",
+                "-c",
+                "user.email=a@example.com",
+                "-c",
+                "commit.gpgsign=false",
+            ])
+            .args(args)
+            .current_dir(&self.root)
+            .output()
+            .expect("git can be launched");
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).expect("utf8").trim().to_owned()
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.root).ok();
+    }
+}
+
+fn tri_depth_fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tri-depth-workspace")
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).expect("creatable");
+    for entry in std::fs::read_dir(from).expect("readable") {
+        let entry = entry.expect("readable");
+        let to = to.join(entry.file_name());
+        if entry.file_type().expect("known type").is_dir() {
+            copy_dir(&entry.path(), &to);
+        } else {
+            std::fs::copy(entry.path(), to).expect("copyable");
+        }
+    }
+}
@@ -1,15 +1,63 @@
-use std::process::Command;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    process::{Command, Stdio},
+};
 
 use anyhow::{anyhow, bail};
-use cargo_metadata::Package;
-use git_repository::{bstr::ByteSlice, easy::object, prelude::ReferenceAccessExt};
+use cargo_metadata::{camino::Utf8Path, Package};
+use git_repository::{
+    bstr::ByteSlice,
+    easy::{head, object},
+    hash::ObjectId,
+    objs::{tree, TreeRefIter},
+    prelude::{ObjectAccessExt, ObjectIdExt, ReferenceAccessExt},
+    refs::{Target, TargetRef},
+};
+use semver::Version;
 
-use crate::utils::{component_to_bytes, tag_name};
+use crate::{
+    config::SubmoduleUpdates,
+    utils::{component_to_bytes, parse_tag_version, tag_name, tag_prefix},
+};
+
+type Oid<'repo> = git_repository::easy::Oid<'repo, git_repository::Easy>;
+
+/// Return the `HEAD` of the worktree the workspace is in, which is private to it if it's a linked worktree.
+pub fn head(ctx: &crate::Context) -> anyhow::Result<git_repository::easy::Head<'_, git_repository::Easy>> {
+    let worktree_refs = match &ctx.worktree_refs {
+        Some(store) => store,
+        None => return Ok(ctx.repo.head()?),
+    };
+    Ok(match worktree_refs.find_loose("HEAD")?.target {
+        Target::Symbolic(branch) => match ctx.repo.try_find_reference(branch.to_partial())? {
+            Some(r) => head::Kind::Symbolic(r.detach()),
+            None => head::Kind::Unborn(branch),
+        },
+        Target::Peeled(target) => head::Kind::Detached { target, peeled: None },
+    }
+    .attach(&ctx.repo))
+}
 
 pub fn has_changed_since_last_release(package: &Package, ctx: &crate::Context, verbose: bool) -> anyhow::Result<bool> {
     let version_tag_name = tag_name(package, &package.version.to_string(), &ctx.repo);
     let mut tag_ref = match ctx.repo.try_find_reference(&version_tag_name)? {
         None => {
+            if ctx.config.ignore_version_bumps {
+                if let Some((previous_tag_name, previous_target)) = previous_release_tag(package, ctx)? {
+                    if changes_are_version_only(ctx, package, previous_target)? {
+                        if verbose {
+                            log::info!(
+                                "Package {} wasn't tagged with {} yet, but only its version changed since {} and thus doesn't need a release",
+                                package.name,
+                                version_tag_name,
+                                previous_tag_name
+                            );
+                        }
+                        return Ok(false);
+                    }
+                }
+            }
             if verbose {
                 log::info!(
                     "Package {} wasn't tagged with {} yet and thus needs a release",
@@ -22,13 +70,21 @@ pub fn has_changed_since_last_release(package: &Package, ctx: &crate::Context, v
         Some(r) => r,
     };
     let repo_relative_crate_dir = ctx.repo_relative_path(package);
-    Ok(match ctx.repo.head()?.into_fully_peeled_id() {
+    Ok(match head(ctx)?.into_fully_peeled_id() {
         Some(c) => {
             let current_commit = c?;
             let released_target = tag_ref.peel_to_id_in_place()?;
 
-            match repo_relative_crate_dir {
-                None => current_commit != released_target,
+            let changed = match repo_relative_crate_dir {
+                None if current_commit == released_target => false,
+                None => {
+                    let tree_entry = |commit: &Oid<'_>| -> anyhow::Result<TreeEntry> {
+                        let tree = commit.object()?.peel_to_kind(object::Kind::Tree)?.into_tree();
+                        Ok((tree.id, tree::EntryMode::Tree))
+                    };
+                    let (released, current) = (tree_entry(&released_target)?, tree_entry(&current_commit)?);
+                    !submodule_updates_are_ignored(ctx, package, &version_tag_name, released, current, verbose)?
+                }
                 Some(dir) => {
                     let components = dir.components().map(component_to_bytes);
                     let current_dir_id = current_commit
@@ -46,14 +102,611 @@ pub fn has_changed_since_last_release(package: &Package, ctx: &crate::Context, v
                         .expect("path must exist as it was supposedly released there")
                         .oid;
 
-                    released_dir_id != current_dir_id
+                    let dir_changed = released_dir_id != current_dir_id
+                        && !submodule_updates_are_ignored(
+                            ctx,
+                            package,
+                            &version_tag_name,
+                            (released_dir_id, tree::EntryMode::Tree),
+                            (current_dir_id, tree::EntryMode::Tree),
+                            verbose,
+                        )?;
+                    let dir_changed = if dir_changed && changes_are_dev_only(ctx, released_dir_id, current_dir_id)? {
+                        if verbose {
+                            log::info!(
+                                "Ignoring changes of {} since {} as they only affect its development",
+                                package.name,
+                                version_tag_name
+                            );
+                        }
+                        false
+                    } else {
+                        dir_changed
+                    };
+                    let mut extra_path_changed = false;
+                    if !dir_changed {
+                        for path in ctx.extra_repo_relative_paths(package)? {
+                            if path_entry_id(&current_commit, &path)? != path_entry_id(&released_target, &path)? {
+                                extra_path_changed = true;
+                                break;
+                            }
+                        }
+                    }
+                    dir_changed || extra_path_changed
                 }
+            };
+            if changed && ctx.changed_by_excluded_commits_only.contains(&package.name) {
+                if verbose {
+                    log::info!(
+                        "Ignoring changes of {} since {} as they were all made by excluded commits",
+                        package.name,
+                        version_tag_name
+                    );
+                }
+                false
+            } else {
+                changed
             }
         }
         None => true,
     })
 }
 
+/// Return the name of the tag of the highest version of `package` below its current one along with the commit it points
+/// to, or `None` if there is no such tag.
+fn previous_release_tag(package: &Package, ctx: &crate::Context) -> anyhow::Result<Option<(String, ObjectId)>> {
+    let prefix = tag_prefix(package, &ctx.repo);
+    let mut previous: Option<(Version, String, ObjectId)> = None;
+    for tag in ctx
+        .repo
+        .references()?
+        .prefixed("refs/tags/")?
+        .peeled()
+        .filter_map(|r| r.ok().map(|r| r.detach()))
+    {
+        let name = match tag.name.as_bstr().strip_prefix(b"refs/tags/") {
+            Some(name) => name.as_bstr(),
+            None => continue,
+        };
+        let (version, target) = match (parse_tag_version(prefix, name), tag.peeled) {
+            (Some(version), Some(target)) if version < package.version => (version, target),
+            _ => continue,
+        };
+        match &previous {
+            Some((previous_version, _, _)) if *previous_version >= version => {}
+            _ => previous = Some((version, name.to_str_lossy().into_owned(), target)),
+        }
+    }
+    Ok(previous.map(|(_, name, target)| (name, target)))
+}
+
+/// Return true if the only difference of `package` between the commit `released` and `HEAD` is its version in its
+/// manifest, ignoring the lock file for crates at the root of the repository as it changes along with the version.
+fn changes_are_version_only(ctx: &crate::Context, package: &Package, released: ObjectId) -> anyhow::Result<bool> {
+    let current = match head(ctx)?.into_fully_peeled_id() {
+        Some(id) => id?.detach(),
+        None => return Ok(false),
+    };
+    let dir = ctx.repo_relative_path(package);
+    let mut changed_files = Vec::new();
+    collect_changed_files(
+        ctx,
+        crate_tree_id(ctx, dir, released)?,
+        crate_tree_id(ctx, dir, current)?,
+        "",
+        &mut changed_files,
+    )?;
+    for (path, released, current) in changed_files {
+        let is_version_only_change = match (path.as_str(), released, current) {
+            ("Cargo.lock", _, _) => dir.is_none(),
+            ("Cargo.toml", Some(released), Some(current)) => {
+                // Objects borrow a shared buffer, so only one of them can be alive at a time.
+                let released = without_version(&ctx.repo.find_object(released)?.data)?;
+                released == without_version(&ctx.repo.find_object(current)?.data)?
+            }
+            _ => false,
+        };
+        if !is_version_only_change {
+            return Ok(false);
+        }
+    }
+    for path in ctx.extra_repo_relative_paths(package)? {
+        if path_entry_id(&released.attach(&ctx.repo), &path)? != path_entry_id(&current.attach(&ctx.repo), &path)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Return the paths of the files of `package` matching its `breaking-paths` which changed between its latest release and
+/// `HEAD`, relative to its directory, along with the name of the tag of that release.
+///
+/// Return `None` if it has no `breaking-paths` or was never released.
+pub fn changed_breaking_paths(
+    package: &Package,
+    ctx: &crate::Context,
+) -> anyhow::Result<Option<(String, Vec<String>)>> {
+    let breaking_paths = crate::config::package(package)?
+        .breaking_paths
+        .iter()
+        .map(|glob| {
+            glob::Pattern::new(glob).map_err(|err| {
+                anyhow!(
+                    "Invalid glob '{}' in 'breaking-paths' of '{}': {}",
+                    glob,
+                    package.name,
+                    err
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if breaking_paths.is_empty() {
+        return Ok(None);
+    }
+    let version_tag_name = tag_name(package, &package.version.to_string(), &ctx.repo);
+    let (released_tag_name, released) = match ctx.repo.try_find_reference(&version_tag_name)? {
+        Some(mut tag_ref) => (version_tag_name, tag_ref.peel_to_id_in_place()?.detach()),
+        None => match previous_release_tag(package, ctx)? {
+            Some(previous) => previous,
+            None => return Ok(None),
+        },
+    };
+    let current = match head(ctx)?.into_fully_peeled_id() {
+        Some(id) => id?.detach(),
+        None => return Ok(None),
+    };
+    let dir = ctx.repo_relative_path(package);
+    let mut changed_files = Vec::new();
+    collect_changed_files(
+        ctx,
+        crate_tree_id(ctx, dir, released)?,
+        crate_tree_id(ctx, dir, current)?,
+        "",
+        &mut changed_files,
+    )?;
+    Ok(Some((
+        released_tag_name,
+        changed_files
+            .into_iter()
+            .map(|(path, _, _)| path)
+            .filter(|path| breaking_paths.iter().any(|glob| glob.matches(path)))
+            .collect(),
+    )))
+}
+
+/// Return the id of the tree at `dir` in `commit`, or of its root tree if `dir` is `None`.
+fn crate_tree_id(ctx: &crate::Context, dir: Option<&Utf8Path>, commit: ObjectId) -> anyhow::Result<Option<ObjectId>> {
+    let tree = commit
+        .attach(&ctx.repo)
+        .object()?
+        .peel_to_kind(object::Kind::Tree)?
+        .into_tree();
+    Ok(match dir {
+        None => Some(tree.id),
+        Some(dir) => tree
+            .lookup_path(dir.components().map(component_to_bytes))?
+            .map(|e| e.oid),
+    })
+}
+
+/// Parse `manifest` and remove the version of its package.
+fn without_version(manifest: &[u8]) -> anyhow::Result<toml::Value> {
+    let mut manifest: toml::Value = toml::from_slice(manifest)?;
+    if let Some(package) = manifest.get_mut("package").and_then(toml::Value::as_table_mut) {
+        package.remove("version");
+    }
+    Ok(manifest)
+}
+
+/// Return true if submodule updates are configured to be ignored and the only differences between the trees `released`
+/// and `current` of `package` are the commits of submodules.
+fn submodule_updates_are_ignored(
+    ctx: &crate::Context,
+    package: &Package,
+    version_tag_name: &str,
+    released: TreeEntry,
+    current: TreeEntry,
+    verbose: bool,
+) -> anyhow::Result<bool> {
+    if ctx.config.submodule_updates != SubmoduleUpdates::Ignore
+        || !only_submodules_differ(&ctx.repo, Some(released), Some(current))?
+    {
+        return Ok(false);
+    }
+    if verbose {
+        log::info!(
+            "Ignoring changes of {} since {} as they only update submodules",
+            package.name,
+            version_tag_name
+        );
+    }
+    Ok(true)
+}
+
+/// An entry of a tree, with the id of the object it points to and its mode.
+pub type TreeEntry = (ObjectId, tree::EntryMode);
+
+/// Return true if the only differences between the tree entries `a` and `b`, if present, are the commits of submodules,
+/// comparing trees recursively.
+pub fn only_submodules_differ(
+    repo: &git_repository::Easy,
+    a: Option<TreeEntry>,
+    b: Option<TreeEntry>,
+) -> anyhow::Result<bool> {
+    use tree::EntryMode::{Commit, Tree};
+    if a == b {
+        return Ok(true);
+    }
+    // Objects borrow a shared buffer, so only one of them can be alive at a time.
+    let tree_data = |entry: Option<TreeEntry>| -> anyhow::Result<Vec<u8>> {
+        Ok(match entry {
+            Some((id, _)) => repo.find_object(id)?.data.to_owned(),
+            None => Vec::new(),
+        })
+    };
+    match (a, b) {
+        (Some((_, Commit)) | None, Some((_, Commit)) | None) => Ok(true),
+        (Some((_, Tree)) | None, Some((_, Tree)) | None) => {
+            only_submodules_differ_in_trees(repo, &tree_data(a)?, &tree_data(b)?)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Return true if the only differences between the trees encoded as `a` and `b` are the commits of submodules, comparing
+/// trees recursively.
+fn only_submodules_differ_in_trees(repo: &git_repository::Easy, a: &[u8], b: &[u8]) -> anyhow::Result<bool> {
+    let entries = |data| -> BTreeMap<Vec<u8>, TreeEntry> {
+        TreeRefIter::from_bytes(data)
+            .filter_map(Result::ok)
+            .map(|e| (e.filename.to_vec(), (e.oid.to_owned(), e.mode)))
+            .collect()
+    };
+    let (a, b) = (entries(a), entries(b));
+    for name in a.keys().chain(b.keys()).collect::<BTreeSet<_>>() {
+        if !only_submodules_differ(repo, a.get(name).copied(), b.get(name).copied())? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Return true if `dev-paths` are configured and all files differing between the crate directory trees `released` and
+/// `current` match them, or are manifests differing only in their dev-dependencies.
+fn changes_are_dev_only(ctx: &crate::Context, released: ObjectId, current: ObjectId) -> anyhow::Result<bool> {
+    if ctx.config.dev_paths.is_empty() {
+        return Ok(false);
+    }
+    let dev_paths = ctx
+        .config
+        .dev_paths
+        .iter()
+        .map(|glob| glob::Pattern::new(glob).map_err(|err| anyhow!("Invalid glob '{}' in 'dev-paths': {}", glob, err)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut changed_files = Vec::new();
+    collect_changed_files(ctx, Some(released), Some(current), "", &mut changed_files)?;
+    for (path, released, current) in changed_files {
+        if dev_paths.iter().any(|glob| glob.matches(&path)) {
+            continue;
+        }
+        let is_dev_only_manifest_change = match (path.as_str(), released, current) {
+            ("Cargo.toml", Some(released), Some(current)) => {
+                // Objects borrow a shared buffer, so only one of them can be alive at a time.
+                let released = without_dev_dependencies(&ctx.repo.find_object(released)?.data)?;
+                released == without_dev_dependencies(&ctx.repo.find_object(current)?.data)?
+            }
+            _ => false,
+        };
+        if !is_dev_only_manifest_change {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Collect the paths of all files which differ between the trees `released` and `current` into `out`, prefixed with
+/// `prefix`, along with their ids in either tree.
+fn collect_changed_files(
+    ctx: &crate::Context,
+    released: Option<ObjectId>,
+    current: Option<ObjectId>,
+    prefix: &str,
+    out: &mut Vec<(String, Option<ObjectId>, Option<ObjectId>)>,
+) -> anyhow::Result<()> {
+    let entries = |tree: Option<ObjectId>| -> anyhow::Result<BTreeMap<String, (ObjectId, bool)>> {
+        let tree = match tree {
+            Some(tree) => ctx.repo.find_object(tree)?,
+            None => return Ok(Default::default()),
+        };
+        Ok(TreeRefIter::from_bytes(&tree.data)
+            .filter_map(Result::ok)
+            .map(|e| {
+                (
+                    e.filename.to_str_lossy().into_owned(),
+                    (e.oid.to_owned(), e.mode == tree::EntryMode::Tree),
+                )
+            })
+            .collect())
+    };
+    let (released, current) = (entries(released)?, entries(current)?);
+    for name in released.keys().chain(current.keys()).collect::<BTreeSet<_>>() {
+        let (released, current) = (released.get(name), current.get(name));
+        if released == current {
+            continue;
+        }
+        let path = format!("{}{}", prefix, name);
+        let subtree = |entry: Option<&(ObjectId, bool)>| entry.filter(|(_, is_tree)| *is_tree).map(|(id, _)| *id);
+        let file = |entry: Option<&(ObjectId, bool)>| entry.filter(|(_, is_tree)| !*is_tree).map(|(id, _)| *id);
+        if subtree(released).is_some() || subtree(current).is_some() {
+            collect_changed_files(ctx, subtree(released), subtree(current), &format!("{}/", path), out)?;
+        }
+        if file(released).is_some() || file(current).is_some() {
+            out.push((path, file(released), file(current)));
+        }
+    }
+    Ok(())
+}
+
+/// Parse `manifest` and remove all of its dev-dependencies, including target-specific ones.
+fn without_dev_dependencies(manifest: &[u8]) -> anyhow::Result<toml::Value> {
+    let mut manifest: toml::Value = toml::from_slice(manifest)?;
+    if let Some(table) = manifest.as_table_mut() {
+        for key in ["dev-dependencies", "dev_dependencies"] {
+            table.remove(key);
+        }
+        if let Some(targets) = table.get_mut("target").and_then(toml::Value::as_table_mut) {
+            let mut emptied_targets = Vec::new();
+            for (name, target) in targets.iter_mut() {
+                let target = match target.as_table_mut() {
+                    Some(target) => target,
+                    None => continue,
+                };
+                for key in ["dev-dependencies", "dev_dependencies"] {
+                    target.remove(key);
+                }
+                if target.is_empty() {
+                    emptied_targets.push(name.clone());
+                }
+            }
+            for name in emptied_targets {
+                targets.remove(&name);
+            }
+            if targets.is_empty() {
+                table.remove("target");
+            }
+        }
+    }
+    Ok(manifest)
+}
+
+fn path_entry_id(commit_id: &Oid<'_>, path: &Utf8Path) -> anyhow::Result<Option<ObjectId>> {
+    Ok(commit_id
+        .object()?
+        .peel_to_kind(object::Kind::Tree)?
+        .into_tree()
+        .lookup_path(path.components().map(component_to_bytes))?
+        .map(|e| e.oid))
+}
+
+/// Return the value of the git configuration `key`, or `None` if it isn't set or empty.
+fn config_value(key: &str) -> anyhow::Result<Option<String>> {
+    let output = Command::new("git").arg("config").arg("--get").arg(key).output()?;
+    let value = output.stdout.trim();
+    if !output.status.success() || value.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(value.to_str()?.to_owned()))
+}
+
+/// Return the key configured in `user.signingkey` to sign commits and tags with, or `None` if `git` should derive it
+/// from the committer identity.
+pub fn signing_key() -> anyhow::Result<Option<String>> {
+    config_value("user.signingkey")
+}
+
+/// The branch on a remote to push the current branch to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushTarget {
+    /// The name of the remote, like `origin`.
+    pub remote: String,
+    /// The full name of the branch on the remote, like `refs/heads/main`, or `None` if `HEAD` isn't on a branch.
+    pub branch: Option<String>,
+}
+
+/// Return the upstream configured for the current branch in `branch.<name>.remote` and `branch.<name>.merge`, or
+/// the default branch of `origin` with a warning if there is none.
+///
+/// If `HEAD` isn't on a branch, return `origin` without a branch to push to.
+pub fn push_target(ctx: &crate::Context) -> anyhow::Result<PushTarget> {
+    let head = head(ctx)?;
+    let branch = match head
+        .referent_name()
+        .and_then(|name| name.as_bstr().strip_prefix(b"refs/heads/"))
+    {
+        Some(name) => name.to_str()?.to_owned(),
+        None => {
+            return Ok(PushTarget {
+                remote: "origin".into(),
+                branch: None,
+            })
+        }
+    };
+    let config = repo_config(ctx)?;
+    if let (Some(remote), Some(merge)) = (
+        repo_config_value(&config, "branch", Some(&branch), "remote")?,
+        repo_config_value(&config, "branch", Some(&branch), "merge")?,
+    ) {
+        return Ok(PushTarget {
+            remote,
+            branch: Some(merge),
+        });
+    }
+
+    let remote = "origin";
+    let target_branch = match default_branch(ctx, remote)? {
+        Some(default_branch) => {
+            log::warn!(
+                "Branch '{}' has no upstream configured - pushing to the default branch '{}' of '{}' instead",
+                branch,
+                default_branch.strip_prefix("refs/heads/").unwrap_or(&default_branch),
+                remote
+            );
+            default_branch
+        }
+        None => {
+            log::warn!(
+                "Branch '{}' has no upstream configured and the default branch of '{}' is unknown - pushing to '{}'",
+                branch,
+                remote,
+                branch
+            );
+            format!("refs/heads/{}", branch)
+        }
+    };
+    Ok(PushTarget {
+        remote: remote.into(),
+        branch: Some(target_branch),
+    })
+}
+
+/// Return the full name of the default branch of `remote` as known locally through `refs/remotes/<remote>/HEAD`.
+fn default_branch(ctx: &crate::Context, remote: &str) -> anyhow::Result<Option<String>> {
+    let prefix = format!("refs/remotes/{}/", remote);
+    let remote_head = match ctx.repo.try_find_reference(format!("{}HEAD", prefix).as_str())? {
+        Some(remote_head) => remote_head,
+        None => return Ok(None),
+    };
+    Ok(match remote_head.target() {
+        TargetRef::Symbolic(name) => name
+            .as_bstr()
+            .to_str()?
+            .strip_prefix(&prefix)
+            .map(|name| format!("refs/heads/{}", name)),
+        TargetRef::Peeled(_) => None,
+    })
+}
+
+/// Read the configuration of the repository of `ctx`, without the configuration of the user or the system.
+fn repo_config(ctx: &crate::Context) -> anyhow::Result<git_config::file::GitConfig<'static>> {
+    Ok(git_config::file::GitConfig::open(
+        ctx.repo.repo.git_dir().join("config"),
+    )?)
+}
+
+/// Return the value of `key` in `section` and `subsection` of `config`, or `None` if it isn't set or empty.
+fn repo_config_value(
+    config: &git_config::file::GitConfig<'_>,
+    section: &str,
+    subsection: Option<&str>,
+    key: &str,
+) -> anyhow::Result<Option<String>> {
+    match config.value::<std::borrow::Cow<'_, [u8]>>(section, subsection, key) {
+        Ok(value) if !value.is_empty() => Ok(Some(value.to_str()?.to_owned())),
+        _ => Ok(None),
+    }
+}
+
+/// Resolve `rev`, like `v1.0.0` or `main~2`, to the id of the commit it points to.
+pub fn resolve_commit(ctx: &crate::Context, rev: &str) -> anyhow::Result<ObjectId> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("--verify")
+        .arg("--quiet")
+        .arg(format!("{}^{{commit}}", rev))
+        .current_dir(&ctx.root)
+        .output()?;
+    if !output.status.success() {
+        bail!("Could not resolve '{}' to a commit", rev);
+    }
+    Ok(ObjectId::from_hex(output.stdout.trim())?)
+}
+
+/// Return true if `ancestor` is reachable from `descendant`, or if both are the same commit.
+pub fn is_ancestor(ctx: &crate::Context, ancestor: &ObjectId, descendant: &ObjectId) -> anyhow::Result<bool> {
+    let output = Command::new("git")
+        .arg("merge-base")
+        .arg("--is-ancestor")
+        .arg(ancestor.to_string())
+        .arg(descendant.to_string())
+        .current_dir(&ctx.root)
+        .output()?;
+    match output.status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => bail!(
+            "Could not determine if {} is an ancestor of {}: {}",
+            ancestor,
+            descendant,
+            output.stderr.to_str_lossy().trim()
+        ),
+    }
+}
+
+/// Return the URL of `remote`, like `origin`, if it has one.
+pub fn remote_url(ctx: &crate::Context, remote: &str) -> anyhow::Result<Option<git_url::Url>> {
+    repo_config_value(&repo_config(ctx)?, "remote", Some(remote), "url")?
+        .map(|url| git_url::parse(url.as_bytes()).map_err(Into::into))
+        .transpose()
+}
+
+/// Return the names of all configured remotes, like `origin`, in the order they are configured in.
+pub fn remotes(ctx: &crate::Context) -> anyhow::Result<Vec<String>> {
+    let config = git_config::parser::parse_from_path(ctx.repo.repo.git_dir().join("config"))?;
+    let mut remotes = Vec::<String>::new();
+    for header in config.sections().iter().map(|section| &section.section_header) {
+        match &header.subsection_name {
+            Some(name) if header.name.eq_ignore_ascii_case("remote") && !remotes.iter().any(|r| r == name) => {
+                remotes.push(name.to_string())
+            }
+            _ => {}
+        }
+    }
+    Ok(remotes)
+}
+
+/// Return the stable patch id of each of the commits with the given `ids`, which is the same for commits with the same
+/// changes, as computed by `git patch-id --stable`. Commits without changes, like merges, have none.
+pub fn patch_ids(ctx: &crate::Context, ids: &[ObjectId]) -> anyhow::Result<BTreeMap<ObjectId, String>> {
+    if ids.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let mut log = Command::new("git")
+        .args([
+            "log",
+            "--no-walk=unsorted",
+            "--stdin",
+            "--patch",
+            "--no-color",
+            "--format=commit %H",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .current_dir(&ctx.root)
+        .spawn()?;
+    let patch_id = Command::new("git")
+        .args(["patch-id", "--stable"])
+        .stdin(log.stdout.take().expect("stdout is piped"))
+        .stdout(Stdio::piped())
+        .current_dir(&ctx.root)
+        .spawn()?;
+    {
+        let mut stdin = log.stdin.take().expect("stdin is piped");
+        for id in ids {
+            writeln!(stdin, "{}", id)?;
+        }
+    }
+    let output = patch_id.wait_with_output()?;
+    if !log.wait()?.success() || !output.status.success() {
+        bail!("Could not compute the patch ids of {} commit(s)", ids.len());
+    }
+    output
+        .stdout
+        .lines()
+        .filter_map(|line| line.to_str().ok()?.split_once(' '))
+        .map(|(patch_id, id)| Ok((ObjectId::from_hex(id.as_bytes())?, patch_id.to_owned())))
+        .collect()
+}
+
 pub fn assure_clean_working_tree() -> anyhow::Result<()> {
     let tracked_changed = !Command::new("git")
         .arg("diff")
@@ -78,3 +731,230 @@ pub fn assure_clean_working_tree() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use git_repository::hash::ObjectId;
+
+    use super::{
+        changed_breaking_paths, changes_are_dev_only, collect_changed_files, has_changed_since_last_release,
+        is_ancestor, push_target, resolve_commit, without_dev_dependencies, without_version, PushTarget,
+    };
+    use crate::fixture::Workspace;
+    use crate::utils::package_by_name;
+
+    #[test]
+    fn commits_are_resolved_and_compared() {
+        let ws = Workspace::tri_depth("resolve-commit");
+        let first = ws.git(&["rev-parse", "HEAD"]);
+        ws.commit("second");
+        ws.git(&["tag", "v1.0.0"]);
+        let ctx = ws.context(&["a"]);
+
+        let first = resolve_commit(&ctx, &first).unwrap();
+        let head = resolve_commit(&ctx, "HEAD").unwrap();
+        assert_eq!(
+            resolve_commit(&ctx, "v1.0.0").unwrap(),
+            head,
+            "tags are peeled to commits"
+        );
+        assert_eq!(resolve_commit(&ctx, "HEAD~1").unwrap(), first);
+        assert_eq!(
+            resolve_commit(&ctx, "does-not-exist").unwrap_err().to_string(),
+            "Could not resolve 'does-not-exist' to a commit"
+        );
+
+        assert!(is_ancestor(&ctx, &first, &head).unwrap());
+        assert!(is_ancestor(&ctx, &head, &head).unwrap());
+        assert!(!is_ancestor(&ctx, &head, &first).unwrap());
+        let unknown = ObjectId::from_hex(b"0123456789abcdef0123456789abcdef01234567").unwrap();
+        assert!(
+            is_ancestor(&ctx, &unknown, &head)
+                .unwrap_err()
+                .to_string()
+                .starts_with("Could not determine if 0123456789abcdef0123456789abcdef01234567 is an ancestor of"),
+            "errors of git aren't mistaken for commits which aren't ancestors"
+        );
+    }
+
+    #[test]
+    fn dev_only_changes_are_told_apart_from_others() {
+        let ws = Workspace::tri_depth("dev-only-changes");
+        let manifest = std::fs::read_to_string(ws.root.join("a/Cargo.toml")).unwrap();
+        let tree = |ws: &Workspace| ObjectId::from_hex(ws.git(&["rev-parse", "HEAD:a"]).as_bytes()).unwrap();
+        let released = tree(&ws);
+        ws.write("a/src/lib.rs", "// changed\n");
+        std::fs::create_dir_all(ws.root.join("a/tests")).unwrap();
+        ws.write("a/tests/it.rs", "// added\n");
+        ws.commit("change a and add a test");
+        let mut ctx = ws.context(&["a"]);
+
+        let mut changed_files = Vec::new();
+        collect_changed_files(&ctx, Some(released), Some(tree(&ws)), "", &mut changed_files).unwrap();
+        assert_eq!(
+            changed_files
+                .iter()
+                .map(|(path, released, current)| (path.as_str(), released.is_some(), current.is_some()))
+                .collect::<Vec<_>>(),
+            [("src/lib.rs", true, true), ("tests/it.rs", false, true)],
+            "files in subdirectories are listed by their path, with the side they are missing from"
+        );
+
+        assert!(
+            !changes_are_dev_only(&ctx, released, tree(&ws)).unwrap(),
+            "without dev-paths nothing is dev-only"
+        );
+        ctx.config.dev_paths = vec!["tests/**".into()];
+        assert!(!changes_are_dev_only(&ctx, released, tree(&ws)).unwrap());
+
+        let released = tree(&ws);
+        ws.write("a/tests/it.rs", "// changed\n");
+        ws.write(
+            "a/Cargo.toml",
+            &format!("{}\n[dev-dependencies]\nb = \"1\"\n", manifest),
+        );
+        ws.commit("change the test and add a dev-dependency");
+        assert!(changes_are_dev_only(&ctx, released, tree(&ws)).unwrap());
+
+        ws.write("a/Cargo.toml", &format!("{}b = \"1\"\n", manifest));
+        ws.commit("add a dependency");
+        assert!(
+            !changes_are_dev_only(&ctx, released, tree(&ws)).unwrap(),
+            "changes to the dependencies count"
+        );
+    }
+
+    #[test]
+    fn changes_of_extra_paths_count_even_if_the_crate_only_changed_for_development() {
+        let ws = Workspace::tri_depth("dev-only-and-extra-paths");
+        ws.configure(
+            "a",
+            "[package.metadata.smart-release.changelog]\nextra-paths = [\"../shared\"]",
+        );
+        std::fs::create_dir_all(ws.root.join("shared")).unwrap();
+        ws.write("shared/file", "released\n");
+        ws.commit("a with extra path");
+        ws.git(&["tag", "a-v0.8.0"]);
+        std::fs::create_dir_all(ws.root.join("a/tests")).unwrap();
+        ws.write("a/tests/it.rs", "// added\n");
+        ws.commit("add a test");
+        let mut ctx = ws.context(&["a"]);
+        ctx.config.dev_paths = vec!["tests/**".into()];
+        let has_changed = |ctx: &crate::Context| {
+            has_changed_since_last_release(package_by_name(&ctx.meta, "a").unwrap(), ctx, false).unwrap()
+        };
+
+        assert!(!has_changed(&ctx), "only the development of a changed");
+        ws.write("shared/file", "changed\n");
+        ws.commit("change the extra path");
+        assert!(
+            has_changed(&ctx),
+            "the extra path changed along with the development of a"
+        );
+    }
+
+    #[test]
+    fn breaking_paths_changed_since_the_latest_release() {
+        let ws = Workspace::tri_depth("changed-breaking-paths");
+        ws.configure("a", "[package.metadata.smart-release]\nbreaking-paths = [\"proto/**\"]");
+        std::fs::create_dir_all(ws.root.join("a/proto")).unwrap();
+        ws.write("a/proto/api.proto", "released\n");
+        ws.commit("a with breaking paths");
+        let changed = |name: &str| {
+            let ctx = ws.context(&[name]);
+            changed_breaking_paths(package_by_name(&ctx.meta, name).unwrap(), &ctx).unwrap()
+        };
+
+        assert_eq!(changed("a"), None, "a was never released");
+        ws.git(&["tag", "a-v0.8.0"]);
+        ws.git(&["tag", "b-v0.8.0"]);
+        assert_eq!(changed("a"), Some(("a-v0.8.0".into(), Vec::new())));
+
+        ws.write("a/proto/api.proto", "changed\n");
+        ws.write("a/src/lib.rs", "// changed\n");
+        ws.write("b/src/lib.rs", "// changed\n");
+        ws.commit("change a and b");
+        assert_eq!(
+            changed("a"),
+            Some(("a-v0.8.0".into(), vec!["proto/api.proto".to_string()])),
+            "only files matching the breaking paths are listed"
+        );
+        assert_eq!(changed("b"), None, "b has no breaking paths");
+
+        let manifest = std::fs::read_to_string(ws.root.join("a/Cargo.toml")).unwrap();
+        ws.write("a/Cargo.toml", &manifest.replacen("0.8.0", "0.8.1", 1));
+        ws.commit("bump a without releasing it");
+        assert_eq!(
+            changed("a"),
+            Some(("a-v0.8.0".into(), vec!["proto/api.proto".to_string()])),
+            "the previous release is used if the manifest version isn't tagged"
+        );
+    }
+
+    #[test]
+    fn push_targets_follow_the_upstream_of_the_current_branch() {
+        let ws = Workspace::tri_depth("push-target");
+        let branch = ws.git(&["symbolic-ref", "--short", "HEAD"]);
+        let ctx = ws.context(&["a"]);
+        let target = |remote: &str, branch: Option<&str>| PushTarget {
+            remote: remote.into(),
+            branch: branch.map(ToOwned::to_owned),
+        };
+
+        assert_eq!(
+            push_target(&ctx).unwrap(),
+            target("origin", Some(&format!("refs/heads/{}", branch))),
+            "without an upstream or default branch the current branch is pushed to origin"
+        );
+
+        ws.git(&["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        ws.git(&["symbolic-ref", "refs/remotes/origin/HEAD", "refs/remotes/origin/main"]);
+        assert_eq!(push_target(&ctx).unwrap(), target("origin", Some("refs/heads/main")));
+
+        ws.git(&["config", &format!("branch.{}.remote", branch), "upstream"]);
+        ws.git(&["config", &format!("branch.{}.merge", branch), "refs/heads/release"]);
+        assert_eq!(
+            push_target(&ctx).unwrap(),
+            target("upstream", Some("refs/heads/release"))
+        );
+
+        ws.git(&["checkout", "-q", "--detach"]);
+        assert_eq!(
+            push_target(&ctx).unwrap(),
+            target("origin", None),
+            "tags can still be pushed without a branch"
+        );
+    }
+
+    #[test]
+    fn manifests_are_compared_without_dev_dependencies() {
+        let manifest = |extra: &str| {
+            without_dev_dependencies(
+                format!("[package]\nname = \"a\"\n\n[dependencies]\nb = \"1\"\n{}", extra).as_bytes(),
+            )
+            .unwrap()
+        };
+        assert_eq!(manifest(""), manifest("\n[dev-dependencies]\nc = \"1\"\n"));
+        assert_eq!(
+            manifest(""),
+            manifest("\n[target.'cfg(unix)'.dev-dependencies]\nc = \"1\"\n")
+        );
+        assert_ne!(manifest(""), manifest("c = \"1\"\n"));
+    }
+
+    #[test]
+    fn manifests_are_compared_without_their_version() {
+        let manifest = |version: &str, dependency: &str| {
+            without_version(
+                format!(
+                    "[package]\nname = \"a\"\nversion = \"{}\"\n\n[dependencies]\nb = \"{}\"\n",
+                    version, dependency
+                )
+                .as_bytes(),
+            )
+            .unwrap()
+        };
+        assert_eq!(manifest("1.0.0", "1"), manifest("1.0.1", "1"));
+        assert_ne!(manifest("1.0.0", "1"), manifest("1.0.1", "2"));
+    }
+}
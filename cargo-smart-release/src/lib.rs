@@ -1,7 +1,11 @@
 pub use context::Context;
 
+pub mod changelog;
 pub mod command;
+pub mod config;
 mod context;
+#[cfg(test)]
+mod fixture;
 pub mod git;
 pub mod traverse;
 mod utils;
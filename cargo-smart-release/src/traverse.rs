@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, fmt};
 
 use cargo_metadata::{DependencyKind, Metadata, Package};
 
@@ -7,9 +7,81 @@ use crate::{
     utils::{is_pre_release_version, is_workspace_member, package_by_name},
 };
 
-pub fn dependencies(ctx: &crate::Context, verbose: bool, add_production_crates: bool) -> anyhow::Result<Vec<String>> {
+/// A crate which was left out of a run, collected so all of them can be summarized at the end of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    pub crate_name: String,
+    pub reason: SkipReason,
+}
+
+/// The reason for a crate to be [`Skipped`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The crate didn't change since its last release.
+    Unchanged,
+    /// The crate is configured with `publish = false`.
+    Unpublishable,
+    /// The crate was excluded with `--exclude`.
+    Excluded,
+    /// The crate changed since its last release, but as it has a stable version it isn't released as dependency
+    /// automatically.
+    StableDependency,
+    /// None of the workspace dependencies of the crate are released, and `--bump-dependencies-only` ignores its own changes.
+    NoReleasedDependencies,
+}
+
+impl Skipped {
+    pub fn new(crate_name: impl Into<String>, reason: SkipReason) -> Self {
+        Skipped {
+            crate_name: crate_name.into(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SkipReason::Unchanged => "no changes since its last release",
+            SkipReason::Unpublishable => "configured with 'publish = false'",
+            SkipReason::Excluded => "excluded with --exclude",
+            SkipReason::StableDependency => {
+                "changed since its last release, but stable crates aren't released as dependency automatically"
+            }
+            SkipReason::NoReleasedDependencies => {
+                "none of its workspace dependencies are released and --bump-dependencies-only ignores its own changes"
+            }
+        })
+    }
+}
+
+/// Log all `skipped` crates together along with the reason they were skipped for, if there are any, listing each crate
+/// only once and leaving out those in `processed` as they were handled after all.
+pub fn log_skipped(skipped: &[Skipped], processed: &[String]) {
+    let mut seen = BTreeSet::new();
+    let skipped: Vec<_> = skipped
+        .iter()
+        .filter(|s| !processed.contains(&s.crate_name) && seen.insert(s.crate_name.as_str()))
+        .collect();
+    if skipped.is_empty() {
+        return;
+    }
+    log::warn!("Skipped {} crate(s) in this run:", skipped.len());
+    for s in skipped {
+        log::warn!("  {}: {}", s.crate_name, s.reason);
+    }
+}
+
+/// Return the names of all crates of `ctx` and their changed workspace dependencies in the order they are to be
+/// released, along with all crates which were skipped in the process.
+pub fn dependencies(
+    ctx: &crate::Context,
+    verbose: bool,
+    add_production_crates: bool,
+) -> anyhow::Result<(Vec<String>, Vec<Skipped>)> {
     let mut seen = BTreeSet::new();
     let mut changed_crate_names_to_publish = Vec::new();
+    let mut skipped_crates = Vec::new();
     for crate_name in &ctx.crate_names {
         if seen.contains(crate_name) {
             continue;
@@ -26,6 +98,7 @@ pub fn dependencies(ctx: &crate::Context, verbose: bool, add_production_crates:
             add_production_crates,
             &mut seen,
             &mut changed_crate_names_to_publish,
+            &mut skipped_crates,
             package,
             verbose,
         )?;
@@ -43,13 +116,14 @@ pub fn dependencies(ctx: &crate::Context, verbose: bool, add_production_crates:
                     crate_package.name,
                     crate_package.version
                 );
+                skipped_crates.push(Skipped::new(crate_name, SkipReason::Unchanged));
                 continue;
             }
         }
         changed_crate_names_to_publish.push(crate_name.to_owned());
         seen.insert(crate_name.to_owned());
     }
-    Ok(changed_crate_names_to_publish)
+    Ok((changed_crate_names_to_publish, skipped_crates))
 }
 
 fn depth_first_traversal(
@@ -57,6 +131,7 @@ fn depth_first_traversal(
     add_production_crates: bool,
     seen: &mut BTreeSet<String>,
     changed_crate_names_to_publish: &mut Vec<String>,
+    skipped_crates: &mut Vec<Skipped>,
     package: &Package,
     verbose: bool,
 ) -> anyhow::Result<usize> {
@@ -72,6 +147,7 @@ fn depth_first_traversal(
             add_production_crates,
             seen,
             changed_crate_names_to_publish,
+            skipped_crates,
             dep_package,
             verbose,
         )?;
@@ -91,6 +167,7 @@ fn depth_first_traversal(
                     dep_package.name,
                     dep_package.version
                 );
+                skipped_crates.push(Skipped::new(&dependency.name, SkipReason::StableDependency));
             }
         } else {
             if verbose {
@@ -101,12 +178,13 @@ fn depth_first_traversal(
                 );
             }
             skipped += 1;
+            skipped_crates.push(Skipped::new(&dependency.name, SkipReason::Unchanged));
         }
     }
     Ok(skipped)
 }
 
-fn dependency_tree_has_link_to_existing_crate_names(
+pub(crate) fn dependency_tree_has_link_to_existing_crate_names(
     meta: &Metadata,
     root_name: &str,
     existing_names: &[String],
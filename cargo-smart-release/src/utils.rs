@@ -43,6 +43,18 @@ pub fn workspace_package_by_name<'a>(meta: &'a Metadata, crate_name: &str) -> Op
         .filter(|p| meta.workspace_members.iter().any(|m| m == &p.id))
 }
 
+/// Return the workspace member whose manifest is at `manifest_path`, comparing canonical paths.
+pub fn workspace_package_by_manifest_path<'a>(
+    meta: &'a Metadata,
+    manifest_path: &std::path::Path,
+) -> Option<&'a Package> {
+    let manifest_path = manifest_path.canonicalize().ok()?;
+    meta.packages
+        .iter()
+        .filter(|p| meta.workspace_members.iter().any(|m| m == &p.id))
+        .find(|p| matches!(p.manifest_path.canonicalize(), Ok(member_path) if member_path == manifest_path))
+}
+
 pub fn workspace_package_by_id<'a>(meta: &'a Metadata, id: &PackageId) -> Option<&'a Package> {
     meta.packages
         .iter()
@@ -79,6 +91,34 @@ pub fn package_by_id<'a>(meta: &'a Metadata, id: &PackageId) -> &'a Package {
         .expect("workspace members are in packages")
 }
 
+/// Return true if `package` may be published, that is it doesn't have `publish = false` in its manifest.
+pub fn is_publishable(package: &Package) -> bool {
+    !matches!(package.publish.as_deref(), Some([]))
+}
+
+/// Return `date_today` if set, or the day of the `SOURCE_DATE_EPOCH` environment variable for reproducible builds,
+/// or the current day, all in UTC.
+pub fn today(date_today: Option<time::Date>) -> anyhow::Result<time::Date> {
+    if let Some(date) = date_today {
+        return Ok(date);
+    }
+    match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => date_from_source_epoch(&epoch),
+        Err(_) => Ok(time::OffsetDateTime::now_utc().date()),
+    }
+}
+
+/// Return the day in UTC of `epoch`, the value of the `SOURCE_DATE_EPOCH` environment variable.
+fn date_from_source_epoch(epoch: &str) -> anyhow::Result<time::Date> {
+    let seconds: i64 = epoch.trim().parse().map_err(|_| {
+        anyhow::anyhow!(
+            "SOURCE_DATE_EPOCH must be seconds since the unix epoch, got '{}'",
+            epoch
+        )
+    })?;
+    Ok(time::OffsetDateTime::from_unix_timestamp(seconds)?.date())
+}
+
 pub fn tag_prefix<'p>(package: &'p Package, repo: &git::Easy) -> Option<&'p str> {
     if is_top_level_package(&package.manifest_path, repo) {
         None
@@ -109,9 +149,26 @@ pub fn is_tag_name(package_name: &str, tag_name: &git::bstr::BStr) -> bool {
     }
 }
 
+/// Return true if `name` looks like a version prefixed with `v`, or is a valid semantic version without prefix, like
+/// `1.2.0-rc.1`, as other tools create it.
 pub fn is_tag_version(name: &git::bstr::BStr) -> bool {
     use git::bstr::ByteSlice;
-    name.starts_with_str(b"v") && name.split_str(b".").count() >= 3
+    (name.starts_with_str(b"v") && name.split_str(b".").count() >= 3)
+        || name.to_str().ok().and_then(|name| Version::parse(name).ok()).is_some()
+}
+
+/// Parse the version of `tag_name`, which is expected to be prefixed with `package_name` as per [`tag_name()`] if set.
+///
+/// The `v` before the version is optional, and pre-release and build metadata are supported.
+pub fn parse_tag_version(package_name: Option<&str>, tag_name: &git::bstr::BStr) -> Option<Version> {
+    use git::bstr::ByteSlice;
+    let version = match package_name {
+        Some(name) => tag_name
+            .strip_prefix(name.as_bytes())
+            .and_then(|r| r.strip_prefix(b"-"))?,
+        None => tag_name.as_bytes(),
+    };
+    Version::parse(version.strip_prefix(b"v").unwrap_or(version).to_str().ok()?).ok()
 }
 
 pub fn component_to_bytes(c: Utf8Component<'_>) -> &[u8] {
@@ -123,6 +180,34 @@ pub fn component_to_bytes(c: Utf8Component<'_>) -> &[u8] {
 
 #[cfg(test)]
 mod tests {
+    mod today {
+        use time::{Date, Month};
+
+        use crate::utils::{date_from_source_epoch, today};
+
+        #[test]
+        fn a_given_date_takes_precedence() {
+            let given = Date::from_calendar_date(2021, Month::September, 15).unwrap();
+            assert_eq!(today(Some(given)).unwrap(), given);
+        }
+
+        #[test]
+        fn source_date_epoch_is_seconds_since_the_unix_epoch() {
+            assert_eq!(
+                date_from_source_epoch("1631750400").unwrap(),
+                Date::from_calendar_date(2021, Month::September, 16).unwrap()
+            );
+            assert_eq!(
+                date_from_source_epoch(" 1631750400\n").unwrap(),
+                Date::from_calendar_date(2021, Month::September, 16).unwrap(),
+                "surrounding whitespace is ignored"
+            );
+            assert_eq!(
+                date_from_source_epoch("yesterday").unwrap_err().to_string(),
+                "SOURCE_DATE_EPOCH must be seconds since the unix epoch, got 'yesterday'"
+            );
+        }
+    }
     mod is_tag_name {
         mod no_match {
             use git_repository::bstr::ByteSlice;
@@ -136,6 +221,11 @@ mod tests {
                     tag_name_inner("bar".into(), "0.0.1-beta.1").as_bytes().as_bstr()
                 ));
             }
+
+            #[test]
+            fn due_to_crate_name_with_common_prefix_and_without_v() {
+                assert!(!is_tag_name("foo", b"foo-bar-1.2.0".as_bstr()));
+            }
         }
         mod matches {
             use git_repository::bstr::ByteSlice;
@@ -154,6 +244,48 @@ mod tests {
                     tag_name_inner("single".into(), "0.0.1-beta.1").as_bytes().as_bstr()
                 ));
             }
+
+            #[test]
+            fn versions_without_v() {
+                assert!(is_tag_name("foo", b"foo-1.2.0-rc.1".as_bstr()));
+                assert!(is_tag_name("foo", b"foo-1.2.0+build.5".as_bstr()));
+            }
+        }
+    }
+    mod parse_tag_version {
+        use git_repository::bstr::ByteSlice;
+        use semver::Version;
+
+        use crate::utils::{parse_tag_version, tag_name_inner};
+
+        #[test]
+        fn with_and_without_package_name() {
+            assert_eq!(
+                parse_tag_version("a".into(), tag_name_inner("a".into(), "0.8.0").as_bytes().as_bstr()),
+                Some(Version::new(0, 8, 0))
+            );
+            assert_eq!(
+                parse_tag_version(None, tag_name_inner(None, "1.0.1-alpha.1").as_bytes().as_bstr()),
+                Some(Version::parse("1.0.1-alpha.1").unwrap())
+            );
+        }
+
+        #[test]
+        fn without_v() {
+            assert_eq!(
+                parse_tag_version("foo".into(), b"foo-1.2.0-rc.1".as_bstr()),
+                Some(Version::parse("1.2.0-rc.1").unwrap())
+            );
+            assert_eq!(
+                parse_tag_version(None, b"1.0.0+build.5".as_bstr()),
+                Some(Version::parse("1.0.0+build.5").unwrap())
+            );
+        }
+
+        #[test]
+        fn mismatching_package_name_or_invalid_version() {
+            assert_eq!(parse_tag_version("b".into(), b"a-v0.8.0".as_bstr()), None);
+            assert_eq!(parse_tag_version(None, b"vHi.Ho.yada".as_bstr()), None);
         }
     }
     mod is_tag_version {
@@ -36,7 +36,7 @@ title "changelog"
 
   snapshot="$snapshot/triple-depth-workspace-changelog"
   cp -R $fixtures/tri-depth-workspace/* .
-  { echo $'target/\n.package-cache' > .gitignore && init-git-repo; } &>/dev/null
+  { echo $'target/\n.package-cache\n.global-cache' > .gitignore && init-git-repo; } &>/dev/null
 
   (when "interacting with 'a'"
     (with 'dry-run only'
@@ -50,7 +50,7 @@ title "changelog"
         expect_run $SUCCESSFULLY "$exe" changelog a --write
       }
       (with ".git and target/ directories removed"
-        rm -Rf .git/ target/
+        rm -Rf .git/ target/ .global-cache
         it "managed to write a changelog" && {
           expect_snapshot "$snapshot/crate-a-released" .
         }
@@ -116,19 +116,19 @@ title "smart-release"
     )
     (with '--execute but without side-effects'
       it "succeeds" && {
-        expect_run $SUCCESSFULLY "$exe" smart-release a --skip-push --skip-publish --execute --allow-dirty
+        expect_run $SUCCESSFULLY "$exe" smart-release a --skip-push --skip-publish --execute --yes --allow-dirty
       }
       (with ".git and target/ directories removed"
-        rm -Rf .git/ target/
+        rm -Rf .git/ target/ .global-cache
         it "managed to bump B's minor but left C alone as it's not pre-release anymore" && {
           expect_snapshot "$snapshot/crate-a-released" .
         }
         (with 'unconditional version minor bumping'
           init-git-repo &>/dev/null
           it "succeeds" && {
-            expect_run $SUCCESSFULLY "$exe" smart-release -b minor a --skip-push --skip-publish --no-bump-on-demand --execute --allow-dirty
+            expect_run $SUCCESSFULLY "$exe" smart-release -b minor a --skip-push --skip-publish --no-bump-on-demand --execute --yes --allow-dirty
           }
-          rm -Rf .git/
+          rm -Rf .git/ .global-cache
           it "managed additionally bumped b but not c as it's not pre-release" && {
             expect_snapshot "$snapshot/crate-a-released-force-bump" .
           }
@@ -138,3 +138,30 @@ title "smart-release"
   )
 )
 
+
+title "smart-release in a linked worktree"
+(sandbox
+  set-static-git-environment
+  export CARGO_HOME=$PWD
+
+  mkdir main && cd main
+  cp -R $fixtures/tri-depth-workspace/* .
+  { echo 'target/' > .gitignore && init-git-repo && git worktree add ../worktree -b release; } &>/dev/null
+  cd ../worktree
+
+  (when "releasing 'a'"
+    (with 'dry-run only'
+      it "succeeds" && {
+        expect_run $SUCCESSFULLY "$exe" smart-release a --skip-push --skip-publish -v -b minor
+      }
+    )
+    (with '--execute but without side-effects'
+      it "succeeds" && {
+        expect_run $SUCCESSFULLY "$exe" smart-release a --skip-push --skip-publish --execute --yes
+      }
+      it "committed to the branch of the worktree and tagged its HEAD" && {
+        expect_run_sh $SUCCESSFULLY 'test "$(git rev-parse release)" = "$(git rev-parse a-v0.8.0^{commit})" && test "$(git -C ../main rev-parse HEAD)" != "$(git rev-parse release)"'
+      }
+    )
+  )
+)